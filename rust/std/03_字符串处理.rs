@@ -20,6 +20,7 @@ Rust中的字符串系统包含两种主要类型：
 - 正则表达式
 */
 
+use std::collections::HashMap;
 use std::fmt;
 
 fn main() {
@@ -207,6 +208,392 @@ fn string_search_replace() {
     // 查找所有匹配项
     let matches: Vec<_> = text.match_indices("Rust").collect();
     println!("所有'Rust'的位置: {:?}", matches);
+    
+    // 简单正则表达式
+    regex_matching();
+}
+
+fn regex_matching() {
+    let log = "2024-01-15 ERROR 连接超时 (code=504)";
+    match regex::Regex::new("[0-9]+-[0-9]+-[0-9]+") {
+        Ok(re) => {
+            if let Some((start, end)) = re.find(log) {
+                println!("日期: {}", &log[start..end]);
+            }
+        }
+        Err(e) => println!("正则表达式错误: {}", e),
+    }
+    
+    let level = regex::Regex::new("WARN|ERROR").unwrap();
+    println!("是否为告警日志: {}", level.is_match(log));
+    
+    if let Err(e) = regex::Regex::new("code=[0-9") {
+        println!("无效的正则表达式: {}", e);
+    }
+}
+
+// 小型正则表达式引擎：支持字面字符、. * + ? [a-z] [^...] ^ $ | 和 (...) 分组，
+// \ 转义元字符。模式先编译成NFA，再用Thompson算法同时推进所有状态，
+// 不回溯，匹配时间与文本长度成线性关系
+mod regex {
+    use std::fmt;
+    
+    #[derive(Debug, PartialEq)]
+    pub enum RegexError {
+        // [ 没有对应的 ]
+        UnclosedClass(usize),
+        // ( 没有对应的 )
+        UnclosedGroup(usize),
+        // 多余的 )
+        UnmatchedParen(usize),
+        // * + ? 前面没有可重复的内容
+        NothingToRepeat(usize),
+        // 字符类中的范围起点大于终点，如 [z-a]
+        InvalidRange(usize),
+        // 模式以 \ 结尾
+        TrailingBackslash,
+    }
+    
+    impl fmt::Display for RegexError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                RegexError::UnclosedClass(pos) => write!(f, "位置 {} 的 [ 没有闭合", pos),
+                RegexError::UnclosedGroup(pos) => write!(f, "位置 {} 的 ( 没有闭合", pos),
+                RegexError::UnmatchedParen(pos) => write!(f, "位置 {} 有多余的 )", pos),
+                RegexError::NothingToRepeat(pos) => {
+                    write!(f, "位置 {} 的重复符号前没有内容", pos)
+                }
+                RegexError::InvalidRange(pos) => write!(f, "位置 {} 的字符范围无效", pos),
+                RegexError::TrailingBackslash => write!(f, "模式以反斜杠结尾"),
+            }
+        }
+    }
+    
+    impl std::error::Error for RegexError {}
+    
+    // 语法树
+    enum Node {
+        Empty,
+        Char(char),
+        Any,
+        Class { ranges: Vec<(char, char)>, negated: bool },
+        Start,
+        End,
+        Concat(Vec<Node>),
+        Alternate(Vec<Node>),
+        Star(Box<Node>),
+        Plus(Box<Node>),
+        Question(Box<Node>),
+    }
+    
+    // NFA状态，next为下一个状态的下标
+    enum State {
+        Char(char, usize),
+        Any(usize),
+        Class { ranges: Vec<(char, char)>, negated: bool, next: usize },
+        // 不消耗字符，同时转移到两个状态
+        Split(usize, usize),
+        // 断言：只在文本开头/结尾处通过
+        Start(usize),
+        End(usize),
+        Match,
+    }
+    
+    pub struct Regex {
+        states: Vec<State>,
+        start: usize,
+    }
+    
+    impl Regex {
+        pub fn new(pattern: &str) -> Result<Regex, RegexError> {
+            let mut parser = Parser { chars: pattern.chars().collect(), pos: 0 };
+            let ast = parser.parse_alternate()?;
+            if parser.pos < parser.chars.len() {
+                return Err(RegexError::UnmatchedParen(parser.pos));
+            }
+            
+            let mut states = vec![State::Match];
+            let start = compile(&ast, 0, &mut states);
+            Ok(Regex { states, start })
+        }
+        
+        pub fn is_match(&self, text: &str) -> bool {
+            self.find(text).is_some()
+        }
+        
+        // 返回最左边的匹配（同一起点取最长）的字节范围
+        pub fn find(&self, text: &str) -> Option<(usize, usize)> {
+            let chars: Vec<(usize, char)> = text.char_indices().collect();
+            let mut best: Option<(usize, usize)> = None;
+            // 每个线程为(状态, 匹配起点)，按起点从早到晚排列
+            let mut current = Vec::new();
+            let mut seen = vec![false; self.states.len()];
+            
+            for i in 0..=chars.len() {
+                let pos = chars.get(i).map_or(text.len(), |&(pos, _)| pos);
+                
+                // 还没找到匹配时，从当前位置开始一个新的尝试
+                if best.is_none() {
+                    self.add_thread(&mut current, &mut seen, self.start, pos, pos, text.len());
+                }
+                
+                for &(state, start) in &current {
+                    if let State::Match = self.states[state] {
+                        // 起点更早的匹配优先，起点相同时取更长的
+                        let better = match best {
+                            Some((best_start, best_end)) => {
+                                start < best_start || (start == best_start && pos > best_end)
+                            }
+                            None => true,
+                        };
+                        if better {
+                            best = Some((start, pos));
+                        }
+                    }
+                }
+                
+                if i == chars.len() || (current.is_empty() && best.is_some()) {
+                    break;
+                }
+                
+                let ch = chars[i].1;
+                let next_pos = pos + ch.len_utf8();
+                let mut next = Vec::new();
+                let mut next_seen = vec![false; self.states.len()];
+                for &(state, start) in &current {
+                    // 已经找到更靠左的匹配，晚开始的线程不再需要
+                    if matches!(best, Some((best_start, _)) if start > best_start) {
+                        continue;
+                    }
+                    let target = match &self.states[state] {
+                        State::Char(c, next) if *c == ch => Some(*next),
+                        State::Any(next) => Some(*next),
+                        State::Class { ranges, negated, next } => {
+                            let inside = ranges.iter().any(|&(lo, hi)| lo <= ch && ch <= hi);
+                            if inside != *negated { Some(*next) } else { None }
+                        }
+                        _ => None,
+                    };
+                    if let Some(target) = target {
+                        let len = text.len();
+                        self.add_thread(&mut next, &mut next_seen, target, start, next_pos, len);
+                    }
+                }
+                current = next;
+                seen = next_seen;
+            }
+            
+            best
+        }
+        
+        // 沿不消耗字符的转移展开，只把需要消耗字符的状态和Match加入列表；
+        // seen保证每个状态在一步中只出现一次，起点更早的线程优先
+        fn add_thread(
+            &self,
+            list: &mut Vec<(usize, usize)>,
+            seen: &mut [bool],
+            state: usize,
+            start: usize,
+            pos: usize,
+            len: usize,
+        ) {
+            if seen[state] {
+                return;
+            }
+            seen[state] = true;
+            match self.states[state] {
+                State::Split(a, b) => {
+                    self.add_thread(list, seen, a, start, pos, len);
+                    self.add_thread(list, seen, b, start, pos, len);
+                }
+                State::Start(next) if pos == 0 => {
+                    self.add_thread(list, seen, next, start, pos, len);
+                }
+                State::End(next) if pos == len => {
+                    self.add_thread(list, seen, next, start, pos, len);
+                }
+                State::Start(_) | State::End(_) => {}
+                _ => list.push((state, start)),
+            }
+        }
+    }
+    
+    // 从后往前编译：node匹配完成后转到next，返回node的入口状态
+    fn compile(node: &Node, next: usize, states: &mut Vec<State>) -> usize {
+        fn push(state: State, states: &mut Vec<State>) -> usize {
+            states.push(state);
+            states.len() - 1
+        }
+        
+        match node {
+            Node::Empty => next,
+            Node::Char(c) => push(State::Char(*c, next), states),
+            Node::Any => push(State::Any(next), states),
+            Node::Class { ranges, negated } => push(
+                State::Class { ranges: ranges.clone(), negated: *negated, next },
+                states,
+            ),
+            Node::Start => push(State::Start(next), states),
+            Node::End => push(State::End(next), states),
+            Node::Concat(nodes) => {
+                nodes.iter().rev().fold(next, |next, node| compile(node, next, states))
+            }
+            Node::Alternate(branches) => {
+                let mut entries: Vec<usize> =
+                    branches.iter().map(|branch| compile(branch, next, states)).collect();
+                let last = entries.pop().unwrap();
+                entries
+                    .into_iter()
+                    .rev()
+                    .fold(last, |rest, entry| push(State::Split(entry, rest), states))
+            }
+            // 循环：split先尝试进入body，body结束后回到split
+            Node::Star(body) => {
+                let split = push(State::Split(0, next), states);
+                let entry = compile(body, split, states);
+                states[split] = State::Split(entry, next);
+                split
+            }
+            Node::Plus(body) => {
+                let split = push(State::Split(0, next), states);
+                let entry = compile(body, split, states);
+                states[split] = State::Split(entry, next);
+                entry
+            }
+            Node::Question(body) => {
+                let entry = compile(body, next, states);
+                push(State::Split(entry, next), states)
+            }
+        }
+    }
+    
+    // 递归下降解析：alternate := concat ('|' concat)*，concat := repeat*，repeat := atom [*+?]*
+    struct Parser {
+        chars: Vec<char>,
+        pos: usize,
+    }
+    
+    impl Parser {
+        fn peek(&self) -> Option<char> {
+            self.chars.get(self.pos).copied()
+        }
+        
+        fn parse_alternate(&mut self) -> Result<Node, RegexError> {
+            let mut branches = vec![self.parse_concat()?];
+            while self.peek() == Some('|') {
+                self.pos += 1;
+                branches.push(self.parse_concat()?);
+            }
+            if branches.len() == 1 {
+                Ok(branches.pop().unwrap())
+            } else {
+                Ok(Node::Alternate(branches))
+            }
+        }
+        
+        fn parse_concat(&mut self) -> Result<Node, RegexError> {
+            let mut items = Vec::new();
+            while let Some(c) = self.peek() {
+                if c == '|' || c == ')' {
+                    break;
+                }
+                let atom = self.parse_atom()?;
+                items.push(self.parse_repeat(atom)?);
+            }
+            Ok(match items.len() {
+                0 => Node::Empty,
+                1 => items.pop().unwrap(),
+                _ => Node::Concat(items),
+            })
+        }
+        
+        fn parse_repeat(&mut self, mut node: Node) -> Result<Node, RegexError> {
+            while let Some(c) = self.peek() {
+                node = match c {
+                    '*' => Node::Star(Box::new(node)),
+                    '+' => Node::Plus(Box::new(node)),
+                    '?' => Node::Question(Box::new(node)),
+                    _ => break,
+                };
+                self.pos += 1;
+            }
+            Ok(node)
+        }
+        
+        fn parse_atom(&mut self) -> Result<Node, RegexError> {
+            let start = self.pos;
+            let c = self.peek().unwrap();
+            self.pos += 1;
+            match c {
+                '.' => Ok(Node::Any),
+                '^' => Ok(Node::Start),
+                '$' => Ok(Node::End),
+                '*' | '+' | '?' => Err(RegexError::NothingToRepeat(start)),
+                '\\' => self.parse_escape().map(Node::Char),
+                '[' => self.parse_class(start),
+                '(' => {
+                    let inner = self.parse_alternate()?;
+                    if self.peek() != Some(')') {
+                        return Err(RegexError::UnclosedGroup(start));
+                    }
+                    self.pos += 1;
+                    Ok(inner)
+                }
+                c => Ok(Node::Char(c)),
+            }
+        }
+        
+        fn parse_escape(&mut self) -> Result<char, RegexError> {
+            let c = self.peek().ok_or(RegexError::TrailingBackslash)?;
+            self.pos += 1;
+            Ok(c)
+        }
+        
+        // 开头的 ] 和末尾的 - 按普通字符处理
+        fn parse_class(&mut self, start: usize) -> Result<Node, RegexError> {
+            let negated = self.peek() == Some('^');
+            if negated {
+                self.pos += 1;
+            }
+            
+            let mut ranges = Vec::new();
+            let mut first = true;
+            loop {
+                let c = self.peek().ok_or(RegexError::UnclosedClass(start))?;
+                self.pos += 1;
+                if c == ']' && !first {
+                    break;
+                }
+                first = false;
+                
+                let lo = if c == '\\' { self.parse_escape()? } else { c };
+                let is_range = self.peek() == Some('-')
+                    && self.chars.get(self.pos + 1).is_some_and(|&next| next != ']');
+                if !is_range {
+                    ranges.push((lo, lo));
+                    continue;
+                }
+                
+                let range_pos = self.pos;
+                self.pos += 1;
+                let hi = match self.peek().ok_or(RegexError::UnclosedClass(start))? {
+                    '\\' => {
+                        self.pos += 1;
+                        self.parse_escape()?
+                    }
+                    hi => {
+                        self.pos += 1;
+                        hi
+                    }
+                };
+                if lo > hi {
+                    return Err(RegexError::InvalidRange(range_pos));
+                }
+                ranges.push((lo, hi));
+            }
+            Ok(Node::Class { ranges, negated })
+        }
+    }
 }
 
 // 字符串分割和解析
@@ -294,6 +681,77 @@ fn string_formatting() {
     // 使用 format! 宏创建字符串
     let formatted = format!("Hello, {}! 你的分数是 {:.1}", name, score);
     println!("格式化字符串: {}", formatted);
+    
+    // 运行时模板：模板字符串可以来自配置文件，而format!的格式串必须在编译期确定
+    let mut vars = HashMap::new();
+    vars.insert("name".to_string(), name.to_string());
+    vars.insert("score".to_string(), score.to_string());
+    match render("{name} 的分数是 {score}，用 {{name}} 引用姓名", &vars) {
+        Ok(text) => println!("模板渲染: {}", text),
+        Err(e) => println!("模板渲染失败: {}", e),
+    }
+    if let Err(e) = render("你好 {nickname}", &vars) {
+        println!("模板渲染失败: {}", e);
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum RenderError {
+    // 模板引用了vars中不存在的变量
+    UnknownVariable(String),
+    // 位置为出错的大括号在模板中的字节偏移
+    UnclosedBrace(usize),
+    UnmatchedCloseBrace(usize),
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenderError::UnknownVariable(name) => write!(f, "未知的变量: {}", name),
+            RenderError::UnclosedBrace(pos) => write!(f, "位置 {} 的 {{ 没有闭合", pos),
+            RenderError::UnmatchedCloseBrace(pos) => write!(f, "位置 {} 有多余的 }}", pos),
+        }
+    }
+}
+
+impl std::error::Error for RenderError {}
+
+// 把模板中的 {name} 替换为vars中的值，{{ 和 }} 输出字面的大括号
+fn render(template: &str, vars: &HashMap<String, String>) -> Result<String, RenderError> {
+    let mut out = String::with_capacity(template.len());
+    let mut chars = template.char_indices().peekable();
+    
+    while let Some((pos, c)) = chars.next() {
+        match c {
+            '{' if chars.peek().map(|&(_, next)| next) == Some('{') => {
+                chars.next();
+                out.push('{');
+            }
+            '}' if chars.peek().map(|&(_, next)| next) == Some('}') => {
+                chars.next();
+                out.push('}');
+            }
+            '{' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '}')) => break,
+                        Some((_, '{')) | None => return Err(RenderError::UnclosedBrace(pos)),
+                        Some((_, c)) => name.push(c),
+                    }
+                }
+                let name = name.trim();
+                let value = vars
+                    .get(name)
+                    .ok_or_else(|| RenderError::UnknownVariable(name.to_string()))?;
+                out.push_str(value);
+            }
+            '}' => return Err(RenderError::UnmatchedCloseBrace(pos)),
+            c => out.push(c),
+        }
+    }
+    
+    Ok(out)
 }
 
 // 字符串遍历
@@ -537,6 +995,100 @@ mod tests {
         assert_eq!(text.find("Rust"), None);
     }
     
+    #[test]
+    fn test_regex_anchors_and_wildcards() {
+        let re = regex::Regex::new("^a.*z$").unwrap();
+        assert!(re.is_match("az"));
+        assert!(re.is_match("abc xyz"));
+        assert!(!re.is_match("abc xyz!"));
+        assert!(!re.is_match(" az"));
+        
+        let re = regex::Regex::new("colou?r").unwrap();
+        assert_eq!(re.find("my colour"), Some((3, 9)));
+        assert_eq!(re.find("my color"), Some((3, 8)));
+        
+        // 转义后的元字符按字面匹配
+        let re = regex::Regex::new("a\\.b").unwrap();
+        assert!(re.is_match("a.b"));
+        assert!(!re.is_match("axb"));
+    }
+    
+    #[test]
+    fn test_regex_classes_and_find() {
+        let re = regex::Regex::new("[0-9]+").unwrap();
+        assert_eq!(re.find("abc 123 45"), Some((4, 7)));
+        assert_eq!(re.find("no digits"), None);
+        
+        let re = regex::Regex::new("[^a-z ]+").unwrap();
+        assert_eq!(re.find("abc 中文 def"), Some((4, 10)));
+        
+        // 最坏情况下也不会指数回溯
+        let re = regex::Regex::new("(a*)*b").unwrap();
+        assert!(!re.is_match(&"a".repeat(5000)));
+    }
+    
+    #[test]
+    fn test_regex_alternation() {
+        let re = regex::Regex::new("cat|dog|bird").unwrap();
+        assert_eq!(re.find("hot dog"), Some((4, 7)));
+        assert!(!re.is_match("cow"));
+        
+        // 同一起点取最长的分支
+        let re = regex::Regex::new("a|ab|abc").unwrap();
+        assert_eq!(re.find("xabcd"), Some((1, 4)));
+        
+        // 起点靠后的匹配先完成，也不能挡住起点更早的匹配
+        let re = regex::Regex::new("b|abc").unwrap();
+        assert_eq!(re.find("abc"), Some((0, 3)));
+        let re = regex::Regex::new("a|xab").unwrap();
+        assert_eq!(re.find("xab"), Some((0, 3)));
+        
+        let re = regex::Regex::new("^(GET|POST) /").unwrap();
+        assert!(re.is_match("POST /api"));
+        assert!(!re.is_match("PUT /api"));
+    }
+    
+    #[test]
+    fn test_regex_rejects_malformed_patterns() {
+        use regex::{Regex, RegexError};
+        
+        assert_eq!(Regex::new("a[").err(), Some(RegexError::UnclosedClass(1)));
+        assert_eq!(Regex::new("(ab").err(), Some(RegexError::UnclosedGroup(0)));
+        assert_eq!(Regex::new("ab)").err(), Some(RegexError::UnmatchedParen(2)));
+        assert_eq!(Regex::new("*a").err(), Some(RegexError::NothingToRepeat(0)));
+        assert_eq!(Regex::new("[z-a]").err(), Some(RegexError::InvalidRange(2)));
+        assert_eq!(Regex::new("ab\\").err(), Some(RegexError::TrailingBackslash));
+    }
+    
+    #[test]
+    fn test_render_substitutes_variables() {
+        let mut vars = HashMap::new();
+        vars.insert("host".to_string(), "localhost".to_string());
+        vars.insert("port".to_string(), "8080".to_string());
+        
+        assert_eq!(render("http://{host}:{port}/", &vars).unwrap(), "http://localhost:8080/");
+        assert_eq!(render("{ host }", &vars).unwrap(), "localhost");
+        assert_eq!(render("没有占位符", &vars).unwrap(), "没有占位符");
+        
+        // {{ 和 }} 是转义的大括号
+        assert_eq!(render("{{host}} = {host}", &vars).unwrap(), "{host} = localhost");
+        assert_eq!(render("{{{port}}}", &vars).unwrap(), "{8080}");
+    }
+    
+    #[test]
+    fn test_render_errors() {
+        let mut vars = HashMap::new();
+        vars.insert("name".to_string(), "张三".to_string());
+        
+        assert_eq!(
+            render("你好 {nmae}", &vars),
+            Err(RenderError::UnknownVariable("nmae".to_string()))
+        );
+        assert_eq!(render("你好 {name", &vars), Err(RenderError::UnclosedBrace(7)));
+        assert_eq!(render("{na{me}", &vars), Err(RenderError::UnclosedBrace(0)));
+        assert_eq!(render("name}", &vars), Err(RenderError::UnmatchedCloseBrace(4)));
+    }
+    
     #[test]
     fn test_string_parsing() {
         let number_str = "42";
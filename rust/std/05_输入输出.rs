@@ -186,6 +186,35 @@ fn buffered_io() {
         Err(e) => println!("逐行读取失败: {}", e),
     }
     
+    // 内存中的环形缓冲区，不需要真实文件
+    let mut ring = RingBuffer::new(64);
+    {
+        let mut writer = BufWriter::new(&mut ring);
+        let _ = writeln!(writer, "内存中的第一行");
+        let _ = writeln!(writer, "内存中的第二行");
+    }
+    println!("环形缓冲区: {}/{} 字节, 已满: {}", ring.len(), ring.capacity(), ring.is_full());
+    let ring_lines: Vec<String> = BufReader::new(&mut ring).lines().map_while(Result::ok).collect();
+    println!("环形缓冲区读取: {:?}", ring_lines);
+    
+    // 按自定义分隔符拆分，例如 find -print0 输出的以NUL分隔的路径
+    let paths = io::Cursor::new(b"/tmp/a.txt\0/tmp/b c.txt\0".to_vec());
+    for path in split_on(paths, 0) {
+        match path {
+            Ok(path) => println!("NUL分隔的路径: {}", String::from_utf8_lossy(&path)),
+            Err(e) => println!("读取失败: {}", e),
+        }
+    }
+    
+    // 流式统计
+    match count_file(Path::new(filename)) {
+        Ok(stats) => println!(
+            "文件统计: {} 行, {} 个单词, {} 字节, {} 个字符",
+            stats.lines, stats.words, stats.bytes, stats.chars
+        ),
+        Err(e) => println!("文件统计失败: {}", e),
+    }
+    
     // 清理测试文件
     let _ = remove_file(filename);
 }
@@ -287,6 +316,15 @@ fn binary_data() {
         Err(e) => println!("数字数据读取失败: {}", e),
     }
     
+    // 以大端序保存 u64 数组
+    let timestamps = vec![1_700_000_000_000u64, 1_700_000_000_500u64];
+    match write_numbers_with(filename, &timestamps, Endian::Big)
+        .and_then(|_| read_numbers_with::<u64>(filename, Endian::Big))
+    {
+        Ok(values) => println!("u64 大端数据: {:?}", values),
+        Err(e) => println!("u64 大端数据处理失败: {}", e),
+    }
+    
     // 清理测试文件
     let _ = remove_file(filename);
 }
@@ -305,6 +343,26 @@ fn advanced_io() {
         Err(e) => println!("随机访问文件失败: {}", e),
     }
     
+    // 定长记录文件
+    let record_path = Path::new("records_test.bin");
+    let result = RecordFile::<8>::open(record_path).and_then(|mut records| {
+        records.write_record(0, b"record-0")?;
+        records.write_record(3, b"record-3")?;
+        let third = records.read_record(3)?;
+        Ok((records.len()?, third))
+    });
+    match result {
+        Ok((len, third)) => println!("记录文件共 {} 条，第3条: {}", len, String::from_utf8_lossy(&third)),
+        Err(e) => println!("记录文件操作失败: {}", e),
+    }
+    let _ = remove_file(record_path);
+    
+    // 读取最后几行
+    match tail(Path::new(filename), 2) {
+        Ok(lines) => println!("最后两行: {:?}", lines),
+        Err(e) => println!("读取末尾失败: {}", e),
+    }
+    
     // 文件锁定 (在实际应用中很重要)
     match file_locking_demo(filename) {
         Ok(_) => println!("文件锁定演示成功"),
@@ -477,6 +535,60 @@ fn read_lines(filename: &str) -> io::Result<Vec<String>> {
     Ok(lines)
 }
 
+// 按任意分隔字节拆分输入，逐条读取而不是一次读入全部内容
+// 返回的记录不含分隔符；最后一条记录没有分隔符结尾时也会返回
+fn split_on<R: BufRead>(mut reader: R, delim: u8) -> impl Iterator<Item = io::Result<Vec<u8>>> {
+    std::iter::from_fn(move || {
+        let mut record = Vec::new();
+        match reader.read_until(delim, &mut record) {
+            Ok(0) => None,
+            Ok(_) => {
+                if record.last() == Some(&delim) {
+                    record.pop();
+                }
+                Some(Ok(record))
+            }
+            Err(e) => Some(Err(e)),
+        }
+    })
+}
+
+// 文件统计信息
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+struct FileStats {
+    lines: usize,
+    words: usize,
+    bytes: usize,
+    chars: usize,
+}
+
+// 流式统计行数、单词数、字节数和字符数，只保留当前行在内存中
+// 没有结尾换行符的最后一行同样计为一行
+fn count_file(path: &Path) -> io::Result<FileStats> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut stats = FileStats::default();
+    let mut line = Vec::new();
+    
+    loop {
+        line.clear();
+        let n = reader.read_until(b'\n', &mut line)?;
+        if n == 0 {
+            break;
+        }
+        
+        // 换行符是 ASCII，按行切分不会截断多字节字符
+        let text = std::str::from_utf8(&line)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        
+        stats.lines += 1;
+        stats.bytes += n;
+        stats.chars += text.chars().count();
+        stats.words += text.split_whitespace().count();
+    }
+    
+    Ok(stats)
+}
+
 // 写入二进制数据
 fn write_binary_data(filename: &str, data: &[u8]) -> io::Result<()> {
     let mut file = File::create(filename)?;
@@ -493,27 +605,90 @@ fn read_binary_data(filename: &str) -> io::Result<Vec<u8>> {
     Ok(data)
 }
 
-// 写入数字数据
-fn write_numbers(filename: &str, numbers: &[u32]) -> io::Result<()> {
-    let mut file = File::create(filename)?;
+// 字节序
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Endian {
+    Little,
+    Big,
+}
+
+// 定长数字的二进制编解码
+trait NumberCodec: Sized + Copy {
+    const SIZE: usize;
+    
+    fn encode(self, endian: Endian, out: &mut Vec<u8>);
+    
+    // bytes 的长度必须正好是 SIZE
+    fn decode(bytes: &[u8], endian: Endian) -> Self;
+}
+
+macro_rules! impl_number_codec {
+    ($($t:ty),*) => {
+        $(
+            impl NumberCodec for $t {
+                const SIZE: usize = std::mem::size_of::<$t>();
+                
+                fn encode(self, endian: Endian, out: &mut Vec<u8>) {
+                    match endian {
+                        Endian::Little => out.extend_from_slice(&self.to_le_bytes()),
+                        Endian::Big => out.extend_from_slice(&self.to_be_bytes()),
+                    }
+                }
+                
+                fn decode(bytes: &[u8], endian: Endian) -> Self {
+                    let mut buf = [0u8; std::mem::size_of::<$t>()];
+                    buf.copy_from_slice(bytes);
+                    match endian {
+                        Endian::Little => <$t>::from_le_bytes(buf),
+                        Endian::Big => <$t>::from_be_bytes(buf),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_number_codec!(u16, u32, u64, i32, i64);
+
+// 按指定字节序写入数字数组
+fn write_numbers_with<T: NumberCodec>(filename: &str, numbers: &[T], endian: Endian) -> io::Result<()> {
+    let mut bytes = Vec::with_capacity(numbers.len() * T::SIZE);
     for &number in numbers {
-        file.write_all(&number.to_le_bytes())?;
+        number.encode(endian, &mut bytes);
     }
+    
+    let mut file = File::create(filename)?;
+    file.write_all(&bytes)?;
     file.sync_all()?;
     Ok(())
 }
 
-// 读取数字数据
-fn read_numbers(filename: &str) -> io::Result<Vec<u32>> {
-    let mut file = File::open(filename)?;
-    let mut numbers = Vec::new();
-    let mut buffer = [0u8; 4];
+// 按指定字节序读取数字数组，末尾不完整的元素视为错误
+fn read_numbers_with<T: NumberCodec>(filename: &str, endian: Endian) -> io::Result<Vec<T>> {
+    let mut data = Vec::new();
+    File::open(filename)?.read_to_end(&mut data)?;
     
-    while file.read_exact(&mut buffer).is_ok() {
-        numbers.push(u32::from_le_bytes(buffer));
+    if data.len() % T::SIZE != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("文件长度 {} 不是元素大小 {} 的整数倍", data.len(), T::SIZE),
+        ));
     }
     
-    Ok(numbers)
+    Ok(data
+        .chunks_exact(T::SIZE)
+        .map(|chunk| T::decode(chunk, endian))
+        .collect())
+}
+
+// 写入数字数据（u32 小端，保持兼容）
+fn write_numbers(filename: &str, numbers: &[u32]) -> io::Result<()> {
+    write_numbers_with(filename, numbers, Endian::Little)
+}
+
+// 读取数字数据（u32 小端，保持兼容）
+fn read_numbers(filename: &str) -> io::Result<Vec<u32>> {
+    read_numbers_with(filename, Endian::Little)
 }
 
 // 随机访问文件
@@ -543,6 +718,208 @@ fn random_access_file(filename: &str) -> io::Result<()> {
     Ok(())
 }
 
+// 定长记录文件：把文件看作 [u8; SIZE] 数组，第 index 条记录位于 index * SIZE 处
+struct RecordFile<const SIZE: usize> {
+    file: File,
+}
+
+impl<const SIZE: usize> RecordFile<SIZE> {
+    fn open(path: &Path) -> io::Result<Self> {
+        if SIZE == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "记录大小不能为0"));
+        }
+        
+        // 保留已有的记录
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        Ok(RecordFile { file })
+    }
+    
+    // 第index条记录在文件中的偏移，溢出时返回错误
+    fn offset(index: usize) -> io::Result<u64> {
+        index
+            .checked_mul(SIZE)
+            .and_then(|offset| u64::try_from(offset).ok())
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidInput, format!("记录 {} 的偏移溢出", index))
+            })
+    }
+    
+    // 记录条数，末尾不完整的记录不计入
+    fn len(&self) -> io::Result<usize> {
+        Ok(self.file.metadata()?.len() as usize / SIZE)
+    }
+    
+    fn read_record(&mut self, index: usize) -> io::Result<[u8; SIZE]> {
+        let len = self.len()?;
+        if index >= len {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("记录 {} 超出范围，文件只有 {} 条记录", index, len),
+            ));
+        }
+        
+        let mut record = [0u8; SIZE];
+        self.file.seek(SeekFrom::Start(Self::offset(index)?))?;
+        self.file.read_exact(&mut record)?;
+        Ok(record)
+    }
+    
+    // 写到文件末尾之后时，中间跳过的记录由文件系统填充为0
+    fn write_record(&mut self, index: usize, record: &[u8; SIZE]) -> io::Result<()> {
+        self.file.seek(SeekFrom::Start(Self::offset(index)?))?;
+        self.file.write_all(record)
+    }
+}
+
+// 读取文件最后 n 行：从末尾按块向前读取，收集到足够的换行符即停止
+fn tail(path: &Path, n: usize) -> io::Result<Vec<String>> {
+    const CHUNK_SIZE: u64 = 4096;
+    
+    if n == 0 {
+        return Ok(Vec::new());
+    }
+    
+    let mut file = File::open(path)?;
+    let mut pos = file.seek(SeekFrom::End(0))?;
+    // 从后往前读到的块，只统计新块中的换行符，最后一次性按正序拼接
+    let mut chunks: Vec<Vec<u8>> = Vec::new();
+    let mut newlines = 0;
+    
+    // 末尾的换行符可能不属于任何新行，因此需要多于 n 个换行符
+    while pos > 0 && newlines <= n {
+        let size = CHUNK_SIZE.min(pos);
+        pos -= size;
+        file.seek(SeekFrom::Start(pos))?;
+        
+        let mut chunk = vec![0u8; size as usize];
+        file.read_exact(&mut chunk)?;
+        newlines += chunk.iter().filter(|&&b| b == b'\n').count();
+        chunks.push(chunk);
+    }
+    chunks.reverse();
+    let buffer = chunks.concat();
+    
+    // 没有读到文件开头时，第一段可能是不完整的行，丢弃它
+    let start = if pos > 0 {
+        buffer.iter().position(|&b| b == b'\n').map_or(buffer.len(), |i| i + 1)
+    } else {
+        0
+    };
+    
+    let text = std::str::from_utf8(&buffer[start..])
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let lines: Vec<&str> = text.lines().collect();
+    let skip = lines.len().saturating_sub(n);
+    
+    Ok(lines[skip..].iter().map(|line| line.to_string()).collect())
+}
+
+// 固定容量的内存环形缓冲区，同时实现Read和Write，可以代替文件或套接字做测试
+// 写满时只写入能容纳的部分（短写），完全写满后返回Ok(0)；读空时返回Ok(0)
+struct RingBuffer {
+    data: Box<[u8]>,
+    head: usize, // 下一个可读字节的位置
+    len: usize,
+}
+
+impl RingBuffer {
+    fn new(capacity: usize) -> Self {
+        RingBuffer {
+            data: vec![0; capacity].into_boxed_slice(),
+            head: 0,
+            len: 0,
+        }
+    }
+    
+    fn len(&self) -> usize {
+        self.len
+    }
+    
+    fn capacity(&self) -> usize {
+        self.data.len()
+    }
+    
+    fn is_full(&self) -> bool {
+        self.len == self.capacity()
+    }
+}
+
+impl Write for RingBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let count = buf.len().min(self.capacity() - self.len);
+        if count == 0 {
+            return Ok(0);
+        }
+        
+        // 可写区域从tail开始，可能绕回缓冲区开头，分两段复制
+        let tail = (self.head + self.len) % self.capacity();
+        let first = count.min(self.capacity() - tail);
+        self.data[tail..tail + first].copy_from_slice(&buf[..first]);
+        self.data[..count - first].copy_from_slice(&buf[first..count]);
+        self.len += count;
+        Ok(count)
+    }
+    
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Read for RingBuffer {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let count = buf.len().min(self.len);
+        if count == 0 {
+            return Ok(0);
+        }
+        
+        let first = count.min(self.capacity() - self.head);
+        buf[..first].copy_from_slice(&self.data[self.head..self.head + first]);
+        buf[first..count].copy_from_slice(&self.data[..count - first]);
+        self.head = (self.head + count) % self.capacity();
+        self.len -= count;
+        Ok(count)
+    }
+}
+
+// 把写入内容同时转发给多个输出，某个输出失败时仍然写其余输出，最后返回第一个错误
+struct TeeWriter {
+    sinks: Vec<Box<dyn Write>>,
+}
+
+impl TeeWriter {
+    fn new(sinks: Vec<Box<dyn Write>>) -> Self {
+        TeeWriter { sinks }
+    }
+    
+    // 对每个输出调用op，返回第一个错误
+    fn for_each_sink(&mut self, mut op: impl FnMut(&mut dyn Write) -> io::Result<()>) -> io::Result<()> {
+        let mut first_error = None;
+        for sink in &mut self.sinks {
+            if let Err(e) = op(sink.as_mut()) {
+                first_error.get_or_insert(e);
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+}
+
+impl Write for TeeWriter {
+    // 每个输出都完整写入buf，保证各输出的内容一致
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.for_each_sink(|sink| sink.write_all(buf))?;
+        Ok(buf.len())
+    }
+    
+    fn flush(&mut self) -> io::Result<()> {
+        self.for_each_sink(|sink| sink.flush())
+    }
+}
+
 // 文件锁定演示
 fn file_locking_demo(filename: &str) -> io::Result<()> {
     let _file = OpenOptions::new()
@@ -560,13 +937,38 @@ fn file_locking_demo(filename: &str) -> io::Result<()> {
 // 内存映射文件演示
 fn memory_mapped_file(filename: &str) -> io::Result<()> {
     // 标准库不直接支持内存映射
-    // 这里只是演示概念
-    let content = read_from_file(filename)?;
-    println!("内存映射文件内容长度: {} 字节", content.len());
-    println!("内容预览: {}", &content[..content.len().min(50)]);
+    // 这里一次读入整个文件，再零拷贝地按行解析，效果类似于对映射区域解析
+    let data = std::fs::read(filename)?;
+    println!("内存映射文件内容长度: {} 字节", data.len());
+    for (i, line) in lines_borrowed(&data).take(3).enumerate() {
+        println!("  第{}行: {}", i + 1, line);
+    }
     Ok(())
 }
 
+// 零拷贝按行拆分：返回的&str直接指向data，不为每行分配内存
+// 每行单独校验UTF-8，兼容 \r\n 和 \n 结尾
+fn try_lines_borrowed(data: &[u8]) -> impl Iterator<Item = Result<&str, std::str::Utf8Error>> {
+    let mut rest = data;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let (line, next) = match rest.iter().position(|&b| b == b'\n') {
+            Some(i) => (&rest[..i], &rest[i + 1..]),
+            None => (rest, &rest[rest.len()..]),
+        };
+        rest = next;
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        Some(std::str::from_utf8(line))
+    })
+}
+
+// 同try_lines_borrowed，但跳过不是合法UTF-8的行
+fn lines_borrowed(data: &[u8]) -> impl Iterator<Item = &str> {
+    try_lines_borrowed(data).filter_map(Result::ok)
+}
+
 // 尝试写入只读文件
 fn write_to_readonly_file() -> io::Result<()> {
     let filename = "readonly_test.txt";
@@ -663,6 +1065,18 @@ fn log_file_example() {
         }
     }
     
+    // 同时输出到终端和日志文件
+    match OpenOptions::new().append(true).create(true).open(log_filename) {
+        Ok(file) => {
+            let mut tee = TeeWriter::new(vec![Box::new(io::stdout()), Box::new(BufWriter::new(file))]);
+            let _ = writeln!(tee, "2023-01-01 10:00:05 INFO 同时写入终端和文件");
+            if let Err(e) = tee.flush() {
+                println!("刷新日志失败: {}", e);
+            }
+        }
+        Err(e) => println!("打开日志文件失败: {}", e),
+    }
+    
     // 读取日志
     match read_lines(log_filename) {
         Ok(lines) => {
@@ -816,6 +1230,111 @@ mod tests {
         let _ = remove_file(filename);
     }
     
+    #[test]
+    fn test_number_codec_u64_big_endian() {
+        let filename = "test_numbers_u64_be.bin";
+        let numbers: Vec<u64> = vec![0, 1, 0x0102_0304_0506_0708, u64::MAX];
+        
+        write_numbers_with(filename, &numbers, Endian::Big).unwrap();
+        
+        // 大端序：高位字节在前
+        let raw = read_binary_data(filename).unwrap();
+        assert_eq!(raw.len(), numbers.len() * 8);
+        assert_eq!(&raw[16..24], &[1, 2, 3, 4, 5, 6, 7, 8]);
+        
+        let decoded: Vec<u64> = read_numbers_with(filename, Endian::Big).unwrap();
+        assert_eq!(decoded, numbers);
+        
+        let _ = remove_file(filename);
+    }
+    
+    #[test]
+    fn test_number_codec_truncated() {
+        let filename = "test_numbers_truncated.bin";
+        
+        // 两个完整的 u32 加上一个多余字节
+        write_binary_data(filename, &[1, 0, 0, 0, 2, 0, 0, 0, 3]).unwrap();
+        
+        let result = read_numbers(filename);
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidData);
+        
+        let result = read_numbers_with::<i64>(filename, Endian::Big);
+        assert!(result.is_err());
+        
+        let _ = remove_file(filename);
+    }
+    
+    #[test]
+    fn test_count_file() {
+        let filename = "test_count_file.txt";
+        
+        // 包含多字节字符，最后一行没有换行符
+        let content = "hello 世界\nfoo bar  baz\nlast";
+        write_to_file(filename, content).unwrap();
+        
+        let stats = count_file(Path::new(filename)).unwrap();
+        assert_eq!(
+            stats,
+            FileStats {
+                lines: 3,
+                words: 6,
+                bytes: 30,
+                chars: 26,
+            }
+        );
+        assert_eq!(stats.bytes, content.len());
+        assert_eq!(stats.chars, content.chars().count());
+        
+        let _ = remove_file(filename);
+    }
+    
+    #[test]
+    fn test_tail_large_file() {
+        let filename = "test_tail_large.txt";
+        
+        // 文件大小超过一个读取块
+        let content: String = (1..=1000).map(|i| format!("line {}\n", i)).collect();
+        write_to_file(filename, &content).unwrap();
+        
+        let lines = tail(Path::new(filename), 5).unwrap();
+        assert_eq!(
+            lines,
+            vec!["line 996", "line 997", "line 998", "line 999", "line 1000"]
+        );
+        
+        let _ = remove_file(filename);
+    }
+    
+    #[test]
+    fn test_tail_short_file() {
+        let filename = "test_tail_short.txt";
+        
+        // 只有两行，最后一行没有换行符
+        write_to_file(filename, "第一行\n第二行").unwrap();
+        
+        let lines = tail(Path::new(filename), 10).unwrap();
+        assert_eq!(lines, vec!["第一行", "第二行"]);
+        
+        assert!(tail(Path::new(filename), 0).unwrap().is_empty());
+        
+        let _ = remove_file(filename);
+    }
+    
+    #[test]
+    fn test_tail_long_lines_span_chunks() {
+        let filename = "test_tail_long_lines.txt";
+        
+        // 每行都比一个读取块长，行的边界落在块中间
+        let long = |c: char| c.to_string().repeat(10_000);
+        let content = format!("{}\n{}\n{}\n", long('a'), long('b'), long('c'));
+        write_to_file(filename, &content).unwrap();
+        
+        assert_eq!(tail(Path::new(filename), 2).unwrap(), vec![long('b'), long('c')]);
+        assert_eq!(tail(Path::new(filename), 5).unwrap(), vec![long('a'), long('b'), long('c')]);
+        
+        let _ = remove_file(filename);
+    }
+    
     #[test]
     fn test_error_handling() {
         // 测试读取不存在的文件
@@ -840,4 +1359,191 @@ mod tests {
         // 清理
         let _ = remove_dir_all(test_dir);
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_record_file_random_access() {
+        let filename = "test_records.bin";
+        let _ = remove_file(filename);
+        
+        let mut records = RecordFile::<4>::open(Path::new(filename)).unwrap();
+        records.write_record(0, &[1, 1, 1, 1]).unwrap();
+        records.write_record(5, &[5, 5, 5, 5]).unwrap();
+        records.write_record(2, &[2, 2, 2, 2]).unwrap();
+        assert_eq!(records.len().unwrap(), 6);
+        
+        assert_eq!(records.read_record(0).unwrap(), [1, 1, 1, 1]);
+        assert_eq!(records.read_record(2).unwrap(), [2, 2, 2, 2]);
+        assert_eq!(records.read_record(5).unwrap(), [5, 5, 5, 5]);
+        // 中间没写过的记录读出来都是0
+        for index in [1, 3, 4] {
+            assert_eq!(records.read_record(index).unwrap(), [0; 4]);
+        }
+        
+        let err = records.read_record(6).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        
+        // 重新打开不会清空已有记录
+        drop(records);
+        let mut records = RecordFile::<4>::open(Path::new(filename)).unwrap();
+        assert_eq!(records.read_record(5).unwrap(), [5, 5, 5, 5]);
+        
+        // 偏移溢出时返回错误而不是回绕到文件开头
+        let err = records.write_record(usize::MAX, &[9; 4]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(records.read_record(0).unwrap(), [1, 1, 1, 1]);
+        
+        let _ = remove_file(filename);
+    }
+    
+    #[test]
+    fn test_record_file_rejects_zero_size() {
+        let filename = "test_records_zero.bin";
+        let err = RecordFile::<0>::open(Path::new(filename)).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(!Path::new(filename).exists());
+    }
+    
+    #[test]
+    fn test_ring_buffer_round_trip_and_wrap_around() {
+        let mut ring = RingBuffer::new(32);
+        {
+            let mut writer = BufWriter::new(&mut ring);
+            writeln!(writer, "hello").unwrap();
+            writeln!(writer, "world").unwrap();
+        }
+        let lines: Vec<String> = BufReader::new(&mut ring).lines().map(|l| l.unwrap()).collect();
+        assert_eq!(lines, vec!["hello", "world"]);
+        assert_eq!(ring.len(), 0);
+        
+        // 读写位置绕回缓冲区开头
+        let mut ring = RingBuffer::new(8);
+        assert_eq!(ring.write(b"abcdef").unwrap(), 6);
+        let mut buf = [0u8; 4];
+        assert_eq!(ring.read(&mut buf).unwrap(), 4);
+        assert_eq!(&buf, b"abcd");
+        assert_eq!(ring.write(b"ghijkl").unwrap(), 6);
+        assert!(ring.is_full());
+        
+        let mut out = Vec::new();
+        ring.read_to_end(&mut out).unwrap();
+        assert_eq!(out, b"efghijkl");
+    }
+    
+    #[test]
+    fn test_ring_buffer_short_writes() {
+        let mut ring = RingBuffer::new(4);
+        assert_eq!(ring.write(b"0123456789").unwrap(), 4);
+        assert!(ring.is_full());
+        assert_eq!(ring.write(b"x").unwrap(), 0);
+        
+        // write_all遇到写满会报WriteZero
+        let err = ring.write_all(b"more").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+        
+        let mut buf = [0u8; 8];
+        assert_eq!(ring.read(&mut buf).unwrap(), 4);
+        assert_eq!(&buf[..4], b"0123");
+        assert_eq!(ring.read(&mut buf).unwrap(), 0);
+    }
+    
+    // 可以在写入后检查内容的共享输出
+    #[derive(Clone, Default)]
+    struct SharedSink(std::rc::Rc<std::cell::RefCell<Vec<u8>>>);
+    
+    impl Write for SharedSink {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+        
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+    
+    struct FailingSink;
+    
+    impl Write for FailingSink {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::Error::other("写入失败"))
+        }
+        
+        fn flush(&mut self) -> io::Result<()> {
+            Err(io::Error::other("刷新失败"))
+        }
+    }
+    
+    #[test]
+    fn test_tee_writer_duplicates_and_flushes() {
+        let direct = SharedSink::default();
+        let buffered = SharedSink::default();
+        let mut tee = TeeWriter::new(vec![
+            Box::new(direct.clone()),
+            Box::new(BufWriter::new(buffered.clone())),
+        ]);
+        
+        write!(tee, "hello, tee").unwrap();
+        assert_eq!(direct.0.borrow().as_slice(), b"hello, tee");
+        // 缓冲输出要等flush才收到数据
+        assert!(buffered.0.borrow().is_empty());
+        
+        tee.flush().unwrap();
+        assert_eq!(*direct.0.borrow(), *buffered.0.borrow());
+    }
+    
+    #[test]
+    fn test_tee_writer_continues_after_failing_sink() {
+        let sink = SharedSink::default();
+        let mut tee = TeeWriter::new(vec![Box::new(FailingSink), Box::new(sink.clone())]);
+        
+        let err = tee.write_all(b"data").unwrap_err();
+        assert_eq!(err.to_string(), "写入失败");
+        assert_eq!(sink.0.borrow().as_slice(), b"data");
+        assert!(tee.flush().is_err());
+    }
+    
+    #[test]
+    fn test_split_on_custom_delimiters() {
+        let records: Vec<Vec<u8>> = split_on(io::Cursor::new("a=1;b=2;;c=3"), b';')
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(records, vec![b"a=1".to_vec(), b"b=2".to_vec(), b"".to_vec(), b"c=3".to_vec()]);
+        
+        // 以分隔符结尾时不会多出空记录
+        let records: Vec<Vec<u8>> = split_on(io::Cursor::new(b"x\0y z\0".to_vec()), 0)
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(records, vec![b"x".to_vec(), b"y z".to_vec()]);
+        
+        // 小缓冲区也能拼出跨越多次读取的记录
+        let reader = BufReader::with_capacity(2, io::Cursor::new(b"first\0unterminated".to_vec()));
+        let records: Vec<Vec<u8>> = split_on(reader, 0).collect::<io::Result<_>>().unwrap();
+        assert_eq!(records, vec![b"first".to_vec(), b"unterminated".to_vec()]);
+        
+        assert_eq!(split_on(io::Cursor::new(""), b';').count(), 0);
+    }
+    
+    #[test]
+    fn test_lines_borrowed_zero_copy() {
+        let data = b"first\r\nsecond\n\xff\xfe\nlast".to_vec();
+        let lines: Vec<&str> = lines_borrowed(&data).collect();
+        assert_eq!(lines, vec!["first", "second", "last"]);
+        
+        // 返回的切片指向原始缓冲区
+        let base = data.as_ptr() as usize;
+        assert_eq!(lines[0].as_ptr() as usize, base);
+        assert_eq!(lines[1].as_ptr() as usize, base + 7);
+        assert!(lines.iter().all(|line| {
+            let start = line.as_ptr() as usize;
+            start >= base && start + line.len() <= base + data.len()
+        }));
+        
+        // 严格模式下非法行作为错误返回
+        let results: Vec<_> = try_lines_borrowed(&data).collect();
+        assert_eq!(results.len(), 4);
+        assert!(results[2].is_err());
+        
+        assert_eq!(lines_borrowed(b"").count(), 0);
+        assert_eq!(lines_borrowed(b"\n\n").collect::<Vec<_>>(), vec!["", ""]);
+    }
+}
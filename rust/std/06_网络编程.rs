@@ -32,12 +32,13 @@ Rust标准库的std::net模块提供了网络编程的基础功能：
 - 考虑防火墙和网络安全
 */
 
-use std::net::{TcpListener, TcpStream, UdpSocket, SocketAddr, IpAddr, Ipv4Addr, Ipv6Addr, ToSocketAddrs};
+use std::net::{TcpListener, TcpStream, UdpSocket, SocketAddr, IpAddr, Ipv4Addr, Ipv6Addr, ToSocketAddrs, Shutdown};
 use std::io::{Read, Write, BufRead, BufReader, BufWriter};
 use std::thread;
 use std::time::Duration;
-use std::sync::mpsc;
-use std::collections::HashMap;
+use std::sync::{mpsc, Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 
 fn main() {
     println!("=== Rust标准库网络编程 ===");
@@ -130,6 +131,109 @@ fn address_handling() {
     println!("  localhost:8080 -> 127.0.0.1:8080");
     println!("  0.0.0.0:8080 -> 监听所有接口");
     println!("  [::1]:8080 -> IPv6本地回环");
+    
+    // 子网判断
+    for (cidr, ip) in [("192.168.1.0/24", "192.168.1.5"), ("10.0.0.0/8", "11.0.0.1"), ("2001:db8::/32", "2001:db8::1")] {
+        match (parse_cidr(cidr), ip.parse::<IpAddr>()) {
+            (Ok(net), Ok(ip)) => println!("{} 属于 {}: {}", ip, net, net.contains(ip)),
+            (Err(e), _) => println!("解析 {} 失败: {}", cidr, e),
+            (_, Err(e)) => println!("解析 {} 失败: {}", ip, e),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum CidrParseError {
+    // 缺少 /前缀长度
+    MissingPrefix(String),
+    InvalidAddress(String),
+    InvalidPrefix(String),
+    // 前缀长度超过地址位数（IPv4为32，IPv6为128）
+    PrefixTooLong { prefix: u8, max: u8 },
+}
+
+impl std::fmt::Display for CidrParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CidrParseError::MissingPrefix(s) => write!(f, "缺少前缀长度: {}", s),
+            CidrParseError::InvalidAddress(s) => write!(f, "非法地址: {}", s),
+            CidrParseError::InvalidPrefix(s) => write!(f, "非法前缀长度: {}", s),
+            CidrParseError::PrefixTooLong { prefix, max } => {
+                write!(f, "前缀长度 {} 超过地址位数 {}", prefix, max)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CidrParseError {}
+
+// CIDR网段，network 中主机位已清零
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Cidr {
+    network: IpAddr,
+    prefix: u8,
+}
+
+impl Cidr {
+    fn contains(&self, ip: IpAddr) -> bool {
+        match (self.network, ip) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let mask = prefix_mask_v4(self.prefix);
+                u32::from(ip) & mask == u32::from(network)
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let mask = prefix_mask_v6(self.prefix);
+                u128::from(ip) & mask == u128::from(network)
+            }
+            // 地址族不同
+            _ => false,
+        }
+    }
+}
+
+impl std::fmt::Display for Cidr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.network, self.prefix)
+    }
+}
+
+// 前缀长度为0时移位会溢出，单独处理
+fn prefix_mask_v4(prefix: u8) -> u32 {
+    if prefix == 0 { 0 } else { u32::MAX << (32 - prefix) }
+}
+
+fn prefix_mask_v6(prefix: u8) -> u128 {
+    if prefix == 0 { 0 } else { u128::MAX << (128 - prefix) }
+}
+
+// 解析 192.168.0.0/24 或 2001:db8::/32 形式的网段
+fn parse_cidr(s: &str) -> Result<Cidr, CidrParseError> {
+    let (addr, prefix) = s
+        .split_once('/')
+        .ok_or_else(|| CidrParseError::MissingPrefix(s.to_string()))?;
+    let addr: IpAddr = addr
+        .parse()
+        .map_err(|_| CidrParseError::InvalidAddress(addr.to_string()))?;
+    let prefix: u8 = prefix
+        .parse()
+        .map_err(|_| CidrParseError::InvalidPrefix(prefix.to_string()))?;
+    
+    let network = match addr {
+        IpAddr::V4(ip) => {
+            if prefix > 32 {
+                return Err(CidrParseError::PrefixTooLong { prefix, max: 32 });
+            }
+            IpAddr::V4(Ipv4Addr::from(u32::from(ip) & prefix_mask_v4(prefix)))
+        }
+        IpAddr::V6(ip) => {
+            if prefix > 128 {
+                return Err(CidrParseError::PrefixTooLong { prefix, max: 128 });
+            }
+            IpAddr::V6(Ipv6Addr::from(u128::from(ip) & prefix_mask_v6(prefix)))
+        }
+    };
+    
+    Ok(Cidr { network, prefix })
 }
 
 // TCP客户端示例
@@ -175,8 +279,10 @@ fn tcp_server_example() {
     println!("TCP服务器示例:");
     
     // 启动一个简单的回声服务器
-    let server_handle = thread::spawn(|| {
-        start_echo_server("127.0.0.1:8081")
+    let shutdown = Arc::new(AtomicBool::new(false));
+    let server_shutdown = shutdown.clone();
+    let server_handle = thread::spawn(move || {
+        start_echo_server("127.0.0.1:8081", server_shutdown)
     });
     
     // 等待服务器启动
@@ -184,41 +290,44 @@ fn tcp_server_example() {
     
     // 测试客户端连接
     match TcpStream::connect("127.0.0.1:8081") {
-        Ok(mut stream) => {
+        Ok(stream) => {
             println!("客户端连接成功");
             
-            // 发送测试消息
-            let messages = vec!["Hello", "World", "Rust", "Network"];
-            
-            for message in messages {
-                // 发送消息
-                if let Err(e) = writeln!(stream, "{}", message) {
-                    println!("发送失败: {}", e);
-                    break;
-                }
-                
-                // 读取回声
-                let mut reader = BufReader::new(&stream);
-                let mut response = String::new();
-                
-                match reader.read_line(&mut response) {
-                    Ok(_) => println!("回声: {}", response.trim()),
-                    Err(e) => {
-                        println!("读取失败: {}", e);
-                        break;
+            match LineProtocol::new(stream) {
+                Ok(mut protocol) => {
+                    // 发送测试消息
+                    let messages = vec!["Hello", "World", "Rust", "Network"];
+                    
+                    for message in messages {
+                        // 发送消息
+                        if let Err(e) = protocol.send_line(message) {
+                            println!("发送失败: {}", e);
+                            break;
+                        }
+                        
+                        // 读取回声
+                        match protocol.recv_line() {
+                            Ok(response) => println!("回声: {}", response),
+                            Err(e) => {
+                                println!("读取失败: {}", e);
+                                break;
+                            }
+                        }
+                        
+                        thread::sleep(Duration::from_millis(100));
                     }
+                    
+                    // 发送退出信号
+                    let _ = protocol.send_line("quit");
                 }
-                
-                thread::sleep(Duration::from_millis(100));
+                Err(e) => println!("创建协议封装失败: {}", e),
             }
-            
-            // 发送退出信号
-            let _ = writeln!(stream, "quit");
         }
         Err(e) => println!("客户端连接失败: {}", e),
     }
     
-    // 等待服务器线程结束
+    // 通知服务器停止并等待线程结束
+    shutdown.store(true, Ordering::SeqCst);
     let _ = server_handle.join();
 }
 
@@ -273,6 +382,30 @@ fn udp_communication() {
     
     // 等待服务器线程结束
     let _ = server_handle.join();
+    
+    // 可靠数据报：模拟每3个包丢1个，依然全部送达且不重复
+    println!("\n可靠UDP示例:");
+    let endpoints = ReliableUdp::bind("127.0.0.1:0").and_then(|sender| {
+        let receiver = ReliableUdp::bind("127.0.0.1:0")?.with_recv_timeout(Duration::from_millis(500));
+        Ok((sender.with_retransmit(Duration::from_millis(50), 5).with_simulated_loss(3), receiver))
+    });
+    match endpoints {
+        Ok((sender, receiver)) => {
+            let receiver_addr = receiver.local_addr().unwrap();
+            let receiver_handle = thread::spawn(move || {
+                while let Ok((data, from)) = receiver.recv() {
+                    println!("  从 {} 收到: {}", from, String::from_utf8_lossy(&data));
+                }
+            });
+            for message in ["可靠消息1", "可靠消息2", "可靠消息3"] {
+                if let Err(e) = sender.send(receiver_addr, message.as_bytes()) {
+                    println!("  发送失败: {}", e);
+                }
+            }
+            let _ = receiver_handle.join();
+        }
+        Err(e) => println!("可靠UDP初始化失败: {}", e),
+    }
 }
 
 // 多线程网络服务器
@@ -283,7 +416,7 @@ fn multithreaded_server() {
     
     // 启动多线程服务器
     let server_handle = thread::spawn(move || {
-        start_multithreaded_server("127.0.0.1:8083", rx)
+        start_multithreaded_server("127.0.0.1:8083", rx, 10)
     });
     
     // 等待服务器启动
@@ -337,14 +470,11 @@ fn network_utilities() {
     println!("端口扫描示例 (localhost):");
     let common_ports = vec![22, 80, 443, 3306, 5432, 6379, 8080];
     
-    for port in common_ports {
-        let addr = format!("127.0.0.1:{}", port);
-        match TcpStream::connect_timeout(
-            &addr.parse().unwrap(),
-            Duration::from_millis(100)
-        ) {
-            Ok(_) => println!("  端口 {} 开放", port),
-            Err(_) => println!("  端口 {} 关闭", port),
+    for (port, open) in scan_ports("127.0.0.1", common_ports, 4, Duration::from_millis(100)) {
+        if open {
+            println!("  端口 {} 开放", port);
+        } else {
+            println!("  端口 {} 关闭", port);
         }
     }
     
@@ -399,6 +529,37 @@ fn error_handling_and_retry() {
         }
     }
     
+    // 自动重连客户端：服务器每个连接只处理一条消息就关闭
+    println!("\n自动重连示例:");
+    match TcpListener::bind("127.0.0.1:0") {
+        Ok(listener) => {
+            let addr = listener.local_addr().unwrap().to_string();
+            let server = thread::spawn(move || {
+                for stream in listener.incoming().take(3).flatten() {
+                    let mut protocol = match LineProtocol::new(stream) {
+                        Ok(protocol) => protocol,
+                        Err(_) => continue,
+                    };
+                    if let Ok(line) = protocol.recv_line() {
+                        let _ = protocol.send_line(&format!("收到: {}", line));
+                    }
+                }
+            });
+            
+            let mut client = ReconnectingClient::new(&addr)
+                .with_backoff(Duration::from_millis(50), Duration::from_millis(500), 3);
+            for message in ["第一条", "第二条", "第三条"] {
+                match client.send_line(message) {
+                    Ok(response) => println!("  {}", response),
+                    Err(e) => println!("  发送失败: {}", e),
+                }
+            }
+            println!("  重连次数: {}", client.reconnect_count());
+            let _ = server.join();
+        }
+        Err(e) => println!("绑定失败: {}", e),
+    }
+    
     // 超时处理
     println!("\n超时处理示例:");
     match TcpStream::connect_timeout(
@@ -429,6 +590,18 @@ fn simple_http_client() {
     
     // 发起HTTP POST请求
     http_post_request("httpbin.org", 80, "/post", "test=data&name=rust");
+    
+    // 服务端视角：从字节流解析HTTP请求，半包时等待更多数据
+    let raw = b"POST /login HTTP/1.1\r\nHost: localhost\r\nContent-Length: 9\r\n\r\nuser=rust";
+    for end in [30, raw.len()] {
+        match parse_http_request(&raw[..end]) {
+            Ok((request, consumed)) => println!(
+                "解析请求: {} {} (头部 {} 个, body {} 字节, 消耗 {} 字节)",
+                request.method, request.path, request.headers.len(), request.body.len(), consumed
+            ),
+            Err(e) => println!("前 {} 字节: {}", end, e),
+        }
+    }
 }
 
 // 网络性能测试
@@ -441,41 +614,59 @@ fn network_performance_test() {
     
     println!("准备发送 {} 字节数据进行性能测试", data_size);
     
-    // 启动测试服务器
+    // 启动测试服务器，回传一半数据
     let server_handle = thread::spawn(|| {
-        start_performance_test_server("127.0.0.1:8084")
+        start_performance_test_server("127.0.0.1:8084", 0.5)
     });
     
     thread::sleep(Duration::from_millis(100));
     
     // 性能测试客户端
-    match TcpStream::connect("127.0.0.1:8084") {
-        Ok(mut stream) => {
-            let start = std::time::Instant::now();
-            
-            match stream.write_all(&test_data) {
-                Ok(_) => {
-                    let duration = start.elapsed();
-                    let throughput = data_size as f64 / duration.as_secs_f64() / 1024.0 / 1024.0;
-                    println!("发送完成，耗时: {:?}", duration);
-                    println!("吞吐量: {:.2} MB/s", throughput);
-                }
-                Err(e) => println!("发送失败: {}", e),
-            }
-        }
-        Err(e) => println!("连接失败: {}", e),
+    match run_throughput_client("127.0.0.1:8084".parse().unwrap(), &test_data) {
+        Ok(report) => println!("客户端: {}", report),
+        Err(e) => println!("客户端测试失败: {}", e),
     }
     
-    let _ = server_handle.join();
+    match server_handle.join() {
+        Ok(Ok(report)) => println!("服务器: {}", report),
+        Ok(Err(e)) => println!("服务器测试失败: {}", e),
+        Err(_) => println!("服务器线程panic"),
+    }
 }
 
 // 实际应用示例
 fn practical_examples() {
     // 聊天服务器示例
-    println!("聊天服务器概念演示:");
-    println!("- 多客户端连接管理");
-    println!("- 消息广播机制");
-    println!("- 用户认证和会话管理");
+    println!("聊天服务器演示:");
+    if let Ok(listener) = TcpListener::bind("127.0.0.1:0") {
+        let addr = listener.local_addr().unwrap();
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let chat = ChatServer::new();
+        let server = chat.clone();
+        let server_shutdown = shutdown.clone();
+        let server_handle = thread::spawn(move || server.serve(listener, server_shutdown));
+        
+        let result = (|| -> std::io::Result<()> {
+            let mut alice = TcpStream::connect(addr)?;
+            let bob = TcpStream::connect(addr)?;
+            bob.set_read_timeout(Some(Duration::from_secs(1)))?;
+            while chat.client_count() < 2 {
+                thread::sleep(Duration::from_millis(10));
+            }
+            
+            writeln!(alice, "大家好")?;
+            let mut line = String::new();
+            BufReader::new(&bob).read_line(&mut line)?;
+            println!("另一个客户端收到: {}", line.trim());
+            Ok(())
+        })();
+        if let Err(e) = result {
+            println!("聊天演示失败: {}", e);
+        }
+        
+        shutdown.store(true, Ordering::SeqCst);
+        let _ = server_handle.join();
+    }
     
     // 文件传输示例
     println!("\n文件传输概念演示:");
@@ -488,217 +679,1239 @@ fn practical_examples() {
     println!("- 请求转发");
     println!("- 负载均衡");
     println!("- 连接池管理");
+    
+    // 连接池复用演示：多次请求共用同一个连接
+    if let Ok(listener) = TcpListener::bind("127.0.0.1:0") {
+        let addr = listener.local_addr().unwrap().to_string();
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let server_shutdown = shutdown.clone();
+        let server_handle = thread::spawn(move || serve_echo(listener, server_shutdown));
+        
+        let pool = TcpConnectionPool::new(&addr, 2);
+        for i in 0..3 {
+            let result = pool.get().and_then(|mut conn| {
+                writeln!(conn, "请求 {}", i)?;
+                let mut response = String::new();
+                BufReader::new(&mut conn).read_line(&mut response)?;
+                Ok(response)
+            });
+            match result {
+                Ok(response) => println!("连接池响应: {}", response.trim()),
+                Err(e) => println!("连接池请求失败: {}", e),
+            }
+        }
+        println!("3次请求共建立 {} 个连接", pool.connections_opened());
+        
+        shutdown.store(true, Ordering::SeqCst);
+        let _ = server_handle.join();
+    }
 }
 
 // 辅助函数实现
 
-// 启动回声服务器
-fn start_echo_server(addr: &str) -> std::io::Result<()> {
-    let listener = TcpListener::bind(addr)?;
-    println!("回声服务器启动在: {}", addr);
-    
-    for stream in listener.incoming() {
-        match stream {
-            Ok(mut stream) => {
-                let mut reader = BufReader::new(&stream);
-                let mut writer = BufWriter::new(&stream);
-                let mut line = String::new();
-                
-                loop {
-                    line.clear();
-                    match reader.read_line(&mut line) {
-                        Ok(0) => break, // 连接关闭
-                        Ok(_) => {
-                            let trimmed = line.trim();
-                            if trimmed == "quit" {
-                                println!("客户端请求退出");
-                                return Ok(());
-                            }
-                            
-                            // 回声
-                            if let Err(e) = writeln!(writer, "回声: {}", trimmed) {
-                                println!("写入失败: {}", e);
-                                break;
-                            }
-                            
-                            if let Err(e) = writer.flush() {
-                                println!("刷新失败: {}", e);
-                                break;
-                            }
-                        }
-                        Err(e) => {
-                            println!("读取失败: {}", e);
-                            break;
-                        }
-                    }
-                }
-            }
-            Err(e) => println!("连接失败: {}", e),
-        }
-    }
-    
-    Ok(())
+// 并发端口扫描：最多concurrency个工作线程从任务通道取端口探测
+// 结果按输入端口的顺序返回，主机无法解析时所有端口都视为关闭
+fn scan_ports(
+    host: &str,
+    ports: impl IntoIterator<Item = u16>,
+    concurrency: usize,
+    timeout: Duration,
+) -> Vec<(u16, bool)> {
+    scan_ports_with(host, ports, concurrency, move |addr| {
+        TcpStream::connect_timeout(&addr, timeout).is_ok()
+    })
 }
 
-// 启动UDP服务器
-fn start_udp_server(addr: &str) -> std::io::Result<()> {
-    let socket = UdpSocket::bind(addr)?;
-    println!("UDP服务器启动在: {}", addr);
-    
-    let mut buffer = [0; 1024];
+// probe负责探测单个地址，返回端口是否开放
+fn scan_ports_with<F>(
+    host: &str,
+    ports: impl IntoIterator<Item = u16>,
+    concurrency: usize,
+    probe: F,
+) -> Vec<(u16, bool)>
+where
+    F: Fn(SocketAddr) -> bool + Send + Sync + 'static,
+{
+    let ports: Vec<u16> = ports.into_iter().collect();
+    let ip = match (host, 0).to_socket_addrs().ok().and_then(|mut addrs| addrs.next()) {
+        Some(addr) => addr.ip(),
+        None => return ports.into_iter().map(|port| (port, false)).collect(),
+    };
+    
+    let (job_tx, job_rx) = mpsc::channel::<(usize, u16)>();
+    let job_rx = Arc::new(Mutex::new(job_rx));
+    let (result_tx, result_rx) = mpsc::channel();
+    
+    for job in ports.iter().copied().enumerate() {
+        job_tx.send(job).unwrap();
+    }
+    drop(job_tx);
+    
+    let probe = Arc::new(probe);
+    let worker_count = concurrency.max(1).min(ports.len());
+    let mut workers = Vec::with_capacity(worker_count);
+    for _ in 0..worker_count {
+        let job_rx = Arc::clone(&job_rx);
+        let result_tx = result_tx.clone();
+        let probe = Arc::clone(&probe);
+        workers.push(thread::spawn(move || loop {
+            // 取到任务后立即释放锁，让其他线程也能取任务
+            let job = job_rx.lock().unwrap().recv();
+            let (index, port) = match job {
+                Ok(job) => job,
+                Err(_) => break, // 任务已取完
+            };
+            let open = probe(SocketAddr::new(ip, port));
+            let _ = result_tx.send((index, port, open));
+        }));
+    }
+    drop(result_tx);
     
-    loop {
-        match socket.recv_from(&mut buffer) {
-            Ok((received, from)) => {
-                let message = String::from_utf8_lossy(&buffer[..received]);
-                
-                if message.trim() == "quit" {
-                    println!("UDP服务器收到退出信号");
-                    break;
-                }
-                
-                println!("UDP服务器收到: {} 从 {}", message.trim(), from);
-                
-                // 发送回声
-                let response = format!("UDP回声: {}", message.trim());
-                if let Err(e) = socket.send_to(response.as_bytes(), from) {
-                    println!("UDP发送失败: {}", e);
-                }
-            }
-            Err(e) => {
-                println!("UDP接收失败: {}", e);
-                break;
-            }
-        }
+    let mut results: Vec<(usize, u16, bool)> = result_rx.iter().collect();
+    for worker in workers {
+        let _ = worker.join();
     }
     
-    Ok(())
+    results.sort_by_key(|&(index, _, _)| index);
+    results.into_iter().map(|(_, port, open)| (port, open)).collect()
 }
 
-// 启动多线程服务器
-fn start_multithreaded_server(addr: &str, shutdown_rx: mpsc::Receiver<()>) -> std::io::Result<()> {
+// 启动回声服务器，shutdown被置为true后停止
+fn start_echo_server(addr: &str, shutdown: Arc<AtomicBool>) -> std::io::Result<usize> {
     let listener = TcpListener::bind(addr)?;
+    println!("回声服务器启动在: {}", listener.local_addr()?);
+    
+    serve_echo(listener, shutdown)
+}
+
+// 回声服务循环：每个客户端一个线程，停止时等待所有客户端线程结束
+// 返回服务过的客户端数量
+fn serve_echo(listener: TcpListener, shutdown: Arc<AtomicBool>) -> std::io::Result<usize> {
     listener.set_nonblocking(true)?;
-    println!("多线程服务器启动在: {}", addr);
     
-    let mut client_count = 0;
+    let mut handles = Vec::new();
     
-    loop {
-        // 检查是否收到停止信号
-        if shutdown_rx.try_recv().is_ok() {
-            println!("多线程服务器收到停止信号");
-            break;
-        }
-        
+    while !shutdown.load(Ordering::SeqCst) {
         match listener.accept() {
             Ok((stream, addr)) => {
-                client_count += 1;
-                let client_id = client_count;
-                println!("客户端 {} 连接: {}", client_id, addr);
+                // 客户端使用阻塞模式，通过读超时定期检查停止标志；
+                // 设置失败只放弃这个连接，服务器继续运行
+                let configured = stream
+                    .set_nonblocking(false)
+                    .and_then(|_| stream.set_read_timeout(Some(Duration::from_millis(100))));
+                if let Err(e) = configured {
+                    println!("设置客户端 {} 失败: {}", addr, e);
+                    continue;
+                }
                 
-                thread::spawn(move || {
-                    handle_client(stream, client_id);
-                });
+                let shutdown = shutdown.clone();
+                handles.push(thread::spawn(move || handle_echo_client(stream, shutdown)));
             }
             Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
-                // 非阻塞模式下没有连接，继续循环
                 thread::sleep(Duration::from_millis(10));
-                continue;
-            }
-            Err(e) => {
-                println!("接受连接失败: {}", e);
-                break;
             }
+            Err(e) => println!("连接失败: {}", e),
         }
     }
     
-    Ok(())
+    let client_count = handles.len();
+    for handle in handles {
+        let _ = handle.join();
+    }
+    
+    println!("回声服务器已停止，共服务 {} 个客户端", client_count);
+    Ok(client_count)
 }
 
-// 处理客户端连接
-fn handle_client(mut stream: TcpStream, client_id: usize) {
+// 处理单个回声客户端，客户端发送quit或服务器停止时结束
+fn handle_echo_client(stream: TcpStream, shutdown: Arc<AtomicBool>) {
     let mut reader = BufReader::new(&stream);
     let mut writer = BufWriter::new(&stream);
     let mut line = String::new();
     
     loop {
-        line.clear();
         match reader.read_line(&mut line) {
-            Ok(0) => {
-                println!("客户端 {} 断开连接", client_id);
-                break;
-            }
+            Ok(0) => break, // 连接关闭
             Ok(_) => {
-                let message = line.trim();
-                println!("客户端 {} 发送: {}", client_id, message);
+                let trimmed = line.trim().to_string();
+                line.clear();
                 
-                // 发送响应
-                let response = format!("服务器收到客户端 {} 的消息: {}", client_id, message);
-                if let Err(e) = writeln!(writer, "{}", response) {
-                    println!("发送响应失败: {}", e);
+                if trimmed == "quit" {
+                    println!("客户端请求退出");
+                    break;
+                }
+                
+                // 回声
+                if let Err(e) = writeln!(writer, "回声: {}", trimmed) {
+                    println!("写入失败: {}", e);
                     break;
                 }
                 
                 if let Err(e) = writer.flush() {
-                    println!("刷新缓冲区失败: {}", e);
+                    println!("刷新失败: {}", e);
+                    break;
+                }
+            }
+            Err(ref e) if matches!(
+                e.kind(),
+                std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+            ) => {
+                // 读超时：已读到的半行保留在line中，检查是否需要停止
+                if shutdown.load(Ordering::SeqCst) {
                     break;
                 }
             }
             Err(e) => {
-                println!("读取客户端 {} 消息失败: {}", client_id, e);
+                println!("读取失败: {}", e);
                 break;
             }
         }
     }
 }
 
-// HTTP GET请求
-fn http_get_request(host: &str, port: u16, path: &str) {
-    match TcpStream::connect_timeout(
-        &format!("{}:{}", host, port).parse().unwrap(),
-        Duration::from_secs(5)
-    ) {
-        Ok(mut stream) => {
-            // 构造HTTP请求
-            let request = format!(
-                "GET {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
-                path, host
-            );
-            
-            // 发送请求
-            if let Err(e) = stream.write_all(request.as_bytes()) {
-                println!("发送HTTP请求失败: {}", e);
-                return;
+// 基于行的请求/响应协议：每条消息占一行，兼容 \r\n 和 \n 结尾
+struct LineProtocol {
+    reader: BufReader<TcpStream>,
+    writer: BufWriter<TcpStream>,
+    // 读超时等错误发生时已读到的半行数据，下次接收时继续拼接
+    pending: String,
+}
+
+impl LineProtocol {
+    fn new(stream: TcpStream) -> std::io::Result<Self> {
+        let reader = BufReader::new(stream.try_clone()?);
+        Ok(LineProtocol {
+            reader,
+            writer: BufWriter::new(stream),
+            pending: String::new(),
+        })
+    }
+    
+    fn send_line(&mut self, line: &str) -> std::io::Result<()> {
+        self.writer.write_all(line.as_bytes())?;
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()
+    }
+    
+    // 读取一整行（不含行尾），对端关闭连接时返回UnexpectedEof；
+    // 连接关闭前最后一行没有换行符时原样返回
+    fn recv_line(&mut self) -> std::io::Result<String> {
+        let read = self.reader.read_line(&mut self.pending)?;
+        if read == 0 && self.pending.is_empty() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "连接已关闭",
+            ));
+        }
+        
+        let mut line = std::mem::take(&mut self.pending);
+        if line.ends_with('\n') {
+            line.pop();
+            if line.ends_with('\r') {
+                line.pop();
             }
-            
-            // 读取响应
-            let mut response = String::new();
-            match stream.read_to_string(&mut response) {
-                Ok(_) => {
-                    let lines: Vec<&str> = response.lines().collect();
-                    if !lines.is_empty() {
-                        println!("HTTP响应状态: {}", lines[0]);
-                        
-                        // 查找响应体
-                        if let Some(body_start) = response.find("\r\n\r\n") {
-                            let body = &response[body_start + 4..];
-                            if !body.is_empty() {
-                                println!("响应体预览: {}...", 
-                                    &body[..body.len().min(100)]);
-                            }
-                        }
+        }
+        Ok(line)
+    }
+}
+
+// 自动重连的行协议客户端：连接失败时按指数退避重试，
+// 发送过程中发现连接已断开时重新连接并重发一次
+struct ReconnectingClient {
+    addr: String,
+    conn: Option<LineProtocol>,
+    initial_delay: Duration,
+    max_delay: Duration,
+    max_attempts: usize,
+    connected_before: bool,
+    reconnects: usize,
+}
+
+impl ReconnectingClient {
+    // 创建时不连接，第一次发送时才建立连接
+    fn new(addr: &str) -> Self {
+        ReconnectingClient {
+            addr: addr.to_string(),
+            conn: None,
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(2),
+            max_attempts: 5,
+            connected_before: false,
+            reconnects: 0,
+        }
+    }
+    
+    fn with_backoff(mut self, initial_delay: Duration, max_delay: Duration, max_attempts: usize) -> Self {
+        self.initial_delay = initial_delay;
+        self.max_delay = max_delay;
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+    
+    // 发送一行并等待一行响应
+    // 注意：断线前请求可能已被服务器处理，重发要求请求是幂等的
+    fn send_line(&mut self, line: &str) -> std::io::Result<String> {
+        let mut retried = false;
+        loop {
+            let conn = self.connection()?;
+            match conn.send_line(line).and_then(|_| conn.recv_line()) {
+                Ok(response) => return Ok(response),
+                Err(e) => {
+                    // 出错后协议状态不可信，丢弃连接
+                    self.conn = None;
+                    if retried || !is_disconnect(&e) {
+                        return Err(e);
                     }
+                    retried = true;
+                }
+            }
+        }
+    }
+    
+    // 成功重新建立连接的次数（不含第一次连接）
+    fn reconnect_count(&self) -> usize {
+        self.reconnects
+    }
+    
+    fn connection(&mut self) -> std::io::Result<&mut LineProtocol> {
+        if self.conn.is_none() {
+            let stream = self.connect_with_backoff()?;
+            if self.connected_before {
+                self.reconnects += 1;
+            }
+            self.connected_before = true;
+            self.conn = Some(LineProtocol::new(stream)?);
+        }
+        Ok(self.conn.as_mut().unwrap())
+    }
+    
+    fn connect_with_backoff(&self) -> std::io::Result<TcpStream> {
+        let mut delay = self.initial_delay;
+        let mut attempt = 1;
+        loop {
+            match TcpStream::connect(self.addr.as_str()) {
+                Ok(stream) => return Ok(stream),
+                Err(e) if attempt >= self.max_attempts => return Err(e),
+                Err(_) => {
+                    thread::sleep(delay);
+                    delay = (delay * 2).min(self.max_delay);
+                    attempt += 1;
                 }
-                Err(e) => println!("读取HTTP响应失败: {}", e),
             }
         }
-        Err(e) => println!("HTTP连接失败: {}", e),
     }
 }
 
-// HTTP POST请求
-fn http_post_request(host: &str, port: u16, path: &str, data: &str) {
-    match TcpStream::connect_timeout(
+// 对端关闭或重置连接导致的错误
+fn is_disconnect(e: &std::io::Error) -> bool {
+    use std::io::ErrorKind;
+    matches!(
+        e.kind(),
+        ErrorKind::UnexpectedEof
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::BrokenPipe
+            | ErrorKind::NotConnected
+    )
+}
+
+// TCP连接池：按目标地址缓存空闲连接，避免每次请求都重新建立连接
+struct TcpConnectionPool {
+    addr: String,
+    max_idle: usize,
+    idle: Arc<Mutex<HashMap<SocketAddr, Vec<TcpStream>>>>,
+    opened: AtomicUsize,
+}
+
+impl TcpConnectionPool {
+    fn new(addr: &str, max_idle: usize) -> Self {
+        TcpConnectionPool {
+            addr: addr.to_string(),
+            max_idle,
+            idle: Arc::new(Mutex::new(HashMap::new())),
+            opened: AtomicUsize::new(0),
+        }
+    }
+    
+    // 优先复用仍然存活的空闲连接，没有时新建连接
+    fn get(&self) -> std::io::Result<PooledStream> {
+        let addrs: Vec<SocketAddr> = self.addr.to_socket_addrs()?.collect();
+        
+        {
+            let mut idle = self.idle.lock().unwrap();
+            for addr in &addrs {
+                if let Some(streams) = idle.get_mut(addr) {
+                    while let Some(stream) = streams.pop() {
+                        if is_connection_alive(&stream) {
+                            return Ok(self.wrap(stream, *addr));
+                        }
+                    }
+                }
+            }
+        }
+        
+        let stream = TcpStream::connect(&addrs[..])?;
+        let peer = stream.peer_addr()?;
+        self.opened.fetch_add(1, Ordering::SeqCst);
+        Ok(self.wrap(stream, peer))
+    }
+    
+    // 池创建以来实际建立的连接数
+    fn connections_opened(&self) -> usize {
+        self.opened.load(Ordering::SeqCst)
+    }
+    
+    fn wrap(&self, stream: TcpStream, peer: SocketAddr) -> PooledStream {
+        PooledStream {
+            stream: Some(stream),
+            peer,
+            idle: self.idle.clone(),
+            max_idle: self.max_idle,
+            closed: false,
+        }
+    }
+}
+
+// 零字节探测：非阻塞地peek一个字节，读到EOF或残留数据都说明连接不可复用
+fn is_connection_alive(stream: &TcpStream) -> bool {
+    if stream.set_nonblocking(true).is_err() {
+        return false;
+    }
+    
+    let mut probe = [0u8; 1];
+    let alive = match stream.peek(&mut probe) {
+        Ok(_) => false,
+        Err(ref e) => e.kind() == std::io::ErrorKind::WouldBlock,
+    };
+    
+    alive && stream.set_nonblocking(false).is_ok()
+}
+
+// 从连接池借出的连接，drop时归还（对端已关闭或池已满时直接关闭）
+struct PooledStream {
+    stream: Option<TcpStream>,
+    peer: SocketAddr,
+    idle: Arc<Mutex<HashMap<SocketAddr, Vec<TcpStream>>>>,
+    max_idle: usize,
+    closed: bool,
+}
+
+impl std::ops::Deref for PooledStream {
+    type Target = TcpStream;
+    
+    fn deref(&self) -> &TcpStream {
+        self.stream.as_ref().unwrap()
+    }
+}
+
+impl Read for PooledStream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let result = self.stream.as_mut().unwrap().read(buf);
+        match result {
+            Ok(0) if !buf.is_empty() => self.closed = true,
+            Err(_) => self.closed = true,
+            _ => {}
+        }
+        result
+    }
+}
+
+impl Write for PooledStream {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let result = self.stream.as_mut().unwrap().write(buf);
+        if result.is_err() {
+            self.closed = true;
+        }
+        result
+    }
+    
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.stream.as_mut().unwrap().flush()
+    }
+}
+
+impl Drop for PooledStream {
+    fn drop(&mut self) {
+        if self.closed {
+            return;
+        }
+        
+        if let Some(stream) = self.stream.take() {
+            let mut idle = self.idle.lock().unwrap();
+            let total: usize = idle.values().map(|streams| streams.len()).sum();
+            if total < self.max_idle {
+                idle.entry(self.peer).or_default().push(stream);
+            }
+        }
+    }
+}
+
+// 启动UDP回声服务器，收到quit时停止
+fn start_udp_server(addr: &str) -> std::io::Result<()> {
+    let shutdown = Arc::new(AtomicBool::new(false));
+    let stop = shutdown.clone();
+    
+    start_udp_server_with(addr, shutdown, move |data: &[u8], from: SocketAddr| {
+        if String::from_utf8_lossy(data).trim() == "quit" {
+            println!("UDP服务器收到退出信号");
+            stop.store(true, Ordering::SeqCst);
+            return None;
+        }
+        udp_echo_handler(data, from)
+    })
+}
+
+// 默认的回声处理器
+fn udp_echo_handler(data: &[u8], from: SocketAddr) -> Option<Vec<u8>> {
+    let message = String::from_utf8_lossy(data);
+    println!("UDP服务器收到: {} 从 {}", message.trim(), from);
+    Some(format!("UDP回声: {}", message.trim()).into_bytes())
+}
+
+// 启动使用自定义处理器的UDP服务器
+fn start_udp_server_with<F>(addr: &str, shutdown: Arc<AtomicBool>, handler: F) -> std::io::Result<()>
+where
+    F: FnMut(&[u8], SocketAddr) -> Option<Vec<u8>>,
+{
+    let socket = UdpSocket::bind(addr)?;
+    println!("UDP服务器启动在: {}", socket.local_addr()?);
+    
+    serve_udp_with(socket, shutdown, handler)
+}
+
+// UDP请求-响应循环：处理器返回Some时回复，返回None时不回复
+// 通过读超时定期检查shutdown标志
+fn serve_udp_with<F>(socket: UdpSocket, shutdown: Arc<AtomicBool>, mut handler: F) -> std::io::Result<()>
+where
+    F: FnMut(&[u8], SocketAddr) -> Option<Vec<u8>>,
+{
+    socket.set_read_timeout(Some(Duration::from_millis(100)))?;
+    
+    let mut buffer = [0; 1024];
+    
+    while !shutdown.load(Ordering::SeqCst) {
+        match socket.recv_from(&mut buffer) {
+            Ok((received, from)) => {
+                if let Some(reply) = handler(&buffer[..received], from) {
+                    if let Err(e) = socket.send_to(&reply, from) {
+                        println!("UDP发送失败: {}", e);
+                    }
+                }
+            }
+            Err(ref e) if matches!(
+                e.kind(),
+                std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+            ) => continue,
+            Err(e) => {
+                println!("UDP接收失败: {}", e);
+                return Err(e);
+            }
+        }
+    }
+    
+    Ok(())
+}
+
+// 可靠数据报：数据包头部9字节（类型1字节 + 大端序号4字节 + 大端下限4字节），
+// 确认包头部5字节（类型 + 序号）。序号按目标地址各自从0递增，
+// 发送方停等确认并超时重传，接收方按(地址, 序号)去重。
+// 下限表示发送方小于它的序号都已确认或放弃，接收方据此清理去重记录
+const RELIABLE_DATA: u8 = 0;
+const RELIABLE_ACK: u8 = 1;
+// 套接字读超时，用于轮询截止时间
+const RELIABLE_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+// 发送方对每个目标地址维护的状态
+#[derive(Default)]
+struct PeerSendState {
+    next_seq: u32,
+    // 正在等待确认的序号，值表示是否已收到确认
+    awaiting: BTreeMap<u32, bool>,
+}
+
+impl PeerSendState {
+    // 小于该值的序号都不会再重传
+    fn floor(&self) -> u32 {
+        self.awaiting.keys().next().copied().unwrap_or(self.next_seq)
+    }
+}
+
+// 接收方对每个来源地址记录的已交付序号：小于floor的都视为已处理，只保存floor及以上的
+#[derive(Default)]
+struct DeliveredSeqs {
+    floor: u32,
+    seen: HashSet<u32>,
+}
+
+impl DeliveredSeqs {
+    // seq第一次出现时返回true；sender_floor是数据包携带的发送方下限
+    fn insert(&mut self, seq: u32, sender_floor: u32) -> bool {
+        if sender_floor > self.floor {
+            self.floor = sender_floor;
+            self.seen.retain(|&s| s >= sender_floor);
+        }
+        if seq < self.floor || !self.seen.insert(seq) {
+            return false;
+        }
+        // 连续交付的序号并入floor
+        while self.seen.remove(&self.floor) {
+            self.floor += 1;
+        }
+        true
+    }
+}
+
+struct ReliableUdp {
+    socket: UdpSocket,
+    retransmit_timeout: Duration,
+    max_retries: usize,
+    recv_timeout: Option<Duration>,
+    // 作为发送方时各目标地址的序号和待确认状态
+    peers: Mutex<HashMap<SocketAddr, PeerSendState>>,
+    // 作为接收方时各来源地址已交付的序号
+    delivered: Mutex<HashMap<SocketAddr, DeliveredSeqs>>,
+    // 等待确认期间收到的数据报，留给recv交付
+    pending: Mutex<VecDeque<(Vec<u8>, SocketAddr)>>,
+    // 模拟丢包：每drop_every个发出的包丢弃一个，0表示不丢
+    drop_every: usize,
+    sent_packets: AtomicUsize,
+}
+
+impl ReliableUdp {
+    fn bind(addr: &str) -> std::io::Result<Self> {
+        let socket = UdpSocket::bind(addr)?;
+        socket.set_read_timeout(Some(RELIABLE_POLL_INTERVAL))?;
+        Ok(ReliableUdp {
+            socket,
+            retransmit_timeout: Duration::from_millis(200),
+            max_retries: 5,
+            recv_timeout: None,
+            peers: Mutex::new(HashMap::new()),
+            delivered: Mutex::new(HashMap::new()),
+            pending: Mutex::new(VecDeque::new()),
+            drop_every: 0,
+            sent_packets: AtomicUsize::new(0),
+        })
+    }
+    
+    fn with_retransmit(mut self, timeout: Duration, max_retries: usize) -> Self {
+        self.retransmit_timeout = timeout;
+        self.max_retries = max_retries;
+        self
+    }
+    
+    // recv等待超过timeout时返回TimedOut，默认一直等待
+    fn with_recv_timeout(mut self, timeout: Duration) -> Self {
+        self.recv_timeout = Some(timeout);
+        self
+    }
+    
+    fn with_simulated_loss(mut self, drop_every: usize) -> Self {
+        self.drop_every = drop_every;
+        self
+    }
+    
+    fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+    
+    // 发送并阻塞到收到确认，重试max_retries次仍未确认时返回TimedOut
+    fn send(&self, addr: SocketAddr, payload: &[u8]) -> std::io::Result<()> {
+        let (seq, floor) = {
+            let mut peers = self.peers.lock().unwrap();
+            let peer = peers.entry(addr).or_default();
+            let seq = peer.next_seq;
+            peer.next_seq += 1;
+            peer.awaiting.insert(seq, false);
+            (seq, peer.floor())
+        };
+        let mut packet = Vec::with_capacity(9 + payload.len());
+        packet.push(RELIABLE_DATA);
+        packet.extend_from_slice(&seq.to_be_bytes());
+        packet.extend_from_slice(&floor.to_be_bytes());
+        packet.extend_from_slice(payload);
+        
+        let result = self.send_until_acked(addr, seq, &packet);
+        // 无论成功还是放弃，这个序号都不再等待确认
+        if let Some(peer) = self.peers.lock().unwrap().get_mut(&addr) {
+            peer.awaiting.remove(&seq);
+        }
+        result
+    }
+    
+    fn send_until_acked(&self, addr: SocketAddr, seq: u32, packet: &[u8]) -> std::io::Result<()> {
+        let mut buffer = [0; 65536];
+        for _ in 0..=self.max_retries {
+            self.transmit(packet, addr)?;
+            
+            let deadline = std::time::Instant::now() + self.retransmit_timeout;
+            while std::time::Instant::now() < deadline {
+                let acked = self.peers.lock().unwrap()
+                    .get(&addr)
+                    .and_then(|peer| peer.awaiting.get(&seq).copied());
+                if acked == Some(true) {
+                    return Ok(());
+                }
+                if let Some((size, from)) = self.poll(&mut buffer)? {
+                    if let Some(datagram) = self.handle_packet(&buffer[..size], from)? {
+                        self.pending.lock().unwrap().push_back(datagram);
+                    }
+                }
+            }
+        }
+        
+        Err(std::io::Error::new(
+            std::io::ErrorKind::TimedOut,
+            format!("序号 {} 重试 {} 次后仍未收到确认", seq, self.max_retries),
+        ))
+    }
+    
+    // 接收下一个未交付过的数据报
+    fn recv(&self) -> std::io::Result<(Vec<u8>, SocketAddr)> {
+        let deadline = self.recv_timeout.map(|timeout| std::time::Instant::now() + timeout);
+        let mut buffer = [0; 65536];
+        loop {
+            if let Some(datagram) = self.pending.lock().unwrap().pop_front() {
+                return Ok(datagram);
+            }
+            if deadline.is_some_and(|deadline| std::time::Instant::now() >= deadline) {
+                return Err(std::io::Error::new(std::io::ErrorKind::TimedOut, "接收超时"));
+            }
+            if let Some((size, from)) = self.poll(&mut buffer)? {
+                if let Some(datagram) = self.handle_packet(&buffer[..size], from)? {
+                    return Ok(datagram);
+                }
+            }
+        }
+    }
+    
+    // 读取一个包，读超时返回None
+    fn poll(&self, buffer: &mut [u8]) -> std::io::Result<Option<(usize, SocketAddr)>> {
+        match self.socket.recv_from(buffer) {
+            Ok(received) => Ok(Some(received)),
+            Err(ref e) if matches!(
+                e.kind(),
+                std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+            ) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+    
+    // 数据包总是回复确认（对方可能没收到上次的确认），只有首次收到时才返回内容
+    fn handle_packet(&self, packet: &[u8], from: SocketAddr) -> std::io::Result<Option<(Vec<u8>, SocketAddr)>> {
+        if packet.len() < 5 {
+            return Ok(None); // 忽略格式错误的包
+        }
+        let seq = u32::from_be_bytes([packet[1], packet[2], packet[3], packet[4]]);
+        
+        match packet[0] {
+            RELIABLE_DATA if packet.len() >= 9 => {
+                let mut ack = vec![RELIABLE_ACK];
+                ack.extend_from_slice(&seq.to_be_bytes());
+                self.transmit(&ack, from)?;
+                
+                let floor = u32::from_be_bytes([packet[5], packet[6], packet[7], packet[8]]);
+                let first_time = self.delivered.lock().unwrap().entry(from).or_default().insert(seq, floor);
+                Ok(first_time.then(|| (packet[9..].to_vec(), from)))
+            }
+            // 只记录仍在等待的确认，迟到的重复确认直接丢弃
+            RELIABLE_ACK => {
+                if let Some(acked) = self.peers.lock().unwrap()
+                    .get_mut(&from)
+                    .and_then(|peer| peer.awaiting.get_mut(&seq))
+                {
+                    *acked = true;
+                }
+                Ok(None)
+            }
+            _ => Ok(None),
+        }
+    }
+    
+    fn transmit(&self, packet: &[u8], addr: SocketAddr) -> std::io::Result<()> {
+        let count = self.sent_packets.fetch_add(1, Ordering::SeqCst) + 1;
+        if self.drop_every > 0 && count.is_multiple_of(self.drop_every) {
+            return Ok(()); // 模拟丢包
+        }
+        self.socket.send_to(packet, addr)?;
+        Ok(())
+    }
+}
+
+// 启动多线程服务器
+fn start_multithreaded_server(
+    addr: &str,
+    shutdown_rx: mpsc::Receiver<()>,
+    max_connections: usize,
+) -> std::io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    println!("多线程服务器启动在: {} (最大连接数 {})", addr, max_connections);
+    
+    serve_with_graceful_shutdown(listener, shutdown_rx, Duration::from_secs(5), max_connections)?;
+    Ok(())
+}
+
+// 活跃连接跟踪：记录正在处理的连接数，并保存流的克隆以便强制断开
+#[derive(Clone, Default)]
+struct ActiveConnections {
+    count: Arc<AtomicUsize>,
+    streams: Arc<Mutex<HashMap<usize, TcpStream>>>,
+}
+
+impl ActiveConnections {
+    fn register(&self, client_id: usize, stream: &TcpStream) -> ConnectionGuard {
+        if let Ok(clone) = stream.try_clone() {
+            self.streams.lock().unwrap().insert(client_id, clone);
+        }
+        self.count.fetch_add(1, Ordering::SeqCst);
+        ConnectionGuard {
+            client_id,
+            connections: self.clone(),
+        }
+    }
+    
+    fn count(&self) -> usize {
+        self.count.load(Ordering::SeqCst)
+    }
+    
+    // 关闭所有仍在处理的连接，返回被强制断开的数量
+    fn force_close_all(&self) -> usize {
+        let streams = self.streams.lock().unwrap();
+        for stream in streams.values() {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+        streams.len()
+    }
+}
+
+// 连接处理线程持有的守卫，线程结束（包括panic）时自动注销
+struct ConnectionGuard {
+    client_id: usize,
+    connections: ActiveConnections,
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        self.connections.streams.lock().unwrap().remove(&self.client_id);
+        self.connections.count.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+// 聊天广播服务器：每个客户端一个读线程，收到的每一行转发给其他所有客户端
+#[derive(Clone, Default)]
+struct ChatServer {
+    // 各客户端连接的写端。广播时复制出Arc后释放表锁，
+    // 每个连接自己的Mutex保证并发广播的行不会交错
+    clients: Arc<Mutex<HashMap<usize, Arc<Mutex<TcpStream>>>>>,
+    next_id: Arc<AtomicUsize>,
+}
+
+impl ChatServer {
+    fn new() -> Self {
+        Self::default()
+    }
+    
+    fn client_count(&self) -> usize {
+        self.clients.lock().unwrap().len()
+    }
+    
+    // 接受连接直到shutdown被置为true，停止时断开所有客户端并等待读线程结束
+    fn serve(&self, listener: TcpListener, shutdown: Arc<AtomicBool>) -> std::io::Result<()> {
+        listener.set_nonblocking(true)?;
+        
+        let mut handles = Vec::new();
+        // 停止时用来关闭连接的句柄，不经过写端的锁，避免被阻塞的写入卡住
+        let mut controls = Vec::new();
+        while !shutdown.load(Ordering::SeqCst) {
+            match listener.accept() {
+                Ok((stream, addr)) => {
+                    // 单个连接设置失败不应该让整个服务器退出
+                    if let Err(e) = stream.set_nonblocking(false) {
+                        println!("设置客户端 {} 为阻塞模式失败: {}", addr, e);
+                        continue;
+                    }
+                    let clones = stream
+                        .try_clone()
+                        .and_then(|writer| Ok((writer, stream.try_clone()?)));
+                    let (writer, control) = match clones {
+                        Ok(clones) => clones,
+                        Err(e) => {
+                            println!("复制客户端 {} 的连接失败: {}", addr, e);
+                            continue;
+                        }
+                    };
+                    controls.push(control);
+                    let id = self.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+                    self.join(id, writer);
+                    
+                    let server = self.clone();
+                    handles.push(thread::spawn(move || server.handle_client(id, stream)));
+                }
+                Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                    thread::sleep(Duration::from_millis(10));
+                }
+                Err(e) => println!("接受连接失败: {}", e),
+            }
+        }
+        
+        // 关闭连接让阻塞在读取上的线程退出
+        for control in &controls {
+            let _ = control.shutdown(Shutdown::Both);
+        }
+        for handle in handles {
+            let _ = handle.join();
+        }
+        Ok(())
+    }
+    
+    fn join(&self, id: usize, writer: TcpStream) {
+        self.clients.lock().unwrap().insert(id, Arc::new(Mutex::new(writer)));
+        self.broadcast(id, &format!("[系统] 用户 {} 加入聊天室", id));
+    }
+    
+    fn leave(&self, id: usize) {
+        if self.clients.lock().unwrap().remove(&id).is_some() {
+            self.broadcast(id, &format!("[系统] 用户 {} 离开聊天室", id));
+        }
+    }
+    
+    fn handle_client(&self, id: usize, stream: TcpStream) {
+        let reader = BufReader::new(stream);
+        for line in reader.lines() {
+            match line {
+                Ok(line) => self.broadcast(id, &format!("用户 {}: {}", id, line.trim_end())),
+                Err(_) => break,
+            }
+        }
+        self.leave(id);
+    }
+    
+    // 发给除from以外的所有客户端，写入失败的客户端视为已断开
+    // 先在锁内复制一份接收者列表，写入时不持锁，慢客户端不会阻塞加入、离开和其他广播
+    fn broadcast(&self, from: usize, message: &str) {
+        let receivers: Vec<(usize, Arc<Mutex<TcpStream>>)> = self
+            .clients
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(&id, _)| id != from)
+            .map(|(&id, stream)| (id, Arc::clone(stream)))
+            .collect();
+        
+        let line = format!("{}\n", message);
+        let failed: Vec<usize> = receivers
+            .into_iter()
+            .filter(|(_, stream)| stream.lock().unwrap().write_all(line.as_bytes()).is_err())
+            .map(|(id, _)| id)
+            .collect();
+        
+        if failed.is_empty() {
+            return;
+        }
+        let mut clients = self.clients.lock().unwrap();
+        for id in failed {
+            if let Some(stream) = clients.remove(&id) {
+                let _ = stream.lock().unwrap().shutdown(Shutdown::Both);
+            }
+        }
+    }
+}
+
+// 优雅关闭：收到信号后停止accept，等待活跃连接处理完毕；
+// 超过drain_timeout仍未结束的连接会被强制断开。返回被强制断开的连接数。
+// 活跃连接达到max_connections时，新客户端收到一行繁忙提示后被关闭
+fn serve_with_graceful_shutdown(
+    listener: TcpListener,
+    shutdown_rx: mpsc::Receiver<()>,
+    drain_timeout: Duration,
+    max_connections: usize,
+) -> std::io::Result<usize> {
+    listener.set_nonblocking(true)?;
+    
+    let connections = ActiveConnections::default();
+    let mut client_count = 0;
+    
+    loop {
+        // 检查是否收到停止信号
+        if shutdown_rx.try_recv().is_ok() {
+            println!("多线程服务器收到停止信号");
+            break;
+        }
+        
+        match listener.accept() {
+            Ok((mut stream, addr)) => {
+                // 监听器是非阻塞的，确保客户端连接使用阻塞模式；
+                // 单个连接设置失败只放弃这个连接
+                if let Err(e) = stream.set_nonblocking(false) {
+                    println!("设置客户端 {} 为阻塞模式失败: {}", addr, e);
+                    continue;
+                }
+                
+                if connections.count() >= max_connections {
+                    println!("连接数已达上限 {}，拒绝客户端: {}", max_connections, addr);
+                    let _ = writeln!(stream, "服务器繁忙，请稍后再试");
+                    continue;
+                }
+                
+                client_count += 1;
+                let client_id = client_count;
+                println!("客户端 {} 连接: {}", client_id, addr);
+                let guard = connections.register(client_id, &stream);
+                
+                thread::spawn(move || {
+                    let _guard = guard;
+                    handle_client(stream, client_id);
+                });
+            }
+            Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                // 非阻塞模式下没有连接，继续循环
+                thread::sleep(Duration::from_millis(10));
+                continue;
+            }
+            Err(e) => {
+                println!("接受连接失败: {}", e);
+                break;
+            }
+        }
+    }
+    
+    // 停止接受新连接
+    drop(listener);
+    
+    let deadline = std::time::Instant::now() + drain_timeout;
+    while connections.count() > 0 {
+        if std::time::Instant::now() >= deadline {
+            let forced = connections.force_close_all();
+            println!("等待超时，强制断开 {} 个连接", forced);
+            return Ok(forced);
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    
+    println!("所有连接处理完毕，服务器退出");
+    Ok(0)
+}
+
+// 处理客户端连接
+fn handle_client(mut stream: TcpStream, client_id: usize) {
+    let mut reader = BufReader::new(&stream);
+    let mut writer = BufWriter::new(&stream);
+    let mut line = String::new();
+    
+    loop {
+        line.clear();
+        match reader.read_line(&mut line) {
+            Ok(0) => {
+                println!("客户端 {} 断开连接", client_id);
+                break;
+            }
+            Ok(_) => {
+                let message = line.trim();
+                println!("客户端 {} 发送: {}", client_id, message);
+                
+                // 发送响应
+                let response = format!("服务器收到客户端 {} 的消息: {}", client_id, message);
+                if let Err(e) = writeln!(writer, "{}", response) {
+                    println!("发送响应失败: {}", e);
+                    break;
+                }
+                
+                if let Err(e) = writer.flush() {
+                    println!("刷新缓冲区失败: {}", e);
+                    break;
+                }
+            }
+            Err(e) => {
+                println!("读取客户端 {} 消息失败: {}", client_id, e);
+                break;
+            }
+        }
+    }
+}
+
+// 解析后的HTTP响应，头部名统一转为小写
+#[derive(Debug)]
+struct HttpResponse {
+    status: u16,
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+impl HttpResponse {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(&name.to_ascii_lowercase()).map(|v| v.as_str())
+    }
+}
+
+// HTTP GET请求，返回解析后的响应
+fn http_get(host: &str, port: u16, path: &str) -> std::io::Result<HttpResponse> {
+    let addr = (host, port).to_socket_addrs()?.next().ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::NotFound, format!("无法解析地址: {}", host))
+    })?;
+    let mut stream = TcpStream::connect_timeout(&addr, Duration::from_secs(5))?;
+    stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+    
+    // 构造HTTP请求
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
+        path, host
+    );
+    stream.write_all(request.as_bytes())?;
+    
+    read_http_response(&mut BufReader::new(stream))
+}
+
+// 从流中读取一个HTTP响应：有Content-Length时精确读取body，否则读到连接关闭
+fn read_http_response<R: BufRead>(reader: &mut R) -> std::io::Result<HttpResponse> {
+    let invalid = |msg: String| std::io::Error::new(std::io::ErrorKind::InvalidData, msg);
+    
+    // 状态行: HTTP/1.1 200 OK
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::UnexpectedEof,
+            "连接在收到状态行之前关闭",
+        ));
+    }
+    let status_line = line.trim_end();
+    let mut parts = status_line.splitn(3, ' ');
+    let status = match (parts.next(), parts.next()) {
+        (Some(version), Some(code)) if version.starts_with("HTTP/") => code
+            .parse::<u16>()
+            .map_err(|_| invalid(format!("无效的状态码: {}", status_line)))?,
+        _ => return Err(invalid(format!("无效的状态行: {}", status_line))),
+    };
+    
+    // 头部，直到空行
+    let mut headers = HashMap::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "连接在头部结束之前关闭",
+            ));
+        }
+        let header_line = line.trim_end();
+        if header_line.is_empty() {
+            break;
+        }
+        let (name, value) = header_line
+            .split_once(':')
+            .ok_or_else(|| invalid(format!("无效的头部: {}", header_line)))?;
+        headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+    }
+    
+    let chunked = headers
+        .get("transfer-encoding")
+        .is_some_and(|v| v.to_ascii_lowercase().contains("chunked"));
+    
+    // 分块编码优先于Content-Length
+    let mut body = Vec::new();
+    if chunked {
+        body = decode_chunked_body(reader)?;
+    } else if let Some(length) = headers.get("content-length") {
+        let length: usize = length
+            .parse()
+            .map_err(|_| invalid(format!("无效的Content-Length: {}", length)))?;
+        // 长度由服务器声明，按实际收到的数据增长，避免按声明长度预先分配
+        reader.take(length as u64).read_to_end(&mut body)?;
+        if body.len() < length {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                format!("body只收到 {} 字节，Content-Length为 {}", body.len(), length),
+            ));
+        }
+    } else {
+        // 没有长度信息（通常是Connection: close），读到对端关闭为止
+        reader.read_to_end(&mut body)?;
+    }
+    
+    Ok(HttpResponse { status, headers, body })
+}
+
+// 分块body累计长度上限，块长度来自对端，不能直接信任
+const MAX_CHUNKED_BODY_LEN: usize = 64 * 1024 * 1024;
+
+// 解码分块传输编码的body：每块以十六进制长度行开头，长度为0的块表示结束
+fn decode_chunked_body<R: BufRead>(reader: &mut R) -> std::io::Result<Vec<u8>> {
+    let invalid = |msg: String| std::io::Error::new(std::io::ErrorKind::InvalidData, msg);
+    let mut body = Vec::new();
+    let mut line = String::new();
+    
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "分块数据在结束块之前中断",
+            ));
+        }
+        
+        // 长度后面可能带有 ;扩展参数
+        let size_field = line.trim_end().split(';').next().unwrap_or("").trim();
+        let size = usize::from_str_radix(size_field, 16)
+            .map_err(|_| invalid(format!("无效的块长度: {:?}", line.trim_end())))?;
+        
+        if size == 0 {
+            break;
+        }
+        
+        let total = body
+            .len()
+            .checked_add(size)
+            .filter(|&total| total <= MAX_CHUNKED_BODY_LEN)
+            .ok_or_else(|| invalid(format!("分块body超过 {} 字节上限", MAX_CHUNKED_BODY_LEN)))?;
+        // 按实际到达的数据增长缓冲区，而不是按声明的长度预先分配
+        reader.take(size as u64).read_to_end(&mut body)?;
+        if body.len() < total {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "块数据不完整",
+            ));
+        }
+        
+        // 每块数据后紧跟 \r\n
+        let mut crlf = [0u8; 2];
+        reader.read_exact(&mut crlf)?;
+        if &crlf != b"\r\n" {
+            return Err(invalid("块数据后缺少CRLF".to_string()));
+        }
+    }
+    
+    // 跳过可选的trailer头部，直到空行
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 || line.trim_end().is_empty() {
+            break;
+        }
+    }
+    
+    Ok(body)
+}
+
+// 打印HTTP GET请求的结果
+fn http_get_request(host: &str, port: u16, path: &str) {
+    match http_get(host, port, path) {
+        Ok(response) => {
+            println!("HTTP响应状态: {}", response.status);
+            if let Some(content_type) = response.header("Content-Type") {
+                println!("内容类型: {}", content_type);
+            }
+            
+            let body = String::from_utf8_lossy(&response.body);
+            if !body.is_empty() {
+                let preview: String = body.chars().take(100).collect();
+                println!("响应体预览: {}...", preview);
+            }
+        }
+        Err(e) => println!("HTTP GET请求失败: {}", e),
+    }
+}
+
+// HTTP POST请求
+fn http_post_request(host: &str, port: u16, path: &str, data: &str) {
+    match TcpStream::connect_timeout(
         &format!("{}:{}", host, port).parse().unwrap(),
         Duration::from_secs(5)
     ) {
@@ -736,35 +1949,211 @@ fn http_post_request(host: &str, port: u16, path: &str, data: &str) {
     }
 }
 
-// 性能测试服务器
-fn start_performance_test_server(addr: &str) -> std::io::Result<()> {
+// 解析后的HTTP请求
+#[derive(Debug, Clone, PartialEq)]
+struct HttpRequest {
+    method: String,
+    path: String,
+    version: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl HttpRequest {
+    // 头部名称不区分大小写
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum HttpParseError {
+    // 数据还不完整（半包），不是真正的错误，继续读取后重试即可
+    NeedMoreData,
+    InvalidRequestLine(String),
+    InvalidHeader(String),
+    InvalidContentLength(String),
+}
+
+impl std::fmt::Display for HttpParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HttpParseError::NeedMoreData => write!(f, "数据不完整，需要更多字节"),
+            HttpParseError::InvalidRequestLine(line) => write!(f, "非法请求行: {}", line),
+            HttpParseError::InvalidHeader(line) => write!(f, "非法头部: {}", line),
+            HttpParseError::InvalidContentLength(value) => write!(f, "非法Content-Length: {}", value),
+        }
+    }
+}
+
+impl std::error::Error for HttpParseError {}
+
+// 从字节流缓冲区解析一个HTTP请求，成功时返回请求和消耗的字节数
+// 缓冲区中可能还有下一个请求的数据（管线化），调用方应丢弃已消耗的部分
+fn parse_http_request(buf: &[u8]) -> Result<(HttpRequest, usize), HttpParseError> {
+    let header_end = match buf.windows(4).position(|w| w == b"\r\n\r\n") {
+        Some(pos) => pos,
+        None => return Err(HttpParseError::NeedMoreData),
+    };
+    
+    let head = String::from_utf8_lossy(&buf[..header_end]);
+    let mut lines = head.split("\r\n");
+    
+    let request_line = lines.next().unwrap_or("");
+    let parts: Vec<&str> = request_line.split(' ').collect();
+    if parts.len() != 3 || parts.iter().any(|p| p.is_empty()) || !parts[2].starts_with("HTTP/") {
+        return Err(HttpParseError::InvalidRequestLine(request_line.to_string()));
+    }
+    
+    let mut headers = Vec::new();
+    for line in lines {
+        let (key, value) = line
+            .split_once(':')
+            .ok_or_else(|| HttpParseError::InvalidHeader(line.to_string()))?;
+        if key.trim().is_empty() {
+            return Err(HttpParseError::InvalidHeader(line.to_string()));
+        }
+        headers.push((key.trim().to_string(), value.trim().to_string()));
+    }
+    
+    let mut request = HttpRequest {
+        method: parts[0].to_string(),
+        path: parts[1].to_string(),
+        version: parts[2].to_string(),
+        headers,
+        body: Vec::new(),
+    };
+    
+    // 长度来自对端，相加溢出也按非法长度处理
+    let body_start = header_end + 4;
+    let body_end = match request.header("Content-Length") {
+        Some(value) => value
+            .parse::<usize>()
+            .ok()
+            .and_then(|length| body_start.checked_add(length))
+            .ok_or_else(|| HttpParseError::InvalidContentLength(value.to_string()))?,
+        None => body_start,
+    };
+    if buf.len() < body_end {
+        return Err(HttpParseError::NeedMoreData);
+    }
+    
+    request.body = buf[body_start..body_end].to_vec();
+    Ok((request, body_end))
+}
+
+// 吞吐量统计：rx为接收字节数，tx为发送字节数
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ThroughputReport {
+    rx_bytes: u64,
+    tx_bytes: u64,
+    duration: Duration,
+}
+
+impl ThroughputReport {
+    fn rx_mb_per_sec(&self) -> f64 {
+        mb_per_sec(self.rx_bytes, self.duration)
+    }
+    
+    fn tx_mb_per_sec(&self) -> f64 {
+        mb_per_sec(self.tx_bytes, self.duration)
+    }
+}
+
+impl std::fmt::Display for ThroughputReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "接收 {} 字节 ({:.2} MB/s)，发送 {} 字节 ({:.2} MB/s)，耗时 {:?}",
+            self.rx_bytes,
+            self.rx_mb_per_sec(),
+            self.tx_bytes,
+            self.tx_mb_per_sec(),
+            self.duration
+        )
+    }
+}
+
+fn mb_per_sec(bytes: u64, duration: Duration) -> f64 {
+    let secs = duration.as_secs_f64();
+    if secs == 0.0 {
+        return 0.0;
+    }
+    bytes as f64 / secs / 1024.0 / 1024.0
+}
+
+// 性能测试服务器：处理一个连接，把收到数据的echo_ratio（0.0~1.0）回传给客户端
+fn start_performance_test_server(addr: &str, echo_ratio: f64) -> std::io::Result<ThroughputReport> {
     let listener = TcpListener::bind(addr)?;
     println!("性能测试服务器启动在: {}", addr);
     
-    if let Ok((mut stream, _)) = listener.accept() {
-        let mut total_received = 0;
-        let mut buffer = [0; 8192];
-        
-        let start = std::time::Instant::now();
+    serve_performance_test(listener, echo_ratio)
+}
+
+fn serve_performance_test(listener: TcpListener, echo_ratio: f64) -> std::io::Result<ThroughputReport> {
+    let echo_ratio = echo_ratio.clamp(0.0, 1.0);
+    let (mut stream, _) = listener.accept()?;
+    let mut writer = BufWriter::new(stream.try_clone()?);
+    let mut buffer = [0; 8192];
+    let mut rx_bytes = 0u64;
+    let mut tx_bytes = 0u64;
+    
+    let start = std::time::Instant::now();
+    loop {
+        let size = stream.read(&mut buffer)?;
+        if size == 0 {
+            break; // 连接关闭
+        }
+        rx_bytes += size as u64;
         
+        // 按累计量计算应回传的字节数，避免每块取整造成的误差
+        let owed = ((rx_bytes as f64 * echo_ratio) as u64 - tx_bytes).min(size as u64);
+        writer.write_all(&buffer[..owed as usize])?;
+        tx_bytes += owed;
+    }
+    writer.flush()?;
+    stream.shutdown(Shutdown::Write)?;
+    
+    Ok(ThroughputReport {
+        rx_bytes,
+        tx_bytes,
+        duration: start.elapsed(),
+    })
+}
+
+// 性能测试客户端：发送data的同时在另一个线程读取回传数据，统计往返吞吐量
+fn run_throughput_client(addr: SocketAddr, data: &[u8]) -> std::io::Result<ThroughputReport> {
+    let stream = TcpStream::connect(addr)?;
+    let mut reader = stream.try_clone()?;
+    
+    let start = std::time::Instant::now();
+    // 边发边收，否则双方的发送缓冲区都写满后会互相等待
+    let reader_handle = thread::spawn(move || -> std::io::Result<u64> {
+        let mut buffer = [0; 8192];
+        let mut received = 0u64;
         loop {
-            match stream.read(&mut buffer) {
-                Ok(0) => break, // 连接关闭
-                Ok(size) => {
-                    total_received += size;
-                }
-                Err(e) => {
-                    println!("性能测试读取失败: {}", e);
-                    break;
-                }
+            match reader.read(&mut buffer)? {
+                0 => return Ok(received),
+                size => received += size as u64,
             }
         }
-        
-        let duration = start.elapsed();
-        println!("性能测试服务器接收了 {} 字节，耗时: {:?}", total_received, duration);
-    }
+    });
     
-    Ok(())
+    let mut writer = BufWriter::with_capacity(64 * 1024, &stream);
+    writer.write_all(data)?;
+    writer.flush()?;
+    drop(writer);
+    stream.shutdown(Shutdown::Write)?;
+    
+    let rx_bytes = reader_handle.join().expect("读取线程panic")?;
+    Ok(ThroughputReport {
+        rx_bytes,
+        tx_bytes: data.len() as u64,
+        duration: start.elapsed(),
+    })
 }
 
 #[cfg(test)]
@@ -846,6 +2235,360 @@ mod tests {
         let _ = server_handle.join();
     }
     
+    #[test]
+    fn test_echo_server_concurrent_clients_and_shutdown() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let shutdown = Arc::new(AtomicBool::new(false));
+        
+        let server_shutdown = shutdown.clone();
+        let server_handle = thread::spawn(move || serve_echo(listener, server_shutdown));
+        
+        // 两个客户端同时保持连接，先连接的客户端不应阻塞后一个
+        let mut first = TcpStream::connect(addr).unwrap();
+        let mut second = TcpStream::connect(addr).unwrap();
+        let mut first_reader = BufReader::new(first.try_clone().unwrap());
+        let mut second_reader = BufReader::new(second.try_clone().unwrap());
+        
+        writeln!(second, "from second").unwrap();
+        writeln!(first, "from first").unwrap();
+        
+        let mut response = String::new();
+        second_reader.read_line(&mut response).unwrap();
+        assert_eq!(response.trim(), "回声: from second");
+        
+        response.clear();
+        first_reader.read_line(&mut response).unwrap();
+        assert_eq!(response.trim(), "回声: from first");
+        
+        // 客户端仍然连接时停止服务器
+        shutdown.store(true, Ordering::SeqCst);
+        let served = server_handle.join().unwrap().unwrap();
+        assert_eq!(served, 2);
+    }
+    
+    #[test]
+    fn test_line_protocol_round_trip() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let shutdown = Arc::new(AtomicBool::new(false));
+        
+        let server_shutdown = shutdown.clone();
+        let server_handle = thread::spawn(move || serve_echo(listener, server_shutdown));
+        
+        let mut protocol = LineProtocol::new(TcpStream::connect(addr).unwrap()).unwrap();
+        for line in ["first", "second\r", "third"] {
+            protocol.send_line(line).unwrap();
+        }
+        
+        // 服务器回声的行尾统一为 \n，\r 被服务器trim掉
+        assert_eq!(protocol.recv_line().unwrap(), "回声: first");
+        assert_eq!(protocol.recv_line().unwrap(), "回声: second");
+        assert_eq!(protocol.recv_line().unwrap(), "回声: third");
+        
+        protocol.send_line("quit").unwrap();
+        assert_eq!(
+            protocol.recv_line().unwrap_err().kind(),
+            std::io::ErrorKind::UnexpectedEof
+        );
+        
+        shutdown.store(true, Ordering::SeqCst);
+        server_handle.join().unwrap().unwrap();
+    }
+    
+    #[test]
+    fn test_line_protocol_crlf_and_partial_lines() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        
+        let server_handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            // 一行分两次发送，另一行用 \r\n 结尾
+            stream.write_all(b"par").unwrap();
+            stream.flush().unwrap();
+            thread::sleep(Duration::from_millis(50));
+            stream.write_all(b"tial\nwindows\r\nlast").unwrap();
+        });
+        
+        let mut protocol = LineProtocol::new(TcpStream::connect(addr).unwrap()).unwrap();
+        assert_eq!(protocol.recv_line().unwrap(), "partial");
+        assert_eq!(protocol.recv_line().unwrap(), "windows");
+        
+        server_handle.join().unwrap();
+        assert_eq!(protocol.recv_line().unwrap(), "last");
+    }
+    
+    #[test]
+    fn test_connection_pool_reuses_connections() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let shutdown = Arc::new(AtomicBool::new(false));
+        
+        let server_shutdown = shutdown.clone();
+        let server_handle = thread::spawn(move || serve_echo(listener, server_shutdown));
+        
+        let max_idle = 2;
+        let pool = TcpConnectionPool::new(&addr, max_idle);
+        for i in 0..20 {
+            let mut conn = pool.get().unwrap();
+            writeln!(conn, "request {}", i).unwrap();
+            let mut response = String::new();
+            BufReader::new(&mut conn).read_line(&mut response).unwrap();
+            assert_eq!(response.trim(), format!("回声: request {}", i));
+        }
+        
+        assert!(pool.connections_opened() <= max_idle);
+        
+        shutdown.store(true, Ordering::SeqCst);
+        let served = server_handle.join().unwrap().unwrap();
+        assert!(served <= max_idle);
+    }
+    
+    #[test]
+    fn test_connection_pool_discards_closed_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let shutdown = Arc::new(AtomicBool::new(false));
+        
+        let server_shutdown = shutdown.clone();
+        let server_handle = thread::spawn(move || serve_echo(listener, server_shutdown));
+        
+        let pool = TcpConnectionPool::new(&addr, 2);
+        {
+            // quit让服务器关闭这个连接，但客户端没有读到EOF，连接仍被归还
+            let mut conn = pool.get().unwrap();
+            writeln!(conn, "quit").unwrap();
+        }
+        thread::sleep(Duration::from_millis(100));
+        
+        // 探测发现连接已关闭，重新建立连接
+        let mut conn = pool.get().unwrap();
+        writeln!(conn, "again").unwrap();
+        let mut response = String::new();
+        BufReader::new(&mut conn).read_line(&mut response).unwrap();
+        assert_eq!(response.trim(), "回声: again");
+        assert_eq!(pool.connections_opened(), 2);
+        
+        drop(conn);
+        shutdown.store(true, Ordering::SeqCst);
+        server_handle.join().unwrap().unwrap();
+    }
+    
+    // 启动一个只处理一次请求的HTTP服务器，返回固定的响应
+    fn spawn_http_server(response: &'static [u8]) -> (u16, thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        
+        let handle = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request = String::new();
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 0 && line != "\r\n" {
+                request.push_str(&line);
+                line.clear();
+            }
+            stream.write_all(response).unwrap();
+            request
+        });
+        
+        (port, handle)
+    }
+    
+    #[test]
+    fn test_http_get_parses_content_length_response() {
+        let (port, server) = spawn_http_server(
+            b"HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: 5\r\n\r\nhello trailing",
+        );
+        
+        let response = http_get("127.0.0.1", port, "/greet").unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.header("content-type"), Some("text/plain"));
+        assert_eq!(response.headers.get("content-length").map(String::as_str), Some("5"));
+        // 只读取Content-Length指定的字节
+        assert_eq!(response.body, b"hello");
+        
+        let request = server.join().unwrap();
+        assert!(request.starts_with("GET /greet HTTP/1.1\r\n"));
+    }
+    
+    #[test]
+    fn test_http_get_reads_until_close_without_length() {
+        let (port, server) = spawn_http_server(
+            b"HTTP/1.1 404 Not Found\r\nConnection: close\r\n\r\nmissing page",
+        );
+        
+        let response = http_get("127.0.0.1", port, "/missing").unwrap();
+        server.join().unwrap();
+        
+        assert_eq!(response.status, 404);
+        assert_eq!(response.header("Connection"), Some("close"));
+        assert_eq!(response.body, b"missing page");
+    }
+    
+    #[test]
+    fn test_read_http_response_invalid_status_line() {
+        let mut reader = BufReader::new(&b"garbage\r\n\r\n"[..]);
+        let err = read_http_response(&mut reader).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+    
+    #[test]
+    fn test_read_http_response_short_body() {
+        let raw = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\nshort", usize::MAX);
+        let err = read_http_response(&mut BufReader::new(raw.as_bytes())).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+    }
+    
+    #[test]
+    fn test_decode_chunked_body() {
+        let raw = b"4\r\nWiki\r\n6;name=value\r\npedia \r\nE\r\nin \r\n\r\nchunks.\r\n0\r\nExpires: never\r\n\r\nnext";
+        let mut reader = BufReader::new(&raw[..]);
+        
+        let body = decode_chunked_body(&mut reader).unwrap();
+        assert_eq!(body, b"Wikipedia in \r\n\r\nchunks.");
+        
+        // 结束块和trailer之后的数据不被消耗
+        let mut rest = String::new();
+        reader.read_to_string(&mut rest).unwrap();
+        assert_eq!(rest, "next");
+    }
+    
+    #[test]
+    fn test_decode_chunked_body_malformed() {
+        let mut bad_size = BufReader::new(&b"zz\r\nabc\r\n0\r\n\r\n"[..]);
+        assert_eq!(
+            decode_chunked_body(&mut bad_size).unwrap_err().kind(),
+            std::io::ErrorKind::InvalidData
+        );
+        
+        let mut missing_crlf = BufReader::new(&b"3\r\nabcX0\r\n\r\n"[..]);
+        assert_eq!(
+            decode_chunked_body(&mut missing_crlf).unwrap_err().kind(),
+            std::io::ErrorKind::InvalidData
+        );
+        
+        let mut truncated = BufReader::new(&b"3\r\nabc\r\n"[..]);
+        assert_eq!(
+            decode_chunked_body(&mut truncated).unwrap_err().kind(),
+            std::io::ErrorKind::UnexpectedEof
+        );
+        
+        let mut short_chunk = BufReader::new(&b"10\r\nabc"[..]);
+        assert_eq!(
+            decode_chunked_body(&mut short_chunk).unwrap_err().kind(),
+            std::io::ErrorKind::UnexpectedEof
+        );
+        
+        // 超大的块长度直接报错，不会溢出也不会预先分配内存
+        for size in ["ffffffffffffffff", "4000001"] {
+            let raw = format!("{}\r\nabc\r\n0\r\n\r\n", size);
+            let mut huge = BufReader::new(raw.as_bytes());
+            assert_eq!(
+                decode_chunked_body(&mut huge).unwrap_err().kind(),
+                std::io::ErrorKind::InvalidData
+            );
+        }
+    }
+    
+    #[test]
+    fn test_read_http_response_chunked() {
+        let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n6\r\n world\r\n0\r\n\r\n";
+        let response = read_http_response(&mut BufReader::new(&raw[..])).unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body, b"hello world");
+    }
+    
+    #[test]
+    fn test_udp_server_with_custom_handler() {
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let server_addr = socket.local_addr().unwrap();
+        let shutdown = Arc::new(AtomicBool::new(false));
+        
+        let server_shutdown = shutdown.clone();
+        let server_handle = thread::spawn(move || {
+            serve_udp_with(socket, server_shutdown, |data: &[u8], _from: SocketAddr| {
+                // 空数据报不回复
+                if data.is_empty() {
+                    None
+                } else {
+                    Some(data.to_ascii_uppercase())
+                }
+            })
+        });
+        
+        let client = UdpSocket::bind("127.0.0.1:0").unwrap();
+        client.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+        
+        client.send_to(b"", server_addr).unwrap();
+        client.send_to(b"hello, udp 123", server_addr).unwrap();
+        
+        let mut buffer = [0; 64];
+        let (received, from) = client.recv_from(&mut buffer).unwrap();
+        assert_eq!(from, server_addr);
+        assert_eq!(&buffer[..received], b"HELLO, UDP 123");
+        
+        shutdown.store(true, Ordering::SeqCst);
+        server_handle.join().unwrap().unwrap();
+    }
+    
+    #[test]
+    fn test_scan_ports_reports_open_and_closed() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let open_port = listener.local_addr().unwrap().port();
+        
+        // 找一个相邻且当前未被占用的端口
+        let closed_port = [open_port.wrapping_add(1), open_port.wrapping_sub(1)]
+            .into_iter()
+            .find(|&port| port != 0 && TcpListener::bind(("127.0.0.1", port)).is_ok())
+            .expect("没有可用的相邻端口");
+        
+        let results = scan_ports(
+            "127.0.0.1",
+            vec![closed_port, open_port],
+            2,
+            Duration::from_millis(200),
+        );
+        assert_eq!(results, vec![(closed_port, false), (open_port, true)]);
+    }
+    
+    #[test]
+    fn test_scan_ports_more_ports_than_workers() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let open_port = listener.local_addr().unwrap().port();
+        
+        let ports: Vec<u16> = std::iter::repeat_n(open_port, 50).collect();
+        let results = scan_ports("127.0.0.1", ports, 3, Duration::from_millis(200));
+        assert_eq!(results.len(), 50);
+        assert!(results.iter().all(|&(port, open)| port == open_port && open));
+    }
+    
+    #[test]
+    fn test_scan_ports_bounds_concurrency() {
+        // 记录同时进行中的探测数的峰值
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let probe = {
+            let in_flight = Arc::clone(&in_flight);
+            let peak = Arc::clone(&peak);
+            move |addr: SocketAddr| {
+                let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(current, Ordering::SeqCst);
+                thread::sleep(Duration::from_millis(20));
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                addr.port().is_multiple_of(2)
+            }
+        };
+        
+        let results = scan_ports_with("127.0.0.1", 1..=20, 3, probe);
+        assert_eq!(results.len(), 20);
+        assert!(results.iter().all(|&(port, open)| open == port.is_multiple_of(2)));
+        // 不超过工作线程数，且确实并发执行了
+        let peak = peak.load(Ordering::SeqCst);
+        assert!(peak > 1 && peak <= 3, "并发峰值: {}", peak);
+        assert_eq!(in_flight.load(Ordering::SeqCst), 0);
+    }
+    
     #[test]
     fn test_socket_addresses_resolution() {
         // 测试localhost地址解析
@@ -864,4 +2607,418 @@ mod tests {
         
         assert!(has_ipv4 || addresses.iter().any(|addr| addr.ip().is_loopback()));
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_graceful_shutdown_waits_for_active_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (tx, rx) = mpsc::channel();
+        
+        let server_handle = thread::spawn(move || {
+            serve_with_graceful_shutdown(listener, rx, Duration::from_secs(5), usize::MAX)
+        });
+        
+        let mut stream = TcpStream::connect(addr).unwrap();
+        writeln!(stream, "slow").unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut response = String::new();
+        reader.read_line(&mut response).unwrap();
+        assert!(response.contains("slow"));
+        
+        // 连接仍在处理中时发出停止信号，服务器不应立即退出
+        tx.send(()).unwrap();
+        thread::sleep(Duration::from_millis(200));
+        assert!(!server_handle.is_finished());
+        
+        drop(reader);
+        drop(stream);
+        let forced = server_handle.join().unwrap().unwrap();
+        assert_eq!(forced, 0);
+    }
+    
+    #[test]
+    fn test_graceful_shutdown_forces_close_after_timeout() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (tx, rx) = mpsc::channel();
+        
+        let server_handle = thread::spawn(move || {
+            serve_with_graceful_shutdown(listener, rx, Duration::from_millis(200), usize::MAX)
+        });
+        
+        let mut stream = TcpStream::connect(addr).unwrap();
+        writeln!(stream, "hold").unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut response = String::new();
+        reader.read_line(&mut response).unwrap();
+        
+        tx.send(()).unwrap();
+        let forced = server_handle.join().unwrap().unwrap();
+        assert_eq!(forced, 1);
+        
+        // 服务器端已断开，客户端读到EOF
+        stream.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+        response.clear();
+        assert_eq!(reader.read_line(&mut response).unwrap(), 0);
+    }
+    
+    #[test]
+    fn test_graceful_shutdown_rejects_over_max_connections() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (tx, rx) = mpsc::channel();
+        
+        let server_handle = thread::spawn(move || {
+            serve_with_graceful_shutdown(listener, rx, Duration::from_secs(5), 1)
+        });
+        
+        // 第一个连接完成一次往返，确保已被登记为活跃连接
+        let mut first = TcpStream::connect(addr).unwrap();
+        writeln!(first, "hello").unwrap();
+        let mut first_reader = BufReader::new(first.try_clone().unwrap());
+        let mut response = String::new();
+        first_reader.read_line(&mut response).unwrap();
+        assert!(response.contains("hello"));
+        
+        // 超过上限的连接收到繁忙提示后被关闭
+        let second = TcpStream::connect(addr).unwrap();
+        second.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+        let mut second_reader = BufReader::new(second);
+        response.clear();
+        second_reader.read_line(&mut response).unwrap();
+        assert!(response.contains("服务器繁忙"));
+        response.clear();
+        assert_eq!(second_reader.read_line(&mut response).unwrap(), 0);
+        
+        drop(first_reader);
+        drop(first);
+        tx.send(()).unwrap();
+        assert_eq!(server_handle.join().unwrap().unwrap(), 0);
+    }
+    
+    #[test]
+    fn test_parse_http_request_complete() {
+        let raw = b"GET /index.html HTTP/1.1\r\nHost: example.com\r\nAccept: */*\r\n\r\n";
+        let (request, consumed) = parse_http_request(raw).unwrap();
+        
+        assert_eq!(consumed, raw.len());
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.path, "/index.html");
+        assert_eq!(request.version, "HTTP/1.1");
+        assert_eq!(request.header("host"), Some("example.com"));
+        assert!(request.body.is_empty());
+    }
+    
+    #[test]
+    fn test_parse_http_request_partial() {
+        let raw = b"POST /submit HTTP/1.1\r\nContent-Length: 5\r\n\r\nhello";
+        
+        // 头部不完整
+        assert_eq!(parse_http_request(&raw[..20]), Err(HttpParseError::NeedMoreData));
+        // 头部完整但body不完整
+        assert_eq!(parse_http_request(&raw[..raw.len() - 2]), Err(HttpParseError::NeedMoreData));
+        // 补齐后解析成功
+        assert!(parse_http_request(raw).is_ok());
+    }
+    
+    #[test]
+    fn test_parse_http_request_invalid_request_line() {
+        let raw = b"GARBAGE\r\n\r\n";
+        assert!(matches!(
+            parse_http_request(raw),
+            Err(HttpParseError::InvalidRequestLine(_))
+        ));
+    }
+    
+    #[test]
+    fn test_parse_http_request_post_body() {
+        let mut raw = b"POST /api HTTP/1.1\r\nContent-Length: 13\r\n\r\nname=rust&a=1".to_vec();
+        // 后面紧跟下一个请求的数据
+        raw.extend_from_slice(b"GET / HTTP/1.1\r\n\r\n");
+        
+        let (request, consumed) = parse_http_request(&raw).unwrap();
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.body, b"name=rust&a=1");
+        
+        let (next, _) = parse_http_request(&raw[consumed..]).unwrap();
+        assert_eq!(next.method, "GET");
+    }
+    
+    #[test]
+    fn test_parse_http_request_huge_content_length() {
+        let raw = format!("POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\nbody", usize::MAX);
+        assert_eq!(
+            parse_http_request(raw.as_bytes()).unwrap_err(),
+            HttpParseError::InvalidContentLength(usize::MAX.to_string())
+        );
+        
+        let raw = b"POST / HTTP/1.1\r\nContent-Length: -1\r\n\r\n";
+        assert!(matches!(
+            parse_http_request(raw),
+            Err(HttpParseError::InvalidContentLength(_))
+        ));
+    }
+    
+    #[test]
+    fn test_cidr_ipv4_contains() {
+        let net = parse_cidr("192.168.1.0/24").unwrap();
+        assert!(net.contains("192.168.1.5".parse().unwrap()));
+        assert!(!net.contains("192.168.2.5".parse().unwrap()));
+        // 地址族不同
+        assert!(!net.contains("::ffff:192.168.1.5".parse().unwrap()));
+        
+        // 主机位被清零
+        let net = parse_cidr("10.1.2.3/8").unwrap();
+        assert_eq!(net.to_string(), "10.0.0.0/8");
+        assert!(net.contains("10.255.0.1".parse().unwrap()));
+        
+        // /0 包含所有地址，/32 只包含自己
+        assert!(parse_cidr("0.0.0.0/0").unwrap().contains("8.8.8.8".parse().unwrap()));
+        let host = parse_cidr("8.8.8.8/32").unwrap();
+        assert!(host.contains("8.8.8.8".parse().unwrap()));
+        assert!(!host.contains("8.8.8.9".parse().unwrap()));
+    }
+    
+    #[test]
+    fn test_cidr_ipv6_and_errors() {
+        let net = parse_cidr("2001:db8::/32").unwrap();
+        assert!(net.contains("2001:db8:1234::1".parse().unwrap()));
+        assert!(!net.contains("2001:db9::1".parse().unwrap()));
+        
+        assert_eq!(
+            parse_cidr("10.0.0.0/33"),
+            Err(CidrParseError::PrefixTooLong { prefix: 33, max: 32 })
+        );
+        assert!(parse_cidr("2001:db8::/128").is_ok());
+        assert!(matches!(parse_cidr("10.0.0.0"), Err(CidrParseError::MissingPrefix(_))));
+        assert!(matches!(parse_cidr("10.0.0/8"), Err(CidrParseError::InvalidAddress(_))));
+        assert!(matches!(parse_cidr("10.0.0.0/x"), Err(CidrParseError::InvalidPrefix(_))));
+    }
+    
+    #[test]
+    fn test_reconnecting_client_recovers_after_server_close() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        
+        // 每个连接只应答一条消息，然后关闭
+        let server_handle = thread::spawn(move || {
+            for stream in listener.incoming().take(2) {
+                let mut protocol = LineProtocol::new(stream.unwrap()).unwrap();
+                let line = protocol.recv_line().unwrap();
+                protocol.send_line(&format!("ok: {}", line)).unwrap();
+            }
+        });
+        
+        let mut client = ReconnectingClient::new(&addr)
+            .with_backoff(Duration::from_millis(10), Duration::from_millis(100), 5);
+        assert_eq!(client.send_line("first").unwrap(), "ok: first");
+        assert_eq!(client.reconnect_count(), 0);
+        
+        // 服务器已关闭第一个连接，客户端应透明重连
+        thread::sleep(Duration::from_millis(50));
+        assert_eq!(client.send_line("second").unwrap(), "ok: second");
+        assert_eq!(client.reconnect_count(), 1);
+        
+        server_handle.join().unwrap();
+        
+        // 服务器不再接受连接，退避重试耗尽后返回错误
+        assert!(client.send_line("third").is_err());
+    }
+    
+    #[test]
+    fn test_chat_server_broadcasts_to_other_clients() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let chat = ChatServer::new();
+        
+        let server = chat.clone();
+        let server_shutdown = shutdown.clone();
+        let server_handle = thread::spawn(move || server.serve(listener, server_shutdown));
+        
+        let mut clients: Vec<TcpStream> = (0..3).map(|_| TcpStream::connect(addr).unwrap()).collect();
+        let mut readers: Vec<BufReader<TcpStream>> = clients
+            .iter()
+            .map(|c| {
+                c.set_read_timeout(Some(Duration::from_millis(300))).unwrap();
+                BufReader::new(c.try_clone().unwrap())
+            })
+            .collect();
+        while chat.client_count() < 3 {
+            thread::sleep(Duration::from_millis(10));
+        }
+        
+        // 读取下一条非系统消息，超时返回None
+        fn next_chat_line(reader: &mut BufReader<TcpStream>) -> Option<String> {
+            let mut line = String::new();
+            loop {
+                line.clear();
+                match reader.read_line(&mut line) {
+                    Ok(n) if n > 0 => {
+                        if !line.starts_with("[系统]") {
+                            return Some(line.trim_end().to_string());
+                        }
+                    }
+                    _ => return None,
+                }
+            }
+        }
+        
+        writeln!(&clients[0], "hello").unwrap();
+        let first = next_chat_line(&mut readers[1]).unwrap();
+        let second = next_chat_line(&mut readers[2]).unwrap();
+        assert_eq!(first, second);
+        assert!(first.ends_with(": hello"));
+        // 发送者自己收不到
+        assert_eq!(next_chat_line(&mut readers[0]), None);
+        
+        // 客户端断开后被清理
+        drop(clients.pop());
+        drop(readers.pop());
+        let deadline = std::time::Instant::now() + Duration::from_secs(2);
+        while chat.client_count() > 2 && std::time::Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(chat.client_count(), 2);
+        
+        shutdown.store(true, Ordering::SeqCst);
+        server_handle.join().unwrap().unwrap();
+    }
+    
+    #[test]
+    fn test_chat_server_concurrent_long_lines_stay_intact() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let chat = ChatServer::new();
+        
+        let server = chat.clone();
+        let server_shutdown = shutdown.clone();
+        let server_handle = thread::spawn(move || server.serve(listener, server_shutdown));
+        
+        let senders: Vec<TcpStream> = (0..2).map(|_| TcpStream::connect(addr).unwrap()).collect();
+        let receiver = TcpStream::connect(addr).unwrap();
+        receiver.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        while chat.client_count() < 3 {
+            thread::sleep(Duration::from_millis(10));
+        }
+        
+        // 发送者也会收到对方的消息，持续读走，避免发送缓冲区写满后互相阻塞
+        for sender in &senders {
+            let mut sink = sender.try_clone().unwrap();
+            thread::spawn(move || std::io::copy(&mut sink, &mut std::io::sink()));
+        }
+        
+        // 每行远大于一次write能写完的长度，交错时会破坏行的内容
+        const LINES: usize = 200;
+        let writers: Vec<_> = senders
+            .iter()
+            .zip(['a', 'b'])
+            .map(|(sender, fill)| {
+                let mut sender = sender.try_clone().unwrap();
+                thread::spawn(move || {
+                    let line = fill.to_string().repeat(64 * 1024);
+                    for _ in 0..LINES {
+                        writeln!(sender, "{}", line).unwrap();
+                    }
+                })
+            })
+            .collect();
+        
+        let mut reader = BufReader::new(receiver);
+        let mut counts = [0; 2];
+        while counts[0] + counts[1] < 2 * LINES {
+            let mut line = String::new();
+            assert!(reader.read_line(&mut line).unwrap() > 0, "连接提前关闭");
+            if line.starts_with("[系统]") {
+                continue;
+            }
+            let (_, body) = line.trim_end().split_once(": ").expect("行缺少发送者前缀");
+            let fill = body.chars().next().unwrap();
+            assert!(body.len() == 64 * 1024 && body.chars().all(|c| c == fill), "行被交错写入");
+            counts[(fill == 'b') as usize] += 1;
+        }
+        assert_eq!(counts, [LINES, LINES]);
+        
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        shutdown.store(true, Ordering::SeqCst);
+        server_handle.join().unwrap().unwrap();
+    }
+    
+    #[test]
+    fn test_throughput_report_counts_bidirectional_bytes() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server_handle = thread::spawn(move || serve_performance_test(listener, 0.5));
+        
+        let data = vec![7u8; 1024 * 1024];
+        let client = run_throughput_client(addr, &data).unwrap();
+        let server = server_handle.join().unwrap().unwrap();
+        
+        assert_eq!(server.rx_bytes, data.len() as u64);
+        assert_eq!(server.tx_bytes, data.len() as u64 / 2);
+        assert_eq!(client.tx_bytes, server.rx_bytes);
+        assert_eq!(client.rx_bytes, server.tx_bytes);
+        assert!(client.rx_mb_per_sec() > 0.0);
+    }
+    
+    #[test]
+    fn test_reliable_udp_delivers_once_despite_loss() {
+        // 发送方丢数据包触发重传，接收方丢确认产生重复包
+        let sender = ReliableUdp::bind("127.0.0.1:0")
+            .unwrap()
+            .with_retransmit(Duration::from_millis(30), 10)
+            .with_simulated_loss(3);
+        let receiver = ReliableUdp::bind("127.0.0.1:0")
+            .unwrap()
+            .with_recv_timeout(Duration::from_millis(500))
+            .with_simulated_loss(4);
+        let receiver_addr = receiver.local_addr().unwrap();
+        
+        let receiver_handle = thread::spawn(move || {
+            let mut received = Vec::new();
+            while let Ok((data, _)) = receiver.recv() {
+                received.push(data);
+            }
+            (received, receiver)
+        });
+        
+        for i in 0..10u8 {
+            sender.send(receiver_addr, &[i]).unwrap();
+        }
+        
+        let (received, receiver) = receiver_handle.join().unwrap();
+        let expected: Vec<Vec<u8>> = (0..10u8).map(|i| vec![i]).collect();
+        assert_eq!(received, expected);
+        
+        // 全部确认后两端都不再保留逐个序号的记录
+        assert!(sender.peers.lock().unwrap()[&receiver_addr].awaiting.is_empty());
+        let sender_addr = sender.local_addr().unwrap();
+        let delivered = receiver.delivered.lock().unwrap();
+        assert_eq!(delivered[&sender_addr].floor, 10);
+        assert!(delivered[&sender_addr].seen.is_empty());
+    }
+    
+    #[test]
+    fn test_delivered_seqs_prunes_below_floor() {
+        let mut seqs = DeliveredSeqs::default();
+        assert!(seqs.insert(0, 0));
+        assert!(!seqs.insert(0, 0));
+        
+        // 序号1被发送方放弃，之后的序号先单独保存
+        assert!(seqs.insert(2, 1));
+        assert!(seqs.insert(3, 1));
+        assert_eq!(seqs.seen.len(), 2);
+        
+        // 发送方的下限越过空缺后，记录被合并清理
+        assert!(seqs.insert(4, 4));
+        assert_eq!(seqs.floor, 5);
+        assert!(seqs.seen.is_empty());
+        
+        // 低于下限的迟到重传不再交付
+        assert!(!seqs.insert(1, 4));
+        assert!(!seqs.insert(3, 4));
+    }
+}
@@ -32,10 +32,11 @@ Rust的并发编程模型基于所有权系统，提供了内存安全的并发
 
 use std::thread;
 use std::time::Duration;
-use std::sync::{Arc, Mutex, RwLock, Barrier, Condvar};
-use std::sync::mpsc::{self, Sender, Receiver};
-use std::sync::atomic::{AtomicUsize, AtomicBool, Ordering};
-use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, RwLock, Barrier, Condvar};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::atomic::{self, AtomicUsize, AtomicBool, Ordering};
+use std::cell::UnsafeCell;
+use std::collections::{HashMap, VecDeque};
 
 fn main() {
     println!("=== Rust标准库线程和并发编程 ===");
@@ -391,6 +392,17 @@ fn advanced_synchronization() {
         handle.join().unwrap();
     }
     
+    // 基于屏障的分阶段流水线
+    let phases: Vec<Box<dyn Fn(usize) + Send + Sync>> = vec![
+        Box::new(|id| println!("工作者{} 加载数据", id)),
+        Box::new(|id| println!("工作者{} 处理数据", id)),
+        Box::new(|id| println!("工作者{} 保存结果", id)),
+    ];
+    match Pipeline::new(3).run(phases) {
+        Ok(()) => println!("流水线所有阶段完成"),
+        Err(e) => println!("流水线失败: {}", e),
+    }
+    
     // Once 一次性初始化
     use std::sync::Once;
     
@@ -422,6 +434,99 @@ fn advanced_synchronization() {
     }
 }
 
+// 某个工作者在某个阶段中的panic
+#[derive(Debug)]
+struct PhasePanic {
+    phase: usize,
+    worker: usize,
+    message: String,
+}
+
+// 流水线执行失败，包含所有收集到的panic
+#[derive(Debug)]
+struct PipelineError {
+    panics: Vec<PhasePanic>,
+}
+
+impl std::fmt::Display for PipelineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} 个工作者发生panic", self.panics.len())?;
+        for p in &self.panics {
+            write!(f, "; 阶段{} 工作者{}: {}", p.phase, p.worker, p.message)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for PipelineError {}
+
+// 分阶段流水线：所有工作者完成第k阶段后才开始第k+1阶段
+struct Pipeline {
+    num_workers: usize,
+}
+
+impl Pipeline {
+    fn new(num_workers: usize) -> Self {
+        Pipeline {
+            num_workers: num_workers.max(1),
+        }
+    }
+    
+    // 每个阶段接收工作者编号；任一工作者panic后，所有工作者在该阶段结束时停止
+    fn run(self, phases: Vec<Box<dyn Fn(usize) + Send + Sync>>) -> Result<(), PipelineError> {
+        let barrier = Barrier::new(self.num_workers);
+        let failed = AtomicBool::new(false);
+        let panics = Mutex::new(Vec::new());
+        
+        thread::scope(|scope| {
+            for worker in 0..self.num_workers {
+                let (barrier, failed, panics, phases) = (&barrier, &failed, &panics, &phases);
+                scope.spawn(move || {
+                    for (phase_index, phase) in phases.iter().enumerate() {
+                        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                            phase(worker)
+                        }));
+                        
+                        if let Err(payload) = result {
+                            panics.lock().unwrap().push(PhasePanic {
+                                phase: phase_index,
+                                worker,
+                                message: panic_message(payload.as_ref()),
+                            });
+                            failed.store(true, Ordering::Release);
+                        }
+                        
+                        // panic的工作者也要到达屏障，否则其他工作者会永远等待
+                        barrier.wait();
+                        if failed.load(Ordering::Acquire) {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+        
+        let mut panics = panics.into_inner().unwrap();
+        if panics.is_empty() {
+            Ok(())
+        } else {
+            panics.sort_by_key(|p| (p.phase, p.worker));
+            Err(PipelineError { panics })
+        }
+    }
+}
+
+// 从panic载荷中提取消息
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "未知panic".to_string()
+    }
+}
+
 // 线程池模式
 fn thread_pool_pattern() {
     println!("线程池模式演示:");
@@ -439,6 +544,102 @@ fn thread_pool_pattern() {
     
     thread::sleep(Duration::from_millis(1500));
     println!("所有任务提交完成");
+    
+    // 作用域线程：闭包可以直接借用调用者的局部变量
+    let offset = 100;
+    let results = par_map((1..=8).collect(), 3, |x: i32| x * x + offset);
+    println!("并行映射结果: {:?}", results);
+    
+    // 分治并行：归并排序的两半在不同的作用域线程中排序
+    let mut numbers = vec![38, 27, 43, 3, 9, 82, 10, 55, 1, 64];
+    par_sort(&mut numbers, 2);
+    println!("并行归并排序结果: {:?}", numbers);
+}
+
+// 并行归并排序：长度超过threshold时把切片分成两半，左半在新的作用域线程中排序，
+// 右半在当前线程排序，然后合并；不超过threshold时直接用标准库排序，避免线程开销
+fn par_sort<T: Ord + Send>(slice: &mut [T], threshold: usize) {
+    if slice.len() <= threshold.max(1) {
+        slice.sort();
+        return;
+    }
+    
+    let mid = slice.len() / 2;
+    let (left, right) = slice.split_at_mut(mid);
+    thread::scope(|scope| {
+        scope.spawn(|| par_sort(left, threshold));
+        par_sort(right, threshold);
+    });
+    merge_sorted_halves(slice, mid);
+}
+
+// 合并[..mid]和[mid..]两个有序段。T没有Clone，元素不能复制出去，
+// 所以先算出每个位置应放的元素下标，再沿置换环交换到位
+fn merge_sorted_halves<T: Ord>(slice: &mut [T], mid: usize) {
+    let mut source = Vec::with_capacity(slice.len());
+    let (mut i, mut j) = (0, mid);
+    while i < mid && j < slice.len() {
+        // 相等时取左半，保持稳定
+        if slice[j] < slice[i] {
+            source.push(j);
+            j += 1;
+        } else {
+            source.push(i);
+            i += 1;
+        }
+    }
+    source.extend(i..mid);
+    source.extend(j..slice.len());
+    
+    // 目标：新slice[k] = 旧slice[source[k]]
+    for start in 0..slice.len() {
+        let mut current = start;
+        loop {
+            let next = source[current];
+            source[current] = current;
+            if next == start || next == current {
+                break;
+            }
+            slice.swap(current, next);
+            current = next;
+        }
+    }
+}
+
+// 并行映射：把items按顺序切成连续的块分给workers个线程，结果顺序与输入一致
+fn par_map<T, R, F>(items: Vec<T>, workers: usize, f: F) -> Vec<R>
+where
+    T: Send,
+    R: Send,
+    F: Fn(T) -> R + Send + Sync,
+{
+    let workers = workers.max(1);
+    let chunk_size = items.len().div_ceil(workers).max(1);
+    
+    // 把所有权移动到各个块中
+    let mut chunks: Vec<Vec<T>> = Vec::with_capacity(workers);
+    let mut iter = items.into_iter();
+    loop {
+        let chunk: Vec<T> = iter.by_ref().take(chunk_size).collect();
+        if chunk.is_empty() {
+            break;
+        }
+        chunks.push(chunk);
+    }
+    
+    let f = &f;
+    thread::scope(|scope| {
+        let handles: Vec<_> = chunks
+            .into_iter()
+            .map(|chunk| scope.spawn(move || chunk.into_iter().map(f).collect::<Vec<R>>()))
+            .collect();
+        
+        // 按块的顺序收集结果
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect()
+    })
 }
 
 // 并发数据结构
@@ -532,6 +733,104 @@ fn performance_benchmarks() {
     
     println!("原子操作比Mutex快 {:.2} 倍", 
              mutex_duration.as_nanos() as f64 / atomic_duration.as_nanos() as f64);
+    
+    // 多线程同时记录每次加锁的耗时分布，记录过程不需要额外的锁
+    let histogram = AtomicHistogram::new(vec![50, 100, 250, 1_000, 10_000, 100_000]);
+    let mutex = Mutex::new(0);
+    thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                for _ in 0..10_000 {
+                    let start = std::time::Instant::now();
+                    *mutex.lock().unwrap() += 1;
+                    histogram.record(start.elapsed().as_nanos() as u64);
+                }
+            });
+        }
+    });
+    let snapshot = histogram.snapshot();
+    println!(
+        "加锁耗时(ns): 次数={} 平均={:.1} p50<={} p90<={} p99<={}",
+        snapshot.total, snapshot.mean, snapshot.p50, snapshot.p90, snapshot.p99
+    );
+    println!("各桶计数: {:?}", snapshot.counts);
+}
+
+// 固定桶边界的原子直方图：第i个桶统计 (bounds[i-1], bounds[i]] 内的值，
+// 最后一个桶统计超过所有边界的值。record只做几次fetch_add，多线程可以并发记录
+struct AtomicHistogram {
+    bounds: Vec<u64>,
+    buckets: Vec<atomic::AtomicU64>,
+    sum: atomic::AtomicU64,
+    max: atomic::AtomicU64,
+}
+
+#[derive(Debug)]
+struct HistogramSnapshot {
+    counts: Vec<u64>,
+    total: u64,
+    mean: f64,
+    p50: u64,
+    p90: u64,
+    p99: u64,
+}
+
+impl AtomicHistogram {
+    fn new(mut bounds: Vec<u64>) -> Self {
+        bounds.sort_unstable();
+        bounds.dedup();
+        let buckets = (0..=bounds.len()).map(|_| atomic::AtomicU64::new(0)).collect();
+        AtomicHistogram {
+            bounds,
+            buckets,
+            sum: atomic::AtomicU64::new(0),
+            max: atomic::AtomicU64::new(0),
+        }
+    }
+    
+    fn record(&self, value: u64) {
+        let index = self.bounds.partition_point(|&bound| bound < value);
+        self.buckets[index].fetch_add(1, Ordering::Relaxed);
+        self.sum.fetch_add(value, Ordering::Relaxed);
+        self.max.fetch_max(value, Ordering::Relaxed);
+    }
+    
+    // 各计数器分别读取，并发记录时快照只是近似一致
+    fn snapshot(&self) -> HistogramSnapshot {
+        let counts: Vec<u64> = self.buckets.iter().map(|b| b.load(Ordering::Relaxed)).collect();
+        let total: u64 = counts.iter().sum();
+        let mean = if total == 0 {
+            0.0
+        } else {
+            self.sum.load(Ordering::Relaxed) as f64 / total as f64
+        };
+        let max = self.max.load(Ordering::Relaxed);
+        
+        HistogramSnapshot {
+            p50: self.percentile(&counts, total, max, 0.50),
+            p90: self.percentile(&counts, total, max, 0.90),
+            p99: self.percentile(&counts, total, max, 0.99),
+            counts,
+            total,
+            mean,
+        }
+    }
+    
+    // 百分位估计：返回累计数量达到该比例的桶的上边界，溢出桶用记录到的最大值
+    fn percentile(&self, counts: &[u64], total: u64, max: u64, p: f64) -> u64 {
+        if total == 0 {
+            return 0;
+        }
+        let rank = ((p * total as f64).ceil() as u64).clamp(1, total);
+        let mut cumulative = 0;
+        for (index, count) in counts.iter().enumerate() {
+            cumulative += count;
+            if cumulative >= rank {
+                return self.bounds.get(index).map_or(max, |&bound| bound.min(max));
+            }
+        }
+        max
+    }
 }
 
 // 实际应用场景
@@ -681,6 +980,81 @@ fn demonstrate_condvar() {
     }
     
     println!("条件变量收到通知，继续执行");
+    
+    // 用Mutex + 两个Condvar实现的有界阻塞通道
+    let channel = Channel::new(1);
+    channel.send(1);
+    match channel.send_timeout(2, Duration::from_millis(50)) {
+        Ok(()) => println!("发送成功"),
+        Err(item) => println!("通道已满，发送超时，取回数据: {}", item),
+    }
+    println!("接收: {}", channel.recv());
+    println!("通道为空时接收: {:?}", channel.recv_timeout(Duration::from_millis(50)));
+}
+
+// 有界阻塞通道：队列满时发送方在not_full上等待，队列空时接收方在not_empty上等待
+struct Channel<T> {
+    queue: Mutex<VecDeque<T>>,
+    capacity: usize,
+    not_empty: Condvar,
+    not_full: Condvar,
+}
+
+impl<T> Channel<T> {
+    fn new(capacity: usize) -> Self {
+        assert!(capacity > 0);
+        Channel {
+            queue: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity,
+            not_empty: Condvar::new(),
+            not_full: Condvar::new(),
+        }
+    }
+    
+    fn send(&self, item: T) {
+        let queue = self.queue.lock().unwrap();
+        let mut queue = self
+            .not_full
+            .wait_while(queue, |queue| queue.len() >= self.capacity)
+            .unwrap();
+        queue.push_back(item);
+        self.not_empty.notify_one();
+    }
+    
+    // 超时仍然没有空位时把数据原样还给调用者，方便重试
+    fn send_timeout(&self, item: T, timeout: Duration) -> Result<(), T> {
+        let queue = self.queue.lock().unwrap();
+        let (mut queue, _) = self
+            .not_full
+            .wait_timeout_while(queue, timeout, |queue| queue.len() >= self.capacity)
+            .unwrap();
+        if queue.len() >= self.capacity {
+            return Err(item);
+        }
+        queue.push_back(item);
+        self.not_empty.notify_one();
+        Ok(())
+    }
+    
+    fn recv(&self) -> T {
+        let queue = self.queue.lock().unwrap();
+        let mut queue = self.not_empty.wait_while(queue, |queue| queue.is_empty()).unwrap();
+        let item = queue.pop_front().unwrap();
+        self.not_full.notify_one();
+        item
+    }
+    
+    // 通道没有断开的概念，失败时总是返回Timeout
+    fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        let queue = self.queue.lock().unwrap();
+        let (mut queue, _) = self
+            .not_empty
+            .wait_timeout_while(queue, timeout, |queue| queue.is_empty())
+            .unwrap();
+        let item = queue.pop_front().ok_or(RecvTimeoutError::Timeout)?;
+        self.not_full.notify_one();
+        Ok(item)
+    }
 }
 
 // 通道选择演示
@@ -695,26 +1069,110 @@ fn demonstrate_channel_selection() {
     
     thread::spawn(move || {
         thread::sleep(Duration::from_millis(200));
-        tx2.send("来自通道2").unwrap();
+        tx2.send(2).unwrap();
     });
     
-    // 注意：标准库没有内置的select!宏
-    // 这里演示基本的轮询接收
-    let mut received = 0;
-    let start = std::time::Instant::now();
+    // 注意：标准库没有内置的select!宏，这里用轮询实现的select2代替
+    for _ in 0..2 {
+        match select2(&rx1, &rx2, Duration::from_millis(500)) {
+            Some(Either::Left(msg)) => println!("选择性接收(通道1): {}", msg),
+            Some(Either::Right(n)) => println!("选择性接收(通道2): {}", n),
+            None => println!("选择性接收超时"),
+        }
+    }
+    
+    // N 路选择：同类型的多个通道
+    let receivers: Vec<Receiver<String>> = (0..3)
+        .map(|i| {
+            let (tx, rx) = mpsc::channel();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(30 * (3 - i)));
+                tx.send(format!("工作者{}完成", i)).unwrap();
+            });
+            rx
+        })
+        .collect();
     
-    while received < 2 && start.elapsed() < Duration::from_millis(500) {
-        if let Ok(msg) = rx1.try_recv() {
-            println!("选择性接收: {}", msg);
-            received += 1;
+    while let Some((index, msg)) = select_many(&receivers, Duration::from_millis(500)) {
+        println!("通道{}: {}", index, msg);
+    }
+}
+
+// 两个通道中任意一个的值
+#[derive(Debug, PartialEq)]
+enum Either<A, B> {
+    Left(A),
+    Right(B),
+}
+
+// 轮询的退避时间：从1毫秒开始翻倍，最长10毫秒
+fn next_backoff(current: Duration) -> Duration {
+    (current * 2).min(Duration::from_millis(10))
+}
+
+// 等待两个通道中先到达的消息，超时或两个通道都已断开时返回None
+fn select2<A, B>(a: &Receiver<A>, b: &Receiver<B>, timeout: Duration) -> Option<Either<A, B>> {
+    let deadline = std::time::Instant::now() + timeout;
+    let mut backoff = Duration::from_millis(1);
+    let mut a_first = true;
+    
+    loop {
+        let mut disconnected = 0;
+        
+        // 每轮交换检查顺序，避免总是偏向同一个通道
+        for turn in 0..2 {
+            let check_a = (turn == 0) == a_first;
+            let result = if check_a {
+                a.try_recv().map(Either::Left)
+            } else {
+                b.try_recv().map(Either::Right)
+            };
+            
+            match result {
+                Ok(value) => return Some(value),
+                Err(mpsc::TryRecvError::Disconnected) => disconnected += 1,
+                Err(mpsc::TryRecvError::Empty) => {}
+            }
         }
         
-        if let Ok(msg) = rx2.try_recv() {
-            println!("选择性接收: {}", msg);
-            received += 1;
+        let now = std::time::Instant::now();
+        if disconnected == 2 || now >= deadline {
+            return None;
         }
         
-        thread::sleep(Duration::from_millis(10));
+        a_first = !a_first;
+        thread::sleep(backoff.min(deadline - now));
+        backoff = next_backoff(backoff);
+    }
+}
+
+// 等待多个同类型通道中先到达的消息，返回(通道下标, 消息)
+fn select_many<T>(receivers: &[Receiver<T>], timeout: Duration) -> Option<(usize, T)> {
+    let deadline = std::time::Instant::now() + timeout;
+    let mut backoff = Duration::from_millis(1);
+    let mut start = 0;
+    
+    loop {
+        let mut disconnected = 0;
+        
+        // 每轮从不同的通道开始检查
+        for offset in 0..receivers.len() {
+            let index = (start + offset) % receivers.len();
+            match receivers[index].try_recv() {
+                Ok(value) => return Some((index, value)),
+                Err(mpsc::TryRecvError::Disconnected) => disconnected += 1,
+                Err(mpsc::TryRecvError::Empty) => {}
+            }
+        }
+        
+        let now = std::time::Instant::now();
+        if disconnected == receivers.len() || now >= deadline {
+            return None;
+        }
+        
+        start = (start + 1) % receivers.len();
+        thread::sleep(backoff.min(deadline - now));
+        backoff = next_backoff(backoff);
     }
 }
 
@@ -750,37 +1208,92 @@ fn demonstrate_memory_ordering() {
     
     writer.join().unwrap();
     reader.join().unwrap();
+    
+    // SeqLock：读者无锁读取，写者更新时读者重试
+    let lock = Arc::new(SeqLock::new((0u64, 0u64)));
+    let writer_lock = Arc::clone(&lock);
+    let writer = thread::spawn(move || {
+        for i in 1..=1000u64 {
+            writer_lock.write((i, i * 2));
+        }
+    });
+    
+    let (a, b) = lock.read();
+    println!("SeqLock读取: ({}, {})，第二项总是第一项的两倍", a, b);
+    writer.join().unwrap();
+    println!("SeqLock最终值: {:?}", lock.read());
 }
 
-// 无锁概念演示
-fn demonstrate_lockfree_concepts() {
-    println!("无锁数据结构概念:");
-    println!("- 使用原子操作和CAS");
-    println!("- 避免阻塞，提高性能");
-    println!("- 实现复杂，需要careful memory ordering");
-    println!("- 常见的有：无锁队列、无锁链表、无锁哈希表");
+// 顺序锁：序号为奇数表示正在写入，读者在写入期间或序号变化时重试
+struct SeqLock<T: Copy> {
+    seq: AtomicUsize,
+    data: UnsafeCell<T>,
+}
+
+// 读者只会复制数据，且只返回序号前后一致时读到的副本
+unsafe impl<T: Copy + Send> Sync for SeqLock<T> {}
+
+impl<T: Copy> SeqLock<T> {
+    fn new(value: T) -> Self {
+        SeqLock {
+            seq: AtomicUsize::new(0),
+            data: UnsafeCell::new(value),
+        }
+    }
     
-    // 简单的无锁计数器
-    struct LockFreeCounter {
-        count: AtomicUsize,
+    fn read(&self) -> T {
+        loop {
+            let before = self.seq.load(Ordering::Acquire);
+            if before % 2 == 1 {
+                std::hint::spin_loop();
+                continue;
+            }
+            
+            // 可能与写者并发，读到的值只有在序号未变时才会被使用
+            let value = unsafe { std::ptr::read_volatile(self.data.get()) };
+            
+            // 保证数据读取发生在第二次读取序号之前
+            atomic::fence(Ordering::Acquire);
+            let after = self.seq.load(Ordering::Relaxed);
+            if before == after {
+                return value;
+            }
+        }
     }
     
-    impl LockFreeCounter {
-        fn new() -> Self {
-            LockFreeCounter {
-                count: AtomicUsize::new(0),
+    fn write(&self, value: T) {
+        // 把序号从偶数改为奇数，同时保证同一时刻只有一个写者
+        let mut seq = self.seq.load(Ordering::Relaxed);
+        loop {
+            if seq % 2 == 1 {
+                std::hint::spin_loop();
+                seq = self.seq.load(Ordering::Relaxed);
+                continue;
+            }
+            match self.seq.compare_exchange_weak(seq, seq + 1, Ordering::Acquire, Ordering::Relaxed) {
+                Ok(_) => break,
+                Err(actual) => seq = actual,
             }
         }
         
-        fn increment(&self) -> usize {
-            self.count.fetch_add(1, Ordering::SeqCst)
-        }
+        // 保证奇数序号先于数据写入对读者可见
+        atomic::fence(Ordering::Release);
+        unsafe { std::ptr::write_volatile(self.data.get(), value) };
         
-        fn get(&self) -> usize {
-            self.count.load(Ordering::SeqCst)
-        }
+        // 写入完成，序号回到偶数
+        self.seq.store(seq + 2, Ordering::Release);
     }
+}
+
+// 无锁概念演示
+fn demonstrate_lockfree_concepts() {
+    println!("无锁数据结构概念:");
+    println!("- 使用原子操作和CAS");
+    println!("- 避免阻塞，提高性能");
+    println!("- 实现复杂，需要careful memory ordering");
+    println!("- 常见的有：无锁队列、无锁链表、无锁哈希表");
     
+    // 简单的无锁计数器
     let counter = Arc::new(LockFreeCounter::new());
     let mut handles = vec![];
     
@@ -799,6 +1312,67 @@ fn demonstrate_lockfree_concepts() {
     }
     
     println!("无锁计数器最终值: {}", counter.get());
+    
+    // 递减在0处饱和，比较交换和重置
+    counter.compare_and_set(5000, 1);
+    counter.decrement();
+    counter.decrement();
+    println!("饱和递减后的值: {}", counter.get());
+    println!("重置前的值: {}", counter.reset());
+}
+
+// 简单的无锁计数器
+// 计数器不用来发布其他数据，只需要原子性，因此统一使用Relaxed
+struct LockFreeCounter {
+    count: AtomicUsize,
+}
+
+impl LockFreeCounter {
+    fn new() -> Self {
+        LockFreeCounter {
+            count: AtomicUsize::new(0),
+        }
+    }
+    
+    // 返回增加前的值
+    fn increment(&self) -> usize {
+        self.count.fetch_add(1, Ordering::Relaxed)
+    }
+    
+    // 返回减少前的值，已经为0时保持为0而不回绕
+    fn decrement(&self) -> usize {
+        let mut current = self.count.load(Ordering::Relaxed);
+        loop {
+            if current == 0 {
+                return 0;
+            }
+            match self.count.compare_exchange_weak(
+                current,
+                current - 1,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(previous) => return previous,
+                Err(actual) => current = actual,
+            }
+        }
+    }
+    
+    // 当前值等于expected时设置为new
+    fn compare_and_set(&self, expected: usize, new: usize) -> bool {
+        self.count
+            .compare_exchange(expected, new, Ordering::Relaxed, Ordering::Relaxed)
+            .is_ok()
+    }
+    
+    // 清零并返回之前的值
+    fn reset(&self) -> usize {
+        self.count.swap(0, Ordering::Relaxed)
+    }
+    
+    fn get(&self) -> usize {
+        self.count.load(Ordering::Relaxed)
+    }
 }
 
 // 生产者消费者示例
@@ -834,32 +1408,229 @@ fn work_stealing_concept() {
     println!("- 空闲线程从其他线程队列窃取任务");
     println!("- 减少线程间竞争，提高效率");
     println!("- 适用于分治算法和任务并行");
+    
+    let counter = Arc::new(AtomicUsize::new(0));
+    let start = std::time::Instant::now();
+    let pool = WorkStealingPool::new(4, 256);
+    for _ in 0..10_000 {
+        let counter = Arc::clone(&counter);
+        pool.execute(move || {
+            counter.fetch_add(1, Ordering::Relaxed);
+        });
+    }
+    let stats = Arc::clone(&pool.shared);
+    drop(pool);
+    println!(
+        "工作窃取线程池完成 {} 个任务，用时 {:?}，窃取 {} 次",
+        counter.load(Ordering::Relaxed),
+        start.elapsed(),
+        stats.steals.load(Ordering::Relaxed)
+    );
 }
 
-// MapReduce示例
-fn map_reduce_example() {
-    let data = vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
-    let chunk_size = 3;
-    let (tx, rx) = mpsc::channel();
-    
-    // Map阶段：并行处理数据块
-    for chunk in data.chunks(chunk_size) {
-        let tx = tx.clone();
-        let chunk = chunk.to_vec();
+// 工作窃取线程池：每个worker有自己的有界双端队列，
+// 自己从队尾取任务，队列为空时从随机一个其他worker的队首窃取
+struct WorkStealingPool {
+    shared: Arc<StealingShared>,
+    workers: Vec<thread::JoinHandle<()>>,
+    next_queue: AtomicUsize,
+}
+
+struct StealingShared {
+    queues: Vec<Mutex<VecDeque<Job>>>,
+    capacity: usize,
+    shutdown: AtomicBool,
+    steals: AtomicUsize,
+    idle: Mutex<()>,
+    available: Condvar,
+}
+
+impl WorkStealingPool {
+    fn new(size: usize, capacity: usize) -> Self {
+        assert!(size > 0 && capacity > 0);
         
-        thread::spawn(move || {
-            let sum: i32 = chunk.iter().map(|x| x * x).sum(); // 平方和
-            tx.send(sum).unwrap();
+        let shared = Arc::new(StealingShared {
+            queues: (0..size).map(|_| Mutex::new(VecDeque::with_capacity(capacity))).collect(),
+            capacity,
+            shutdown: AtomicBool::new(false),
+            steals: AtomicUsize::new(0),
+            idle: Mutex::new(()),
+            available: Condvar::new(),
         });
+        
+        let workers = (0..size)
+            .map(|id| {
+                let shared = Arc::clone(&shared);
+                thread::spawn(move || shared.run_worker(id))
+            })
+            .collect();
+        
+        WorkStealingPool {
+            shared,
+            workers,
+            next_queue: AtomicUsize::new(0),
+        }
     }
     
-    drop(tx); // 关闭发送端
+    // 轮流投递到各worker的队列；目标队列满了就换下一个，
+    // 全部满时由调用者线程直接执行，形成背压
+    fn execute<F>(&self, f: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let job: Job = Box::new(f);
+        let count = self.shared.queues.len();
+        let start = self.next_queue.fetch_add(1, Ordering::Relaxed);
+        
+        for offset in 0..count {
+            let mut queue = self.shared.queues[(start + offset) % count].lock().unwrap();
+            if queue.len() < self.shared.capacity {
+                queue.push_back(job);
+                drop(queue);
+                // 持有idle再通知，worker检查队列和进入wait之间不会漏掉这次唤醒
+                let _idle = self.shared.idle.lock().unwrap();
+                self.shared.available.notify_one();
+                return;
+            }
+        }
+        
+        // 和worker一样，任务panic不能传给调用者
+        let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(job));
+    }
+}
+
+impl Drop for WorkStealingPool {
+    // 等待所有已提交的任务执行完再退出
+    fn drop(&mut self) {
+        self.shared.shutdown.store(true, Ordering::Release);
+        drop(self.shared.idle.lock().unwrap());
+        self.shared.available.notify_all();
+        
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl StealingShared {
+    fn run_worker(&self, id: usize) {
+        // xorshift随机数，用来挑选窃取对象
+        let mut seed = (id as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        
+        loop {
+            let own = self.queues[id].lock().unwrap().pop_back();
+            if let Some(job) = own.or_else(|| self.steal(id, &mut seed)) {
+                // 任务panic不能带走worker，否则它队列里剩下的任务只能靠窃取
+                let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(job));
+                continue;
+            }
+            
+            if self.shutdown.load(Ordering::Acquire)
+                && self.queues.iter().all(|queue| queue.lock().unwrap().is_empty())
+            {
+                break;
+            }
+            
+            // 持有idle后再检查一次，execute和drop通知前都会拿idle，所以可以放心睡下去
+            let idle = self.idle.lock().unwrap();
+            if self.shutdown.load(Ordering::Acquire)
+                || self.queues.iter().any(|queue| !queue.lock().unwrap().is_empty())
+            {
+                continue;
+            }
+            drop(self.available.wait(idle).unwrap());
+        }
+    }
     
-    // Reduce阶段：收集并汇总结果
-    let total: i32 = rx.iter().sum();
+    fn steal(&self, id: usize, seed: &mut u64) -> Option<Job> {
+        let count = self.queues.len();
+        if count < 2 {
+            return None;
+        }
+        
+        *seed ^= *seed << 13;
+        *seed ^= *seed >> 7;
+        *seed ^= *seed << 17;
+        let start = (*seed % count as u64) as usize;
+        
+        (0..count)
+            .map(|offset| (start + offset) % count)
+            .filter(|&victim| victim != id)
+            .find_map(|victim| self.queues[victim].lock().unwrap().pop_front())
+            .inspect(|_| {
+                self.steals.fetch_add(1, Ordering::Relaxed);
+            })
+    }
+}
+
+// MapReduce示例
+fn map_reduce_example() {
+    let data: Vec<i32> = (1..=10).collect();
+    
+    // 平方和：每个块计算局部平方和，再把局部结果相加
+    let total = map_reduce(
+        data,
+        3,
+        2,
+        |chunk| chunk.iter().map(|x| x * x).sum::<i32>(),
+        0,
+        |a, b| a + b,
+    );
     println!("MapReduce结果 (平方和): {}", total);
 }
 
+// 通用并行MapReduce：data按chunk_size切块，最多max_threads个线程领取并映射各块，
+// 最后按块的顺序用reduce从identity开始合并，因此reduce不要求满足交换律
+fn map_reduce<T, R, MapF, RedF>(
+    data: Vec<T>,
+    chunk_size: usize,
+    max_threads: usize,
+    map: MapF,
+    identity: R,
+    reduce: RedF,
+) -> R
+where
+    T: Sync,
+    R: Send,
+    MapF: Fn(&[T]) -> R + Sync,
+    RedF: Fn(R, R) -> R,
+{
+    let chunks: Vec<&[T]> = data.chunks(chunk_size.max(1)).collect();
+    let threads = max_threads.max(1).min(chunks.len());
+    let next_chunk = AtomicUsize::new(0);
+    
+    // Map阶段：工作线程不断领取下一个未处理的块
+    let mut partials: Vec<(usize, R)> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads)
+            .map(|_| {
+                let (chunks, next_chunk, map) = (&chunks, &next_chunk, &map);
+                scope.spawn(move || {
+                    let mut results = Vec::new();
+                    loop {
+                        let index = next_chunk.fetch_add(1, Ordering::Relaxed);
+                        if index >= chunks.len() {
+                            break;
+                        }
+                        results.push((index, map(chunks[index])));
+                    }
+                    results
+                })
+            })
+            .collect();
+        
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect()
+    });
+    
+    // Reduce阶段：按块的顺序汇总结果
+    partials.sort_by_key(|&(index, _)| index);
+    partials
+        .into_iter()
+        .fold(identity, |acc, (_, partial)| reduce(acc, partial))
+}
+
 // 死锁预防示例
 fn deadlock_prevention_example() {
     println!("死锁预防示例:");
@@ -892,9 +1663,43 @@ fn deadlock_prevention_example() {
     handle1.join().unwrap();
     handle2.join().unwrap();
     
+    // 用lock_ordered加锁时，调用者不需要关心顺序
+    let account_a = Mutex::new(100);
+    let account_b = Mutex::new(50);
+    thread::scope(|scope| {
+        scope.spawn(|| {
+            let mut guards = lock_ordered(&[&account_a, &account_b]);
+            *guards[0] -= 30;
+            *guards[1] += 30;
+        });
+        scope.spawn(|| {
+            let mut guards = lock_ordered(&[&account_b, &account_a]);
+            *guards[0] -= 10;
+            *guards[1] += 10;
+        });
+    });
+    println!("转账后余额: A={}, B={}", account_a.lock().unwrap(), account_b.lock().unwrap());
+    
     println!("成功避免死锁");
 }
 
+// 按互斥锁的地址顺序依次加锁，所有调用者的实际加锁顺序都一致，因此不会互相死锁；
+// 返回的guard与传入的顺序一一对应。同一个锁传入两次会自己死锁，直接panic
+fn lock_ordered<'a, T>(mutexes: &[&'a Mutex<T>]) -> Vec<MutexGuard<'a, T>> {
+    let mut order: Vec<usize> = (0..mutexes.len()).collect();
+    order.sort_by_key(|&i| mutexes[i] as *const Mutex<T> as usize);
+    assert!(
+        order.windows(2).all(|pair| !std::ptr::eq(mutexes[pair[0]], mutexes[pair[1]])),
+        "lock_ordered: 同一个Mutex不能出现两次"
+    );
+    
+    let mut guards: Vec<Option<MutexGuard<'a, T>>> = mutexes.iter().map(|_| None).collect();
+    for i in order {
+        guards[i] = Some(mutexes[i].lock().unwrap());
+    }
+    guards.into_iter().map(|guard| guard.unwrap()).collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1005,6 +1810,473 @@ mod tests {
         assert_eq!(*r, 10);
     }
     
+    #[test]
+    fn test_select2_faster_channel() {
+        let (tx1, rx1) = mpsc::channel::<&str>();
+        let (tx2, rx2) = mpsc::channel::<i32>();
+        
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(200));
+            let _ = tx1.send("慢");
+        });
+        
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            tx2.send(7).unwrap();
+        });
+        
+        let result = select2(&rx1, &rx2, Duration::from_secs(2));
+        assert_eq!(result, Some(Either::Right(7)));
+        
+        // select_many 同样返回先到达的通道下标
+        let (fast_tx, fast_rx) = mpsc::channel();
+        let (_slow_tx, slow_rx) = mpsc::channel();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            fast_tx.send("快").unwrap();
+        });
+        
+        let receivers = vec![slow_rx, fast_rx];
+        assert_eq!(select_many(&receivers, Duration::from_secs(2)), Some((1, "快")));
+    }
+    
+    #[test]
+    fn test_select_timeout() {
+        // 保持发送端存活，但不发送任何消息
+        let (_tx1, rx1) = mpsc::channel::<i32>();
+        let (_tx2, rx2) = mpsc::channel::<String>();
+        
+        let start = std::time::Instant::now();
+        assert_eq!(select2(&rx1, &rx2, Duration::from_millis(50)), None);
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        
+        let (_tx3, rx3) = mpsc::channel::<i32>();
+        let receivers = vec![rx1, rx3];
+        assert_eq!(select_many(&receivers, Duration::from_millis(50)), None);
+    }
+    
+    #[test]
+    fn test_lockfree_counter() {
+        let counter = Arc::new(LockFreeCounter::new());
+        let mut handles = vec![];
+        
+        for _ in 0..5 {
+            let counter = Arc::clone(&counter);
+            let handle = thread::spawn(move || {
+                for _ in 0..1000 {
+                    counter.increment();
+                    counter.decrement();
+                }
+            });
+            handles.push(handle);
+        }
+        
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        
+        assert_eq!(counter.get(), 0);
+        
+        // 不会回绕到usize::MAX
+        assert_eq!(counter.decrement(), 0);
+        assert_eq!(counter.get(), 0);
+        
+        counter.increment();
+        assert!(!counter.compare_and_set(0, 10));
+        assert!(counter.compare_and_set(1, 10));
+        assert_eq!(counter.reset(), 10);
+        assert_eq!(counter.get(), 0);
+    }
+    
+    #[test]
+    fn test_par_map() {
+        let items: Vec<u64> = (0..1000).collect();
+        let expected: Vec<u64> = items.iter().map(|x| x * x).collect();
+        
+        assert_eq!(par_map(items, 4, |x| x * x), expected);
+        
+        // 空输入和工作线程数多于元素的情况
+        assert!(par_map(Vec::<u64>::new(), 4, |x| x).is_empty());
+        assert_eq!(par_map(vec![1, 2], 8, |x| x + 1), vec![2, 3]);
+    }
+    
+    #[test]
+    fn test_pipeline_phases_in_order() {
+        // 每个阶段一份日志，每条记录为(全局序号, 工作者编号)
+        type PhaseLog = Arc<Mutex<Vec<(usize, usize)>>>;
+        let sequence = Arc::new(AtomicUsize::new(0));
+        let logs: Vec<PhaseLog> = (0..3).map(|_| Arc::new(Mutex::new(Vec::new()))).collect();
+        
+        let phases: Vec<Box<dyn Fn(usize) + Send + Sync>> = logs
+            .iter()
+            .enumerate()
+            .map(|(phase, log)| {
+                let log = Arc::clone(log);
+                let sequence = Arc::clone(&sequence);
+                Box::new(move |worker: usize| {
+                    // 让不同工作者以不同速度完成
+                    thread::sleep(Duration::from_millis(((worker + phase) % 3) as u64 * 5));
+                    let seq = sequence.fetch_add(1, Ordering::SeqCst);
+                    log.lock().unwrap().push((seq, worker));
+                }) as Box<dyn Fn(usize) + Send + Sync>
+            })
+            .collect();
+        
+        Pipeline::new(4).run(phases).unwrap();
+        
+        for k in 1..logs.len() {
+            let previous = logs[k - 1].lock().unwrap();
+            let current = logs[k].lock().unwrap();
+            assert_eq!(previous.len(), 4);
+            assert_eq!(current.len(), 4);
+            
+            let last_previous = previous.iter().map(|&(seq, _)| seq).max().unwrap();
+            let first_current = current.iter().map(|&(seq, _)| seq).min().unwrap();
+            assert!(last_previous < first_current);
+        }
+    }
+    
+    #[test]
+    fn test_pipeline_collects_panics() {
+        let reached_last = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&reached_last);
+        
+        let phases: Vec<Box<dyn Fn(usize) + Send + Sync>> = vec![
+            Box::new(|worker| {
+                if worker == 1 {
+                    panic!("工作者1失败");
+                }
+            }),
+            Box::new(move |_| {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }),
+        ];
+        
+        let err = Pipeline::new(3).run(phases).unwrap_err();
+        assert_eq!(err.panics.len(), 1);
+        assert_eq!(err.panics[0].phase, 0);
+        assert_eq!(err.panics[0].worker, 1);
+        assert_eq!(err.panics[0].message, "工作者1失败");
+        
+        // 失败后不再进入下一阶段
+        assert_eq!(reached_last.load(Ordering::SeqCst), 0);
+    }
+    
+    #[test]
+    fn test_map_reduce_word_frequency() {
+        let lines: Vec<String> = (0..200)
+            .map(|i| format!("rust thread {} channel rust lock{}", i % 7, i % 3))
+            .collect();
+        
+        fn count_words(lines: &[String]) -> HashMap<String, usize> {
+            let mut counts = HashMap::new();
+            for line in lines {
+                for word in line.split_whitespace() {
+                    *counts.entry(word.to_string()).or_insert(0) += 1;
+                }
+            }
+            counts
+        }
+        
+        let expected = count_words(&lines);
+        
+        let merged = map_reduce(
+            lines,
+            16,
+            3,
+            count_words,
+            HashMap::new(),
+            |mut acc, partial| {
+                for (word, count) in partial {
+                    *acc.entry(word).or_insert(0) += count;
+                }
+                acc
+            },
+        );
+        
+        assert_eq!(merged, expected);
+        assert_eq!(merged["rust"], 400);
+    }
+    
+    #[test]
+    fn test_map_reduce_preserves_chunk_order() {
+        // 字符串拼接不满足交换律，结果必须按块的顺序合并
+        let data: Vec<u32> = (0..50).collect();
+        let joined = map_reduce(
+            data,
+            4,
+            8,
+            |chunk| chunk.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(","),
+            String::new(),
+            |acc, part| if acc.is_empty() { part } else { acc + "," + &part },
+        );
+        
+        let expected: Vec<String> = (0..50).map(|n| n.to_string()).collect();
+        assert_eq!(joined, expected.join(","));
+    }
+    
+    #[test]
+    fn test_seqlock_no_torn_reads() {
+        let lock = Arc::new(SeqLock::new((0u64, 0u64)));
+        let done = Arc::new(AtomicBool::new(false));
+        
+        let writer = {
+            let lock = Arc::clone(&lock);
+            let done = Arc::clone(&done);
+            thread::spawn(move || {
+                for i in 1..=100_000u64 {
+                    lock.write((i, i));
+                }
+                done.store(true, Ordering::Release);
+            })
+        };
+        
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let lock = Arc::clone(&lock);
+                let done = Arc::clone(&done);
+                thread::spawn(move || {
+                    let mut last = 0;
+                    while !done.load(Ordering::Acquire) {
+                        let (a, b) = lock.read();
+                        assert_eq!(a, b, "读到了撕裂的值");
+                        assert!(a >= last, "读到的值倒退了");
+                        last = a;
+                    }
+                })
+            })
+            .collect();
+        
+        writer.join().unwrap();
+        for reader in readers {
+            reader.join().unwrap();
+        }
+        
+        assert_eq!(lock.read(), (100_000, 100_000));
+    }
+    
+    #[test]
+    fn test_work_stealing_pool_runs_all_jobs() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let pool = WorkStealingPool::new(4, 64);
+        
+        for _ in 0..10_000 {
+            let counter = Arc::clone(&counter);
+            pool.execute(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+            });
+        }
+        
+        drop(pool);
+        assert_eq!(counter.load(Ordering::SeqCst), 10_000);
+    }
+    
+    #[test]
+    fn test_work_stealing_pool_survives_panicking_job() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let pool = WorkStealingPool::new(2, 64);
+        
+        for i in 0..100 {
+            let counter = Arc::clone(&counter);
+            pool.execute(move || {
+                if i % 10 == 0 {
+                    panic!("任务 {} 失败", i);
+                }
+                counter.fetch_add(1, Ordering::SeqCst);
+            });
+        }
+        
+        // drop不会因为worker panic而再次panic，其余任务全部完成
+        drop(pool);
+        assert_eq!(counter.load(Ordering::SeqCst), 90);
+    }
+    
+    #[test]
+    fn test_work_stealing_pool_inline_job_panic_is_caught() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let (started_tx, started_rx) = mpsc::channel();
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        let pool = WorkStealingPool::new(1, 1);
+        
+        // 占住唯一的worker，再把队列填满
+        pool.execute(move || {
+            started_tx.send(()).unwrap();
+            let _ = release_rx.recv();
+        });
+        started_rx.recv().unwrap();
+        let queued_counter = Arc::clone(&counter);
+        pool.execute(move || {
+            queued_counter.fetch_add(1, Ordering::SeqCst);
+        });
+        
+        // 队列已满，任务在调用者线程执行，panic不会传出来
+        pool.execute(|| panic!("调用者线程上的任务失败"));
+        let inline_counter = Arc::clone(&counter);
+        pool.execute(move || {
+            inline_counter.fetch_add(1, Ordering::SeqCst);
+        });
+        assert_eq!(counter.load(Ordering::SeqCst), 1);
+        
+        release_tx.send(()).unwrap();
+        drop(pool);
+        assert_eq!(counter.load(Ordering::SeqCst), 2);
+    }
+    
+    #[test]
+    fn test_work_stealing_pool_steals_from_blocked_worker() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let (started_tx, started_rx) = mpsc::channel();
+        let pool = WorkStealingPool::new(2, 1024);
+        
+        // 第一个任务占住一个worker，直到其余任务全部完成
+        let blocker_counter = Arc::clone(&counter);
+        pool.execute(move || {
+            started_tx.send(()).unwrap();
+            let deadline = std::time::Instant::now() + Duration::from_secs(5);
+            while blocker_counter.load(Ordering::SeqCst) < 100
+                && std::time::Instant::now() < deadline
+            {
+                thread::sleep(Duration::from_millis(1));
+            }
+        });
+        started_rx.recv().unwrap();
+        
+        // 一半任务落在被占住的worker队列里，只能被另一个worker窃取
+        for _ in 0..100 {
+            let counter = Arc::clone(&counter);
+            pool.execute(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+            });
+        }
+        
+        let shared = Arc::clone(&pool.shared);
+        drop(pool);
+        assert_eq!(counter.load(Ordering::SeqCst), 100);
+        assert!(shared.steals.load(Ordering::Relaxed) >= 50);
+    }
+    
+    #[test]
+    fn test_lock_ordered_opposite_orders_no_deadlock() {
+        let first = Arc::new(Mutex::new(0));
+        let second = Arc::new(Mutex::new(0));
+        let (done_tx, done_rx) = mpsc::channel();
+        
+        for reversed in [false, true] {
+            let (first, second) = (Arc::clone(&first), Arc::clone(&second));
+            let done_tx = done_tx.clone();
+            thread::spawn(move || {
+                for _ in 0..1000 {
+                    let input: [&Mutex<i32>; 2] = if reversed {
+                        [&second, &first]
+                    } else {
+                        [&first, &second]
+                    };
+                    let mut guards = lock_ordered(&input);
+                    // guard顺序与传入顺序一致
+                    *guards[0] += if reversed { 10 } else { 1 };
+                    *guards[1] += if reversed { 1 } else { 10 };
+                }
+                done_tx.send(()).unwrap();
+            });
+        }
+        
+        // 看门狗：死锁时超时失败而不是一直挂起
+        for _ in 0..2 {
+            done_rx
+                .recv_timeout(Duration::from_secs(5))
+                .expect("lock_ordered 发生死锁");
+        }
+        assert_eq!(*first.lock().unwrap(), 1000 + 1000);
+        assert_eq!(*second.lock().unwrap(), 10_000 + 10_000);
+    }
+    
+    #[test]
+    fn test_channel_send_timeout_returns_item() {
+        let channel = Channel::new(2);
+        channel.send("a");
+        assert_eq!(channel.send_timeout("b", Duration::from_millis(10)), Ok(()));
+        
+        let start = std::time::Instant::now();
+        assert_eq!(channel.send_timeout("c", Duration::from_millis(50)), Err("c"));
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        
+        // 腾出空位后重试成功
+        assert_eq!(channel.recv(), "a");
+        assert_eq!(channel.send_timeout("c", Duration::from_millis(10)), Ok(()));
+    }
+    
+    #[test]
+    fn test_channel_recv_timeout_and_wakeup() {
+        let channel = Arc::new(Channel::new(1));
+        let start = std::time::Instant::now();
+        assert_eq!(channel.recv_timeout(Duration::from_millis(50)), Err(RecvTimeoutError::Timeout));
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        
+        // 另一个线程发送后，等待中的接收方被唤醒
+        let sender = Arc::clone(&channel);
+        let handle = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            sender.send(42);
+        });
+        assert_eq!(channel.recv_timeout(Duration::from_secs(5)), Ok(42));
+        handle.join().unwrap();
+    }
+    
+    #[test]
+    fn test_par_sort_matches_sequential_sort() {
+        // 线性同余生成伪随机数据
+        let mut seed: u64 = 12345;
+        let data: Vec<u64> = (0..100_000)
+            .map(|_| {
+                seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                (seed >> 33) % 10_000
+            })
+            .collect();
+        let mut expected = data.clone();
+        expected.sort();
+        
+        let mut parallel = data.clone();
+        par_sort(&mut parallel, 1_000);
+        assert_eq!(parallel, expected);
+        
+        // 阈值不小于长度时不会启动任何线程
+        let mut sequential = data;
+        par_sort(&mut sequential, usize::MAX);
+        assert_eq!(sequential, expected);
+        
+        let mut strings = vec!["pear", "apple", "fig", "kiwi", "banana"];
+        par_sort(&mut strings, 1);
+        assert_eq!(strings, ["apple", "banana", "fig", "kiwi", "pear"]);
+    }
+    
+    #[test]
+    fn test_atomic_histogram_concurrent_records() {
+        let histogram = AtomicHistogram::new(vec![10, 50, 100]);
+        
+        // 4个线程各记录1..=100
+        thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for value in 1..=100 {
+                        histogram.record(value);
+                    }
+                });
+            }
+        });
+        histogram.record(500);
+        
+        let snapshot = histogram.snapshot();
+        assert_eq!(snapshot.counts, vec![40, 160, 200, 1]);
+        assert_eq!(snapshot.total, 401);
+        assert!((snapshot.mean - (4.0 * 5050.0 + 500.0) / 401.0).abs() < 1e-9);
+        // 第201个值落在(50, 100]桶
+        assert_eq!(snapshot.p50, 100);
+        assert_eq!(snapshot.p99, 100);
+        
+        let empty = AtomicHistogram::new(vec![10]).snapshot();
+        assert_eq!((empty.total, empty.p50), (0, 0));
+    }
+    
     #[test]
     fn test_thread_pool() {
         let pool = SimpleThreadPool::new(2);
@@ -32,7 +32,9 @@ Rust标准库的时间处理主要通过std::time模块提供：
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use std::thread;
 use std::sync::{Arc, Mutex};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
 
 fn main() {
     println!("=== Rust标准库时间和日期处理 ===");
@@ -520,37 +522,142 @@ fn memory_allocation_benchmark() {
 
 // 重试机制
 fn retry_with_timeout() {
-    let max_retries = 3;
-    let timeout = Duration::from_millis(100);
+    let policy = RetryPolicy::new(3, Duration::from_millis(50))
+        .exponential(2.0)
+        .with_max_delay(Duration::from_secs(1))
+        .with_jitter();
+    
+    let mut attempt = 0;
+    let result = retry(
+        || {
+            attempt += 1;
+            if simulate_network_request() {
+                println!("  第{}次尝试成功", attempt);
+                Ok(attempt)
+            } else {
+                println!("  第{}次尝试失败", attempt);
+                Err("网络请求失败")
+            }
+        },
+        policy,
+    );
     
-    for attempt in 1..=max_retries {
-        let start = Instant::now();
-        
-        // 模拟可能失败的操作
-        let success = simulate_network_request();
-        
-        if success {
-            println!("  第{}次尝试成功", attempt);
-            return;
+    match result {
+        Ok(n) => println!("  共尝试{}次", n),
+        Err(e) => println!("  所有重试均失败: {}", e),
+    }
+}
+
+// 重试间隔的增长方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Backoff {
+    // 每次等待相同时间
+    Fixed,
+    // 每次等待时间乘以factor
+    Exponential { factor: f64 },
+}
+
+// 指数退避默认的等待上限
+const DEFAULT_MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+// 重试策略
+#[derive(Debug, Clone)]
+struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    backoff: Backoff,
+    max_delay: Duration,
+    jitter: bool,
+}
+
+impl RetryPolicy {
+    // 默认固定间隔、无抖动；max_attempts包含第一次调用
+    fn new(max_attempts: u32, base_delay: Duration) -> Self {
+        RetryPolicy {
+            max_attempts,
+            base_delay,
+            backoff: Backoff::Fixed,
+            max_delay: DEFAULT_MAX_RETRY_DELAY,
+            jitter: false,
         }
+    }
+    
+    // factor小于1、NaN或无穷大时按1处理，即退化为固定间隔
+    fn exponential(mut self, factor: f64) -> Self {
+        let factor = if factor.is_finite() && factor >= 1.0 { factor } else { 1.0 };
+        self.backoff = Backoff::Exponential { factor };
+        self
+    }
+    
+    // 指数退避的等待时间不超过max_delay
+    fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+    
+    // 在 [delay/2, delay] 之间随机等待，避免多个客户端同时重试
+    fn with_jitter(mut self) -> Self {
+        self.jitter = true;
+        self
+    }
+    
+    // 第retry次重试之前的等待时间（从0开始）
+    fn delay_for(&self, retry: u32) -> Duration {
+        let delay = match self.backoff {
+            Backoff::Fixed => self.base_delay,
+            Backoff::Exponential { factor } => {
+                // 重试次数很大时结果超出Duration的表示范围，取上限
+                let scale = factor.powi(i32::try_from(retry).unwrap_or(i32::MAX));
+                Duration::try_from_secs_f64(scale * self.base_delay.as_secs_f64())
+                    .unwrap_or(self.max_delay)
+                    .min(self.max_delay)
+            }
+        };
         
-        let elapsed = start.elapsed();
-        if elapsed < timeout {
-            let wait_time = timeout - elapsed;
-            println!("  第{}次尝试失败，等待{:?}后重试", attempt, wait_time);
-            thread::sleep(wait_time);
+        if self.jitter {
+            delay / 2 + (delay / 2).mul_f64(pseudo_random_fraction(retry))
         } else {
-            println!("  第{}次尝试失败，已超时", attempt);
+            delay
         }
     }
+}
+
+// [0, 1) 之间的伪随机数，只用于抖动
+fn pseudo_random_fraction(seed: u32) -> f64 {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
     
-    println!("  所有重试均失败");
+    let mut hasher = DefaultHasher::new();
+    SystemTime::now().hash(&mut hasher);
+    seed.hash(&mut hasher);
+    (hasher.finish() % 10_000) as f64 / 10_000.0
+}
+
+// 按策略重试op，直到成功或用完次数，失败时返回最后一次的错误
+fn retry<T, E, F>(mut op: F, policy: RetryPolicy) -> Result<T, E>
+where
+    F: FnMut() -> Result<T, E>,
+{
+    let attempts = policy.max_attempts.max(1);
+    let mut retry_index = 0;
+    
+    loop {
+        match op() {
+            Ok(value) => return Ok(value),
+            Err(e) if retry_index + 1 >= attempts => return Err(e),
+            Err(_) => {
+                thread::sleep(policy.delay_for(retry_index));
+                retry_index += 1;
+            }
+        }
+    }
 }
 
 // 自适应超时示例
 fn adaptive_timeout_example() {
-    let mut adaptive_timeout = Duration::from_millis(100);
-    let mut response_times = Vec::new();
+    // 超时 = 最近5次的平均值 + 2倍标准差，限制在 [60ms, 1s]
+    let mut estimator = AdaptiveTimeout::new(5, 2.0, Duration::from_millis(60), Duration::from_secs(1));
+    println!("  初始超时: {:?}", estimator.current());
     
     for i in 1..=5 {
         let start = Instant::now();
@@ -559,18 +666,60 @@ fn adaptive_timeout_example() {
         let response_time = simulate_request_with_time();
         let actual_time = start.elapsed();
         
-        response_times.push(actual_time);
+        estimator.record(actual_time);
+        println!("  请求{}: 实际{:?}, 模拟{:?}, 新超时{:?}",
+                 i, actual_time, response_time, estimator.current());
         
-        // 调整超时时间
-        if response_times.len() >= 3 {
-            let avg_time: Duration = response_times.iter().sum::<Duration>() / response_times.len() as u32;
-            adaptive_timeout = avg_time * 2; // 设置为平均时间的2倍
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+// 自适应超时估计器：超时 = 均值 + k * 标准差，只统计最近window个样本
+struct AdaptiveTimeout {
+    samples: VecDeque<Duration>,
+    window: usize,
+    k: f64,
+    min: Duration,
+    max: Duration,
+}
+
+impl AdaptiveTimeout {
+    // k为负数、NaN或无穷大时按0处理；min大于max时交换两者
+    fn new(window: usize, k: f64, min: Duration, max: Duration) -> Self {
+        let k = if k.is_finite() && k > 0.0 { k } else { 0.0 };
+        let (min, max) = if min <= max { (min, max) } else { (max, min) };
+        AdaptiveTimeout {
+            samples: VecDeque::with_capacity(window),
+            window: window.max(1),
+            k,
+            min,
+            max,
+        }
+    }
+    
+    fn record(&mut self, latency: Duration) {
+        if self.samples.len() == self.window {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(latency);
+    }
+    
+    // 没有样本时保守地返回上限
+    fn current(&self) -> Duration {
+        if self.samples.is_empty() {
+            return self.max;
         }
         
-        println!("  请求{}: 实际{:?}, 模拟{:?}, 新超时{:?}", 
-                 i, actual_time, response_time, adaptive_timeout);
+        let n = self.samples.len() as f64;
+        let mean = self.samples.iter().map(|d| d.as_secs_f64()).sum::<f64>() / n;
+        let variance = self.samples
+            .iter()
+            .map(|d| (d.as_secs_f64() - mean).powi(2))
+            .sum::<f64>() / n;
         
-        thread::sleep(Duration::from_millis(10));
+        // 结果超出Duration的表示范围时取上限
+        let timeout = Duration::try_from_secs_f64(mean + self.k * variance.sqrt()).unwrap_or(self.max);
+        timeout.clamp(self.min, self.max)
     }
 }
 
@@ -653,29 +802,172 @@ fn humanize_duration(duration: Duration) -> String {
 
 // ISO 8601 格式示例
 fn iso8601_example() {
-    if let Ok(duration) = SystemTime::now().duration_since(UNIX_EPOCH) {
-        let timestamp = duration.as_secs();
-        
-        // 简化的ISO 8601格式（仅UTC，不处理时区）
-        let days_since_epoch = timestamp / 86400;
-        let seconds_today = timestamp % 86400;
-        
-        // 简化的年月日计算（不考虑闰年）
-        let years = days_since_epoch / 365;
-        let remaining_days = days_since_epoch % 365;
-        let months = remaining_days / 30;
-        let days = remaining_days % 30;
-        
-        let hours = seconds_today / 3600;
-        let minutes = (seconds_today % 3600) / 60;
-        let seconds = seconds_today % 60;
-        
-        let iso_date = format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
-                              1970 + years, months + 1, days + 1,
-                              hours, minutes, seconds);
-        
-        println!("  简化ISO 8601: {}", iso_date);
+    println!("  当前时间: {}", format_iso8601(SystemTime::now()));
+    println!("  Unix纪元: {}", format_iso8601(UNIX_EPOCH));
+    println!("  闰日: {}", format_iso8601(UNIX_EPOCH + Duration::from_secs(1_582_979_696)));
+    
+    // 解析回时间戳
+    for text in ["2021-01-01T00:00:00Z", "2021-02-29T00:00:00Z", "2021-01-01T08:00:00+08:00"] {
+        match parse_iso8601(text) {
+            Ok(t) => println!("  解析 {}: 时间戳 {:?}", text, t.duration_since(UNIX_EPOCH).map(|d| d.as_secs())),
+            Err(e) => println!("  解析 {} 失败: {}", text, e),
+        }
+    }
+}
+
+// ISO 8601 解析错误
+#[derive(Debug, PartialEq)]
+enum ParseError {
+    // 不符合 YYYY-MM-DDTHH:MM:SSZ 格式
+    InvalidFormat(String),
+    // 字段超出范围，例如13月或平年的2月29日
+    OutOfRange { field: &'static str, value: u32 },
+    // 暂不支持UTC以外的时区偏移
+    UnsupportedOffset(String),
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::InvalidFormat(s) => write!(f, "格式错误，应为YYYY-MM-DDTHH:MM:SSZ: {}", s),
+            ParseError::OutOfRange { field, value } => write!(f, "{} 超出范围: {}", field, value),
+            ParseError::UnsupportedOffset(offset) => write!(f, "只支持UTC(Z)，不支持时区偏移: {}", offset),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// civil_from_days 的逆运算：公历日期转换为Unix纪元以来的天数
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let year_of_era = y.rem_euclid(400);
+    let mp = (if month > 2 { month - 3 } else { month + 9 }) as i64;
+    let day_of_year = (153 * mp + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+// 解析UTC的 YYYY-MM-DDTHH:MM:SSZ
+fn parse_iso8601(s: &str) -> Result<SystemTime, ParseError> {
+    let invalid = || ParseError::InvalidFormat(s.to_string());
+    
+    // 日期时间部分固定19个ASCII字符
+    let (datetime, suffix) = match (s.get(..19), s.get(19..)) {
+        (Some(datetime), Some(suffix)) => (datetime, suffix),
+        _ => return Err(invalid()),
+    };
+    match suffix {
+        "Z" => {}
+        _ if suffix.starts_with('+') || suffix.starts_with('-') => {
+            return Err(ParseError::UnsupportedOffset(suffix.to_string()));
+        }
+        _ => return Err(invalid()),
+    }
+    
+    let bytes = datetime.as_bytes();
+    for (i, &b) in bytes.iter().enumerate() {
+        let ok = match i {
+            4 | 7 => b == b'-',
+            10 => b == b'T',
+            13 | 16 => b == b':',
+            _ => b.is_ascii_digit(),
+        };
+        if !ok {
+            return Err(invalid());
+        }
+    }
+    
+    // 上面已经确认都是数字
+    let field = |range: std::ops::Range<usize>| datetime[range].parse::<u32>().unwrap();
+    let year = field(0..4) as i64;
+    let month = field(5..7);
+    let day = field(8..10);
+    let hour = field(11..13);
+    let minute = field(14..16);
+    let second = field(17..19);
+    
+    if !(1..=12).contains(&month) {
+        return Err(ParseError::OutOfRange { field: "月", value: month });
+    }
+    if day == 0 || day > days_in_month(year, month) {
+        return Err(ParseError::OutOfRange { field: "日", value: day });
     }
+    if hour > 23 {
+        return Err(ParseError::OutOfRange { field: "时", value: hour });
+    }
+    if minute > 59 {
+        return Err(ParseError::OutOfRange { field: "分", value: minute });
+    }
+    if second > 59 {
+        return Err(ParseError::OutOfRange { field: "秒", value: second });
+    }
+    
+    let secs = days_from_civil(year, month, day) * 86_400
+        + (hour * 3600 + minute * 60 + second) as i64;
+    
+    Ok(if secs >= 0 {
+        UNIX_EPOCH + Duration::from_secs(secs as u64)
+    } else {
+        UNIX_EPOCH - Duration::from_secs(secs.unsigned_abs())
+    })
+}
+
+// 把Unix纪元以来的天数转换为公历(年, 月, 日)，适用于任意年份（预期格里高利历）
+// 算法来自 Howard Hinnant 的 civil_from_days
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    // 以0000-03-01为起点，这样闰日正好落在每个400年周期中每年的最后一天
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    // 从三月开始计数的月份 [0, 11]
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+// 格式化为UTC的 YYYY-MM-DDTHH:MM:SSZ，纪元之前的时间同样正确，秒以下的部分被舍去
+fn format_iso8601(t: SystemTime) -> String {
+    let secs = match t.duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_secs() as i64,
+        Err(e) => {
+            // 向下取整到整秒
+            let d = e.duration();
+            -(d.as_secs() as i64) - if d.subsec_nanos() > 0 { 1 } else { 0 }
+        }
+    };
+    
+    let days = secs.div_euclid(86_400);
+    let seconds_of_day = secs.rem_euclid(86_400);
+    let (year, month, day) = civil_from_days(days);
+    
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        seconds_of_day / 3600,
+        (seconds_of_day % 3600) / 60,
+        seconds_of_day % 60
+    )
 }
 
 // 简单性能分析器
@@ -717,31 +1009,90 @@ impl SimpleProfiler {
 
 // CPU使用率监控（简化版）
 fn cpu_usage_monitor() {
+    let mut sampler = match CpuSampler::new() {
+        Ok(sampler) => sampler,
+        Err(e) => {
+            println!("  无法读取CPU时间: {}", e);
+            return;
+        }
+    };
+    
+    // CPU密集型工作
     let start = Instant::now();
-    let start_time = SystemTime::now();
+    let mut acc = 0u64;
+    while start.elapsed() < Duration::from_millis(200) {
+        acc = std::hint::black_box(acc.wrapping_mul(31).wrapping_add(7));
+    }
+    println!("  忙碌阶段CPU使用率: {:.1}%", sampler.sample());
     
-    // 模拟CPU密集型工作
-    let mut work_time = Duration::ZERO;
-    let monitor_duration = Duration::from_millis(200);
+    // 空闲
+    thread::sleep(Duration::from_millis(200));
+    println!("  空闲阶段CPU使用率: {:.1}%", sampler.sample());
+}
+
+// 进程CPU使用率采样器：两次采样之间 CPU时间 / 墙上时间 * 100
+// 多线程进程的结果可能超过100%
+struct CpuSampler {
+    last_wall: Instant,
+    last_cpu: Duration,
+}
+
+impl CpuSampler {
+    // 不支持的平台返回 Unsupported 错误
+    fn new() -> std::io::Result<Self> {
+        Ok(CpuSampler {
+            last_cpu: process_cpu_time()?,
+            last_wall: Instant::now(),
+        })
+    }
     
-    while start.elapsed() < monitor_duration {
-        let work_start = Instant::now();
-        
-        // 模拟工作
-        for _ in 0..10000 {
-            std::hint::black_box(std::ptr::null::<i32>());
-        }
+    // 返回自上次采样以来的CPU使用率
+    fn sample(&mut self) -> f64 {
+        // 创建时已经成功读取过，读取失败时按没有新增CPU时间处理
+        let cpu = process_cpu_time().unwrap_or(self.last_cpu);
+        let wall = Instant::now();
         
-        work_time += work_start.elapsed();
+        let cpu_delta = cpu.saturating_sub(self.last_cpu);
+        let wall_delta = wall.duration_since(self.last_wall);
+        self.last_cpu = cpu;
+        self.last_wall = wall;
         
-        // 模拟空闲
-        thread::sleep(Duration::from_millis(1));
+        if wall_delta.is_zero() {
+            return 0.0;
+        }
+        cpu_delta.as_secs_f64() / wall_delta.as_secs_f64() * 100.0
     }
-    
-    let total_time = start.elapsed();
-    let cpu_usage = (work_time.as_nanos() as f64 / total_time.as_nanos() as f64) * 100.0;
-    
-    println!("  模拟CPU使用率: {:.1}%", cpu_usage);
+}
+
+// 从 /proc/self/stat 读取进程的用户态+内核态CPU时间
+#[cfg(target_os = "linux")]
+fn process_cpu_time() -> std::io::Result<Duration> {
+    use std::io::{Error, ErrorKind};
+    
+    // /proc 中的时间以 USER_HZ 为单位，Linux 的用户态接口固定为100
+    const TICKS_PER_SECOND: u64 = 100;
+    
+    let stat = std::fs::read_to_string("/proc/self/stat")?;
+    let invalid = || Error::new(ErrorKind::InvalidData, "无法解析 /proc/self/stat");
+    
+    // 进程名可能包含空格和括号，从最后一个 ')' 之后开始按空格拆分，第一个字段是第3项
+    let rest = &stat[stat.rfind(')').ok_or_else(invalid)? + 1..];
+    let fields: Vec<&str> = rest.split_whitespace().collect();
+    let field = |index: usize| -> std::io::Result<u64> {
+        fields.get(index).and_then(|f| f.parse().ok()).ok_or_else(invalid)
+    };
+    
+    // 第14项utime、第15项stime
+    let ticks = field(11)? + field(12)?;
+    Ok(Duration::from_millis(ticks * 1000 / TICKS_PER_SECOND))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn process_cpu_time() -> std::io::Result<Duration> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "当前平台不支持读取进程CPU时间",
+    ))
 }
 
 // 内存使用监控（概念演示）
@@ -770,23 +1121,253 @@ fn memory_usage_monitor() {
 
 // 日志记录器示例
 fn logger_example() {
-    struct SimpleLogger;
+    let log_path = std::env::temp_dir().join(format!("time_logger_{}.log", std::process::id()));
+    let mut logger = Logger::new("app", Level::Info).with_sink(StdoutSink);
+    match FileSink::new(&log_path) {
+        Ok(sink) => logger = logger.with_sink(sink),
+        Err(e) => println!("  无法打开日志文件: {}", e),
+    }
+    
+    logger.debug("低于最低级别，不会输出");
+    logger.info("应用程序启动");
+    thread::sleep(Duration::from_millis(10));
+    logger.warn("配置文件缺失，使用默认值");
+    thread::sleep(Duration::from_millis(10));
+    logger.error("数据库连接失败");
+    
+    // 释放文件句柄，缓冲区中的内容写入文件
+    drop(logger);
+    if let Ok(content) = std::fs::read_to_string(&log_path) {
+        println!("  日志文件共 {} 行", content.lines().count());
+    }
+    let _ = std::fs::remove_file(&log_path);
+    
+    // 按大小轮转：每个文件最多约100字节，保留2个旧文件
+    let rotating_path = std::env::temp_dir().join(format!("time_rotating_{}.log", std::process::id()));
+    if let Ok(sink) = FileSink::with_rotation(&rotating_path, 100, 2) {
+        let logger = Logger::new("rotate", Level::Info).with_sink(sink);
+        for i in 0..5 {
+            logger.info(&format!("第{}条记录", i));
+        }
+    }
+    for suffix in ["", ".1", ".2"] {
+        let mut name = rotating_path.clone().into_os_string();
+        name.push(suffix);
+        if let Ok(content) = std::fs::read_to_string(&name) {
+            println!("  {:?}: {} 行", std::path::Path::new(&name).file_name().unwrap(), content.lines().count());
+            let _ = std::fs::remove_file(&name);
+        }
+    }
+}
+
+// 日志级别，按严重程度排序
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Level {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl std::fmt::Display for Level {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Level::Debug => "DEBUG",
+            Level::Info => "INFO",
+            Level::Warn => "WARN",
+            Level::Error => "ERROR",
+        };
+        f.write_str(name)
+    }
+}
+
+// 一条日志记录
+struct LogRecord {
+    timestamp: SystemTime,
+    level: Level,
+    target: String,
+    message: String,
+}
+
+impl std::fmt::Display for LogRecord {
+    // 格式：2021-01-01T00:00:00Z [INFO] app: 消息
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} [{}] {}: {}", format_iso8601(self.timestamp), self.level, self.target, self.message)
+    }
+}
+
+// 日志输出目标
+trait Sink: Send + Sync {
+    fn write(&self, record: &LogRecord);
+}
+
+// 输出到标准输出
+struct StdoutSink;
+
+impl Sink for StdoutSink {
+    fn write(&self, record: &LogRecord) {
+        println!("  {}", record);
+    }
+}
+
+// 以追加模式写入文件，带缓冲，Drop时刷新
+struct FileSink {
+    state: Mutex<FileSinkState>,
+}
+
+struct FileSinkState {
+    path: std::path::PathBuf,
+    writer: BufWriter<File>,
+    // 当前文件的字节数，避免每次写入都查询metadata
+    written: u64,
+    rotation: Option<Rotation>,
+}
+
+// 按大小轮转：app.log 超过 max_bytes 时依次改名为 app.log.1、app.log.2 ...
+struct Rotation {
+    max_bytes: u64,
+    max_files: usize,
+}
+
+impl FileSink {
+    fn new(path: impl AsRef<std::path::Path>) -> io::Result<Self> {
+        Self::open(path.as_ref(), None)
+    }
+    
+    // 最多保留 max_files 个轮转后的旧文件，更早的被删除
+    fn with_rotation(path: impl AsRef<std::path::Path>, max_bytes: u64, max_files: usize) -> io::Result<Self> {
+        Self::open(path.as_ref(), Some(Rotation { max_bytes, max_files }))
+    }
     
-    impl SimpleLogger {
-        fn log(&self, level: &str, message: &str) {
-            if let Ok(duration) = SystemTime::now().duration_since(UNIX_EPOCH) {
-                let timestamp = duration.as_secs();
-                println!("  [{}] {} - {}", timestamp, level, message);
+    fn open(path: &std::path::Path, rotation: Option<Rotation>) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let written = file.metadata()?.len();
+        Ok(FileSink {
+            state: Mutex::new(FileSinkState {
+                path: path.to_path_buf(),
+                writer: BufWriter::new(file),
+                written,
+                rotation,
+            }),
+        })
+    }
+}
+
+impl FileSinkState {
+    // 第index个旧文件的路径，例如 app.log.1
+    fn rotated_path(&self, index: usize) -> std::path::PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", index));
+        name.into()
+    }
+    
+    fn rotate(&mut self, max_files: usize) -> io::Result<()> {
+        self.writer.flush()?;
+        
+        if max_files > 0 {
+            // 删除最旧的文件，其余的编号依次加一
+            let oldest = self.rotated_path(max_files);
+            if oldest.exists() {
+                std::fs::remove_file(&oldest)?;
+            }
+            for index in (1..max_files).rev() {
+                let from = self.rotated_path(index);
+                if from.exists() {
+                    std::fs::rename(&from, self.rotated_path(index + 1))?;
+                }
             }
+            std::fs::rename(&self.path, self.rotated_path(1))?;
         }
+        
+        let file = OpenOptions::new().create(true).write(true).truncate(true).open(&self.path)?;
+        self.writer = BufWriter::new(file);
+        self.written = 0;
+        Ok(())
     }
     
-    let logger = SimpleLogger;
-    logger.log("INFO", "应用程序启动");
-    thread::sleep(Duration::from_millis(10));
-    logger.log("WARN", "配置文件缺失，使用默认值");
-    thread::sleep(Duration::from_millis(10));
-    logger.log("ERROR", "数据库连接失败");
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        let len = line.len() as u64;
+        let max_files = match &self.rotation {
+            // 空文件不轮转，避免单条超长记录导致不断产生空文件
+            Some(rotation) if self.written > 0 && self.written + len > rotation.max_bytes => {
+                Some(rotation.max_files)
+            }
+            _ => None,
+        };
+        if let Some(max_files) = max_files {
+            self.rotate(max_files)?;
+        }
+        
+        self.writer.write_all(line.as_bytes())?;
+        self.written += len;
+        Ok(())
+    }
+}
+
+impl Sink for FileSink {
+    fn write(&self, record: &LogRecord) {
+        // 日志写入失败不应该影响业务逻辑
+        let _ = self.state.lock().unwrap().write_line(&format!("{}\n", record));
+    }
+}
+
+// 带最低级别过滤的日志器，一条记录会发送到所有sink
+struct Logger {
+    target: String,
+    min_level: Level,
+    sinks: Vec<Box<dyn Sink>>,
+}
+
+impl Logger {
+    fn new(target: &str, min_level: Level) -> Self {
+        Logger {
+            target: target.to_string(),
+            min_level,
+            sinks: Vec::new(),
+        }
+    }
+    
+    fn with_sink(mut self, sink: impl Sink + 'static) -> Self {
+        self.sinks.push(Box::new(sink));
+        self
+    }
+    
+    fn enabled(&self, level: Level) -> bool {
+        level >= self.min_level
+    }
+    
+    fn log(&self, level: Level, message: &str) {
+        // 先比较级别，被过滤的记录不会分配内存
+        if !self.enabled(level) {
+            return;
+        }
+        
+        let record = LogRecord {
+            timestamp: SystemTime::now(),
+            level,
+            target: self.target.clone(),
+            message: message.to_string(),
+        };
+        for sink in &self.sinks {
+            sink.write(&record);
+        }
+    }
+    
+    fn debug(&self, message: &str) {
+        self.log(Level::Debug, message);
+    }
+    
+    fn info(&self, message: &str) {
+        self.log(Level::Info, message);
+    }
+    
+    fn warn(&self, message: &str) {
+        self.log(Level::Warn, message);
+    }
+    
+    fn error(&self, message: &str) {
+        self.log(Level::Error, message);
+    }
 }
 
 // 缓存过期管理
@@ -892,50 +1473,95 @@ fn rate_limiter_example() {
 
 // 性能监控示例
 fn performance_monitor_example() {
-    struct PerformanceMonitor {
-        metrics: HashMap<String, Vec<Duration>>,
-    }
+    let monitor = PerformanceMonitor::new();
     
-    impl PerformanceMonitor {
-        fn new() -> Self {
-            PerformanceMonitor {
-                metrics: HashMap::new(),
-            }
-        }
-        
-        fn record(&mut self, operation: &str, duration: Duration) {
-            self.metrics.entry(operation.to_string())
-                .or_insert_with(Vec::new)
-                .push(duration);
-        }
-        
-        fn report(&self) {
-            for (operation, durations) in &self.metrics {
-                if !durations.is_empty() {
-                    let total: Duration = durations.iter().sum();
-                    let avg = total / durations.len() as u32;
-                    let min = *durations.iter().min().unwrap();
-                    let max = *durations.iter().max().unwrap();
-                    
-                    println!("  {}: 平均{:?}, 最小{:?}, 最大{:?} ({} 次)",
-                             operation, avg, min, max, durations.len());
-                }
-            }
-        }
-    }
-    
-    let mut monitor = PerformanceMonitor::new();
-    
-    // 记录一些操作
+    // 手动记录一些操作
     for i in 0..5 {
         let start = Instant::now();
         thread::sleep(Duration::from_millis(10 + i * 5));
         monitor.record("task", start.elapsed());
     }
     
+    // 作用域计时：守卫离开作用域时自动记录，监控器可以克隆到其他线程
+    let handles: Vec<_> = (0..3)
+        .map(|i| {
+            let monitor = monitor.clone();
+            thread::spawn(move || {
+                let _t = monitor.scope("worker");
+                thread::sleep(Duration::from_millis(5 * (i + 1)));
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    println!("  worker 采样次数: {}", monitor.sample_count("worker"));
+    
     monitor.report();
 }
 
+// 线程安全的性能监控器，克隆只复制Arc
+#[derive(Clone)]
+struct PerformanceMonitor {
+    metrics: Arc<Mutex<HashMap<String, Vec<Duration>>>>,
+}
+
+impl PerformanceMonitor {
+    fn new() -> Self {
+        PerformanceMonitor {
+            metrics: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+    
+    fn record(&self, operation: &str, duration: Duration) {
+        self.metrics.lock().unwrap()
+            .entry(operation.to_string())
+            .or_default()
+            .push(duration);
+    }
+    
+    // 返回一个计时守卫，在Drop时记录存活时长
+    fn scope(&self, operation: &str) -> ScopedTimer {
+        ScopedTimer {
+            monitor: self.clone(),
+            operation: operation.to_string(),
+            start: Instant::now(),
+        }
+    }
+    
+    fn sample_count(&self, operation: &str) -> usize {
+        self.metrics.lock().unwrap().get(operation).map_or(0, |durations| durations.len())
+    }
+    
+    fn report(&self) {
+        let metrics = self.metrics.lock().unwrap();
+        for (operation, durations) in metrics.iter() {
+            if !durations.is_empty() {
+                let total: Duration = durations.iter().sum();
+                let avg = total / durations.len() as u32;
+                let min = *durations.iter().min().unwrap();
+                let max = *durations.iter().max().unwrap();
+                
+                println!("  {}: 平均{:?}, 最小{:?}, 最大{:?} ({} 次)",
+                         operation, avg, min, max, durations.len());
+            }
+        }
+    }
+}
+
+// 作用域计时守卫
+struct ScopedTimer {
+    monitor: PerformanceMonitor,
+    operation: String,
+    start: Instant,
+}
+
+impl Drop for ScopedTimer {
+    fn drop(&mut self) {
+        self.monitor.record(&self.operation, self.start.elapsed());
+    }
+}
+
 // 常见陷阱
 fn common_pitfalls() {
     println!("1. 使用SystemTime测量时间间隔可能受系统时间调整影响");
@@ -1022,6 +1648,348 @@ mod tests {
         assert_eq!(formatted, "1小时1分");
     }
     
+    #[test]
+    fn test_format_iso8601() {
+        let at = |secs: u64| UNIX_EPOCH + Duration::from_secs(secs);
+        
+        assert_eq!(format_iso8601(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        assert_eq!(format_iso8601(at(1_609_459_200)), "2021-01-01T00:00:00Z");
+        
+        // 闰日：2020年是闰年，2000年能被400整除也是闰年，2100年不是
+        assert_eq!(format_iso8601(at(1_582_979_696)), "2020-02-29T12:34:56Z");
+        assert_eq!(format_iso8601(at(951_782_400)), "2000-02-29T00:00:00Z");
+        assert_eq!(format_iso8601(at(4_107_542_399)), "2100-02-28T23:59:59Z");
+        
+        // 纪元之前
+        assert_eq!(format_iso8601(UNIX_EPOCH - Duration::from_secs(1)), "1969-12-31T23:59:59Z");
+        assert_eq!(format_iso8601(UNIX_EPOCH - Duration::from_millis(500)), "1969-12-31T23:59:59Z");
+    }
+    
+    #[test]
+    fn test_parse_iso8601_round_trip() {
+        let dates = [
+            "1970-01-01T00:00:00Z",
+            "1969-12-31T23:59:59Z",
+            "2000-02-29T00:00:00Z",
+            "2020-02-29T12:34:56Z",
+            "2021-01-01T00:00:00Z",
+            "2038-01-19T03:14:08Z",
+            "2100-12-31T23:59:59Z",
+        ];
+        
+        for date in dates {
+            let t = parse_iso8601(date).unwrap();
+            assert_eq!(format_iso8601(t), date);
+        }
+        
+        let t = parse_iso8601("2021-01-01T00:00:00Z").unwrap();
+        assert_eq!(t.duration_since(UNIX_EPOCH).unwrap().as_secs(), 1_609_459_200);
+    }
+    
+    #[test]
+    fn test_parse_iso8601_rejects_invalid() {
+        // 2021年不是闰年
+        assert_eq!(
+            parse_iso8601("2021-02-29T00:00:00Z"),
+            Err(ParseError::OutOfRange { field: "日", value: 29 })
+        );
+        assert_eq!(
+            parse_iso8601("2020-13-01T00:00:00Z"),
+            Err(ParseError::OutOfRange { field: "月", value: 13 })
+        );
+        assert_eq!(
+            parse_iso8601("2021-01-01T08:00:00+08:00"),
+            Err(ParseError::UnsupportedOffset("+08:00".to_string()))
+        );
+        assert!(matches!(parse_iso8601("2021-01-01"), Err(ParseError::InvalidFormat(_))));
+        assert!(matches!(parse_iso8601("2021/01/01T00:00:00Z"), Err(ParseError::InvalidFormat(_))));
+        assert!(matches!(parse_iso8601("2021-01-01T24:00:00Z"), Err(ParseError::OutOfRange { .. })));
+    }
+    
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_cpu_sampler_busy_loop() {
+        let mut sampler = CpuSampler::new().unwrap();
+        
+        let start = Instant::now();
+        let mut acc = 0u64;
+        while start.elapsed() < Duration::from_millis(300) {
+            acc = std::hint::black_box(acc.wrapping_mul(31).wrapping_add(7));
+        }
+        
+        // 忙循环期间至少占用一部分CPU；时钟粒度为10毫秒，阈值留足余量
+        let usage = sampler.sample();
+        assert!(usage > 30.0, "CPU使用率过低: {:.1}%", usage);
+    }
+    
+    #[test]
+    fn test_scoped_timer_across_threads() {
+        let monitor = PerformanceMonitor::new();
+        
+        let handles: Vec<_> = (0..3)
+            .map(|_| {
+                let monitor = monitor.clone();
+                thread::spawn(move || {
+                    let _t = monitor.scope("sleep");
+                    thread::sleep(Duration::from_millis(10));
+                })
+            })
+            .collect();
+        
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        
+        assert_eq!(monitor.sample_count("sleep"), 3);
+        assert_eq!(monitor.sample_count("other"), 0);
+        
+        let metrics = monitor.metrics.lock().unwrap();
+        assert!(metrics["sleep"].iter().all(|d| *d >= Duration::from_millis(10)));
+    }
+    
+    #[test]
+    fn test_retry_succeeds_on_third_attempt() {
+        let calls = std::cell::Cell::new(0);
+        let policy = RetryPolicy::new(5, Duration::from_millis(1));
+        
+        let result: Result<&str, String> = retry(
+            || {
+                calls.set(calls.get() + 1);
+                if calls.get() < 3 {
+                    Err(format!("失败{}", calls.get()))
+                } else {
+                    Ok("成功")
+                }
+            },
+            policy,
+        );
+        
+        assert_eq!(result, Ok("成功"));
+        assert_eq!(calls.get(), 3);
+    }
+    
+    #[test]
+    fn test_retry_gives_up_with_last_error() {
+        let calls = std::cell::Cell::new(0);
+        let policy = RetryPolicy::new(4, Duration::from_millis(1)).exponential(2.0);
+        
+        let start = Instant::now();
+        let result: Result<(), u32> = retry(
+            || {
+                calls.set(calls.get() + 1);
+                Err(calls.get())
+            },
+            policy,
+        );
+        
+        assert_eq!(result, Err(4));
+        assert_eq!(calls.get(), 4);
+        // 三次等待：1 + 2 + 4 毫秒
+        assert!(start.elapsed() >= Duration::from_millis(7));
+    }
+    
+    #[test]
+    fn test_retry_policy_delays() {
+        let fixed = RetryPolicy::new(3, Duration::from_millis(10));
+        assert_eq!(fixed.delay_for(0), Duration::from_millis(10));
+        assert_eq!(fixed.delay_for(2), Duration::from_millis(10));
+        
+        let exp = RetryPolicy::new(3, Duration::from_millis(10)).exponential(3.0);
+        assert_eq!(exp.delay_for(2), Duration::from_millis(90));
+        
+        let jittered = exp.with_jitter();
+        for retry in 0..3 {
+            let delay = jittered.delay_for(retry);
+            let full = Duration::from_millis(10).mul_f64(3f64.powi(retry as i32));
+            assert!(delay >= full / 2 && delay <= full);
+        }
+    }
+    
+    #[test]
+    fn test_retry_policy_large_retry_and_bad_factor() {
+        let ms = Duration::from_millis;
+        
+        // 10^30倍超出Duration范围，不panic而是取上限
+        let exp = RetryPolicy::new(3, ms(10)).exponential(10.0);
+        assert_eq!(exp.delay_for(30), DEFAULT_MAX_RETRY_DELAY);
+        assert_eq!(exp.delay_for(u32::MAX), DEFAULT_MAX_RETRY_DELAY);
+        
+        let capped = exp.with_max_delay(ms(500));
+        assert_eq!(capped.delay_for(1), ms(100));
+        assert_eq!(capped.delay_for(2), ms(500));
+        
+        // 非法的factor退化为固定间隔
+        for factor in [-2.0, 0.5, f64::NAN, f64::INFINITY] {
+            let policy = RetryPolicy::new(3, ms(10)).exponential(factor);
+            assert_eq!(policy.delay_for(0), ms(10));
+            assert_eq!(policy.delay_for(5), ms(10));
+        }
+    }
+    
+    #[test]
+    fn test_adaptive_timeout() {
+        let ms = Duration::from_millis;
+        let mut estimator = AdaptiveTimeout::new(4, 2.0, ms(10), ms(500));
+        assert_eq!(estimator.current(), ms(500));
+        
+        // 稳定的延迟：标准差为0，超时等于均值
+        for _ in 0..4 {
+            estimator.record(ms(50));
+        }
+        assert_eq!(estimator.current(), ms(50));
+        
+        // 出现尖峰后超时变宽
+        estimator.record(ms(250));
+        let widened = estimator.current();
+        assert!(widened > ms(200), "超时没有变宽: {:?}", widened);
+        
+        // 窗口滑过尖峰后恢复
+        for _ in 0..4 {
+            estimator.record(ms(50));
+        }
+        assert_eq!(estimator.current(), ms(50));
+    }
+    
+    #[test]
+    fn test_adaptive_timeout_clamp() {
+        let ms = Duration::from_millis;
+        let mut estimator = AdaptiveTimeout::new(3, 4.0, ms(100), ms(300));
+        
+        estimator.record(ms(1));
+        assert_eq!(estimator.current(), ms(100));
+        
+        estimator.record(ms(200));
+        estimator.record(ms(400));
+        assert_eq!(estimator.current(), ms(300));
+    }
+    
+    #[test]
+    fn test_adaptive_timeout_invalid_params() {
+        let ms = Duration::from_millis;
+        
+        // min和max写反时不会在clamp中panic
+        let mut estimator = AdaptiveTimeout::new(3, 2.0, ms(300), ms(100));
+        estimator.record(ms(200));
+        assert_eq!(estimator.current(), ms(200));
+        
+        // 负数和非有限的k按0处理，超时等于均值
+        for k in [-5.0, f64::NAN, f64::INFINITY] {
+            let mut estimator = AdaptiveTimeout::new(2, k, ms(10), ms(500));
+            estimator.record(ms(20));
+            estimator.record(ms(40));
+            assert_eq!(estimator.current(), ms(30));
+        }
+        
+        // k极大时结果超出Duration范围，取上限
+        let mut estimator = AdaptiveTimeout::new(2, f64::MAX, ms(10), ms(500));
+        estimator.record(ms(20));
+        estimator.record(ms(40));
+        assert_eq!(estimator.current(), ms(500));
+    }
+    
+    // 把日志记录保存在内存中的sink
+    struct MemorySink {
+        lines: Arc<Mutex<Vec<String>>>,
+    }
+    
+    impl Sink for MemorySink {
+        fn write(&self, record: &LogRecord) {
+            self.lines.lock().unwrap().push(record.to_string());
+        }
+    }
+    
+    #[test]
+    fn test_logger_filters_and_formats() {
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let logger = Logger::new("db", Level::Warn).with_sink(MemorySink { lines: Arc::clone(&lines) });
+        
+        logger.debug("调试");
+        logger.info("信息");
+        logger.warn("连接变慢");
+        logger.error("连接断开");
+        
+        let lines = lines.lock().unwrap();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with(" [WARN] db: 连接变慢"));
+        assert!(lines[1].ends_with(" [ERROR] db: 连接断开"));
+        
+        // 时间戳部分是ISO 8601格式
+        let timestamp = lines[0].split(' ').next().unwrap();
+        assert!(parse_iso8601(timestamp).is_ok());
+        
+        let record = LogRecord {
+            timestamp: UNIX_EPOCH + Duration::from_secs(1_609_459_200),
+            level: Level::Info,
+            target: "app".to_string(),
+            message: "启动".to_string(),
+        };
+        assert_eq!(record.to_string(), "2021-01-01T00:00:00Z [INFO] app: 启动");
+    }
+    
+    #[test]
+    fn test_file_sink_appends() {
+        let path = std::env::temp_dir().join(format!("test_file_sink_{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        
+        for message in ["第一条", "第二条"] {
+            // 每次重新打开，验证是追加而不是覆盖
+            let sink = FileSink::new(&path).unwrap();
+            Logger::new("t", Level::Debug).with_sink(sink).info(message);
+        }
+        
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with("t: 第一条"));
+        assert!(lines[1].ends_with("t: 第二条"));
+        
+        let _ = std::fs::remove_file(&path);
+    }
+    
+    #[test]
+    fn test_file_sink_rotation() {
+        let dir = std::env::temp_dir().join(format!("test_rotation_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("app.log");
+        
+        // 每条记录约72字节，每个文件最多放两条
+        let message = "x".repeat(40);
+        let logger = Logger::new("t", Level::Info)
+            .with_sink(FileSink::with_rotation(&path, 200, 2).unwrap());
+        
+        for _ in 0..6 {
+            logger.info(&message);
+        }
+        drop(logger);
+        
+        // 6条记录触发两次轮转
+        let names = |dir: &std::path::Path| {
+            let mut names: Vec<String> = std::fs::read_dir(dir)
+                .unwrap()
+                .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+                .collect();
+            names.sort();
+            names
+        };
+        assert_eq!(names(&dir), vec!["app.log", "app.log.1", "app.log.2"]);
+        for name in names(&dir) {
+            let content = std::fs::read_to_string(dir.join(&name)).unwrap();
+            assert_eq!(content.lines().count(), 2, "{} 的内容不对", name);
+        }
+        
+        // 重新打开后继续写入，最旧的文件被删除，不会出现 app.log.3
+        let logger = Logger::new("t", Level::Info)
+            .with_sink(FileSink::with_rotation(&path, 200, 2).unwrap());
+        logger.info("新的记录");
+        drop(logger);
+        assert_eq!(names(&dir), vec!["app.log", "app.log.1", "app.log.2"]);
+        assert!(std::fs::read_to_string(&path).unwrap().contains("新的记录"));
+        assert!(std::fs::metadata(dir.join("app.log.1")).unwrap().len() > 0);
+        
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+    
     #[test]
     fn test_profiler() {
         let mut profiler = SimpleProfiler::new();
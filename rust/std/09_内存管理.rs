@@ -34,6 +34,7 @@ RAII原则：
 
 use std::rc::{Rc, Weak};
 use std::sync::{Arc, Mutex, RwLock};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::cell::{RefCell, Cell};
 use std::collections::HashMap;
 use std::mem;
@@ -612,6 +613,84 @@ fn weak_reference_demo() {
     if weak_ref.upgrade().is_none() {
         println!("强引用已销毁，弱引用无法升级");
     }
+    
+    // 观察者模式：主题只持有观察者的弱引用
+    let subject = Subject::new();
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let first: Rc<dyn Observer> = Rc::new(LoggingObserver::new("观察者1", &log));
+    let second: Rc<dyn Observer> = Rc::new(LoggingObserver::new("观察者2", &log));
+    subject.subscribe(&first);
+    subject.subscribe(&second);
+    
+    // 观察者被销毁后不会被主题保持存活
+    drop(second);
+    let notified = subject.notify("数据已更新");
+    println!("通知了 {} 个观察者，剩余 {} 个: {:?}", notified, subject.observer_count(), log.borrow());
+}
+
+// 观察者
+trait Observer {
+    fn on_notify(&self, event: &str);
+}
+
+// 主题：只保存弱引用，避免观察者模式中常见的内存泄漏
+struct Subject {
+    observers: RefCell<Vec<Weak<dyn Observer>>>,
+}
+
+impl Subject {
+    fn new() -> Self {
+        Subject {
+            observers: RefCell::new(Vec::new()),
+        }
+    }
+    
+    fn subscribe(&self, observer: &Rc<dyn Observer>) {
+        self.observers.borrow_mut().push(Rc::downgrade(observer));
+    }
+    
+    // 通知所有存活的观察者并移除已销毁的，返回通知的数量
+    fn notify(&self, event: &str) -> usize {
+        let mut live = Vec::new();
+        self.observers.borrow_mut().retain(|weak| match weak.upgrade() {
+            Some(observer) => {
+                live.push(observer);
+                true
+            }
+            None => false,
+        });
+        
+        // 释放借用后再回调，观察者可以在回调中订阅新的观察者
+        for observer in &live {
+            observer.on_notify(event);
+        }
+        live.len()
+    }
+    
+    fn observer_count(&self) -> usize {
+        self.observers.borrow().len()
+    }
+}
+
+// 把收到的事件记录到共享日志中的观察者
+struct LoggingObserver {
+    name: String,
+    log: Rc<RefCell<Vec<String>>>,
+}
+
+impl LoggingObserver {
+    fn new(name: &str, log: &Rc<RefCell<Vec<String>>>) -> Self {
+        LoggingObserver {
+            name: name.to_string(),
+            log: Rc::clone(log),
+        }
+    }
+}
+
+impl Observer for LoggingObserver {
+    fn on_notify(&self, event: &str) {
+        self.log.borrow_mut().push(format!("{}: {}", self.name, event));
+    }
 }
 
 // Arc演示
@@ -909,6 +988,57 @@ fn memory_leak_detection_tools() {
     println!("4. cargo-leak");
     println!("5. Heaptrack");
     println!("6. 自定义分配器跟踪");
+    
+    // 在测试中检查Rc是否有残留的强引用
+    let shared = Rc::new(vec![1, 2, 3]);
+    let guard = LeakGuard::new(&shared);
+    {
+        let temp = Rc::clone(&shared);
+        println!("  作用域内强引用数: {}", Rc::strong_count(&temp));
+    }
+    guard.check();
+    println!("  LeakGuard检查通过，作用域结束后没有残留强引用");
+}
+
+// 引用计数泄漏检测守卫
+// 创建时记录强引用计数，check()或Drop时断言计数回到初始值
+// 只持有Weak，因此守卫本身不会影响强引用计数；Weak引用也不计入泄漏
+struct LeakGuard<T> {
+    target: Weak<T>,
+    initial_strong: usize,
+    checked: Cell<bool>,
+}
+
+impl<T> LeakGuard<T> {
+    fn new(rc: &Rc<T>) -> Self {
+        LeakGuard {
+            target: Rc::downgrade(rc),
+            initial_strong: Rc::strong_count(rc),
+            checked: Cell::new(false),
+        }
+    }
+    
+    fn check(&self) {
+        self.checked.set(true);
+        let current = self.target.strong_count();
+        if current > self.initial_strong {
+            panic!(
+                "检测到引用泄漏: 残留 {} 个强引用 (初始 {}, 当前 {})",
+                current - self.initial_strong,
+                self.initial_strong,
+                current
+            );
+        }
+    }
+}
+
+impl<T> Drop for LeakGuard<T> {
+    fn drop(&mut self) {
+        // 已经显式检查过或正在panic时不再重复断言
+        if !self.checked.get() && !std::thread::panicking() {
+            self.check();
+        }
+    }
 }
 
 // 预分配容量
@@ -1042,6 +1172,107 @@ fn memory_pool_pattern() {
     
     let id3 = pool.allocate("Rust".to_string()).unwrap();
     println!("  重新分配ID {}: {:?}", id3, pool.get(id3));
+    
+    // 线程安全的对象池：归还时清空缓冲区，复用已分配的容量
+    let buffers = ObjectPool::new(2, || Vec::<u8>::with_capacity(1024))
+        .with_reset(|buffer: &mut Vec<u8>| buffer.clear());
+    std::thread::scope(|s| {
+        for i in 0..4u8 {
+            let buffers = &buffers;
+            s.spawn(move || {
+                let mut buffer = buffers.get();
+                buffer.extend_from_slice(&[i; 16]);
+            });
+        }
+    });
+    let buffer = buffers.get();
+    println!("  取出的缓冲区: 长度 {}, 容量 {}", buffer.len(), buffer.capacity());
+    println!("  共创建 {} 个对象, 池中空闲 {} 个", buffers.created(), buffers.available());
+}
+
+// 对象归还到池中时调用的重置函数
+type ResetFn<T> = Box<dyn Fn(&mut T) + Send + Sync>;
+
+// 线程安全的对象池：空闲对象保存在Mutex<Vec<T>>中，池空时调用工厂函数创建新对象
+struct ObjectPool<T> {
+    items: Mutex<Vec<T>>,
+    factory: Box<dyn Fn() -> T + Send + Sync>,
+    reset: Option<ResetFn<T>>,
+    created: AtomicUsize,
+}
+
+impl<T> ObjectPool<T> {
+    // 预先创建initial个对象
+    fn new(initial: usize, factory: impl Fn() -> T + Send + Sync + 'static) -> Self {
+        let items = (0..initial).map(|_| factory()).collect();
+        ObjectPool {
+            items: Mutex::new(items),
+            factory: Box::new(factory),
+            reset: None,
+            created: AtomicUsize::new(initial),
+        }
+    }
+    
+    // 对象归还时先调用reset，保证下次取出的是干净的对象
+    fn with_reset(mut self, reset: impl Fn(&mut T) + Send + Sync + 'static) -> Self {
+        self.reset = Some(Box::new(reset));
+        self
+    }
+    
+    fn get(&self) -> Pooled<'_, T> {
+        // 先释放锁再调用工厂函数
+        let item = self.items.lock().unwrap().pop();
+        let item = item.unwrap_or_else(|| {
+            self.created.fetch_add(1, Ordering::SeqCst);
+            (self.factory)()
+        });
+        Pooled {
+            item: Some(item),
+            pool: self,
+        }
+    }
+    
+    // 工厂函数累计创建的对象数
+    fn created(&self) -> usize {
+        self.created.load(Ordering::SeqCst)
+    }
+    
+    fn available(&self) -> usize {
+        self.items.lock().unwrap().len()
+    }
+}
+
+// 从对象池借出的对象，drop时归还
+struct Pooled<'a, T> {
+    item: Option<T>,
+    pool: &'a ObjectPool<T>,
+}
+
+impl<T> std::ops::Deref for Pooled<'_, T> {
+    type Target = T;
+    
+    fn deref(&self) -> &T {
+        self.item.as_ref().unwrap()
+    }
+}
+
+impl<T> std::ops::DerefMut for Pooled<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.item.as_mut().unwrap()
+    }
+}
+
+impl<T> Drop for Pooled<'_, T> {
+    fn drop(&mut self) {
+        if let Some(mut item) = self.item.take() {
+            if let Some(reset) = &self.pool.reset {
+                reset(&mut item);
+            }
+            if let Ok(mut items) = self.pool.items.lock() {
+                items.push(item);
+            }
+        }
+    }
 }
 
 // 缓存友好的数据结构
@@ -1176,4 +1407,86 @@ mod tests {
         drop(strong);
         assert!(weak.upgrade().is_none());
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_observer_prunes_dropped() {
+        let subject = Subject::new();
+        let log = Rc::new(RefCell::new(Vec::new()));
+        
+        let alive: Rc<dyn Observer> = Rc::new(LoggingObserver::new("存活", &log));
+        let dropped: Rc<dyn Observer> = Rc::new(LoggingObserver::new("销毁", &log));
+        subject.subscribe(&alive);
+        subject.subscribe(&dropped);
+        assert_eq!(subject.observer_count(), 2);
+        
+        // 主题不持有强引用
+        assert_eq!(Rc::strong_count(&alive), 1);
+        drop(dropped);
+        
+        assert_eq!(subject.notify("事件"), 1);
+        assert_eq!(*log.borrow(), vec!["存活: 事件".to_string()]);
+        assert_eq!(subject.observer_count(), 1);
+    }
+    
+    #[test]
+    fn test_leak_guard_no_leak() {
+        let shared = Rc::new(String::from("data"));
+        let guard = LeakGuard::new(&shared);
+        {
+            let _clone1 = Rc::clone(&shared);
+            let _clone2 = Rc::clone(&shared);
+        }
+        guard.check();
+    }
+    
+    #[test]
+    #[should_panic(expected = "残留 1 个强引用")]
+    fn test_leak_guard_detects_leak() {
+        let shared = Rc::new(String::from("data"));
+        let guard = LeakGuard::new(&shared);
+        let _leaked = Rc::clone(&shared);
+        guard.check();
+    }
+    
+    #[test]
+    fn test_leak_guard_ignores_weak() {
+        let shared = Rc::new(String::from("data"));
+        let _guard = LeakGuard::new(&shared);
+        let _weak = Rc::downgrade(&shared);
+        // Drop时自动检查
+    }
+    
+    #[test]
+    fn test_object_pool_reuses_and_resets() {
+        let pool = ObjectPool::new(1, Vec::<i32>::new).with_reset(|v: &mut Vec<i32>| v.clear());
+        {
+            let mut v = pool.get();
+            v.push(1);
+        }
+        assert!(pool.get().is_empty());
+        assert_eq!(pool.created(), 1);
+        
+        // 多线程反复借还，创建数不超过同时借出的峰值
+        let pool = ObjectPool::new(0, Vec::<i32>::new);
+        let in_use = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for i in 0..200 {
+                        // 借出前加计数、归还后减计数，计数覆盖对象被借出的整个区间
+                        let current = in_use.fetch_add(1, Ordering::SeqCst) + 1;
+                        peak.fetch_max(current, Ordering::SeqCst);
+                        let mut v = pool.get();
+                        v.push(i);
+                        drop(v);
+                        in_use.fetch_sub(1, Ordering::SeqCst);
+                    }
+                });
+            }
+        });
+        assert!(pool.created() >= 1);
+        assert!(pool.created() <= peak.load(Ordering::SeqCst));
+        assert_eq!(pool.available(), pool.created());
+    }
+}
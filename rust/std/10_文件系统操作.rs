@@ -31,10 +31,14 @@ std::path 模块：
 */
 
 use std::fs::{self, File, OpenOptions, DirEntry, Metadata};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::io::{Read, Write, BufRead, BufReader, BufWriter};
+use std::io::{self, Read, Write, Seek, SeekFrom, BufRead, BufReader, BufWriter};
 use std::os::unix::fs::PermissionsExt; // Unix系统特定
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
+use std::sync::{mpsc, Arc};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
 
 fn main() {
     println!("=== Rust标准库文件系统操作 ===");
@@ -189,32 +193,206 @@ fn directory_operations() {
     
     // 递归遍历目录
     println!("递归遍历目录:");
-    recursive_dir_walk(Path::new(test_dir), 0);
+    recursive_dir_walk(Path::new(test_dir));
+    
+    // 只遍历第一层，不跟随符号链接
+    let top_level_files: Vec<PathBuf> = WalkDir::new(test_dir)
+        .max_depth(1)
+        .follow_links(false)
+        .filter_map(Result::ok)
+        .filter(|entry| entry.is_file())
+        .map(|entry| entry.path().to_path_buf())
+        .collect();
+    println!("第一层的文件: {:?}", top_level_files);
+    
+    // 统计目录大小
+    match dir_size(Path::new(test_dir)) {
+        Ok(size) => println!("目录总大小: {} 字节", size),
+        Err(e) => println!("统计目录大小失败: {}", e),
+    }
+    if let Ok(largest) = largest_files(Path::new(test_dir), 1) {
+        println!("最大的文件: {:?}", largest);
+    }
     
     // 清理目录
     let _ = fs::remove_dir_all(test_dir);
     println!("清理完成");
 }
 
-// 递归遍历目录
-fn recursive_dir_walk(dir: &Path, depth: usize) {
-    let indent = "  ".repeat(depth);
+// 递归遍历目录，按深度缩进打印
+fn recursive_dir_walk(dir: &Path) {
+    for entry in WalkDir::new(dir) {
+        match entry {
+            Ok(entry) => {
+                let indent = "  ".repeat(entry.depth() - 1);
+                let icon = if entry.is_dir() { "📁" } else { "📄" };
+                println!("{}{} {}", indent, icon, entry.file_name().to_string_lossy());
+            }
+            Err(e) => println!("遍历出错: {}", e),
+        }
+    }
+}
+
+// 遍历得到的目录项，depth为相对根目录的层数（根目录的直接子项为1）
+#[derive(Debug)]
+struct WalkEntry {
+    path: PathBuf,
+    depth: usize,
+    file_type: fs::FileType,
+}
+
+impl WalkEntry {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+    
+    fn depth(&self) -> usize {
+        self.depth
+    }
     
-    if let Ok(entries) = fs::read_dir(dir) {
-        for entry in entries {
-            if let Ok(entry) = entry {
-                let path = entry.path();
-                if path.is_dir() {
-                    println!("{}📁 {}", indent, path.file_name().unwrap().to_string_lossy());
-                    recursive_dir_walk(&path, depth + 1);
-                } else {
-                    println!("{}📄 {}", indent, path.file_name().unwrap().to_string_lossy());
+    fn file_name(&self) -> &std::ffi::OsStr {
+        self.path.file_name().unwrap_or(self.path.as_os_str())
+    }
+    
+    // 跟随符号链接时，file_type是链接目标的类型
+    fn is_dir(&self) -> bool {
+        self.file_type.is_dir()
+    }
+    
+    fn is_file(&self) -> bool {
+        self.file_type.is_file()
+    }
+}
+
+// 惰性的深度优先目录遍历器，用显式栈保存打开的ReadDir而不是递归调用
+// I/O错误作为Err项返回，遍历会继续处理其余条目
+struct WalkDir {
+    max_depth: usize,
+    follow_links: bool,
+    // 下一次调用时要打开的目录及其子项的深度
+    pending: Option<(PathBuf, usize)>,
+    // 每层打开的目录：(ReadDir, 子项深度, 规范化路径)
+    stack: Vec<(fs::ReadDir, usize, Option<PathBuf>)>,
+}
+
+impl WalkDir {
+    fn new<P: AsRef<Path>>(root: P) -> Self {
+        WalkDir {
+            max_depth: usize::MAX,
+            follow_links: false,
+            pending: Some((root.as_ref().to_path_buf(), 1)),
+            stack: Vec::new(),
+        }
+    }
+    
+    // 只返回深度不超过max_depth的条目，max_depth(1)只包含直接子项
+    fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+    
+    // 是否进入指向目录的符号链接，默认关闭以避免循环
+    fn follow_links(mut self, follow: bool) -> Self {
+        self.follow_links = follow;
+        self
+    }
+    
+    fn open_pending(&mut self) -> io::Result<()> {
+        let (dir, depth) = match self.pending.take() {
+            Some(pending) => pending,
+            None => return Ok(()),
+        };
+        
+        // 跟随链接时记录规范化路径，用于检测回到祖先目录的循环
+        let canonical = if self.follow_links {
+            let canonical = fs::canonicalize(&dir)?;
+            if self.stack.iter().any(|(_, _, ancestor)| ancestor.as_ref() == Some(&canonical)) {
+                return Err(io::Error::other(format!("检测到符号链接循环: {}", dir.display())));
+            }
+            Some(canonical)
+        } else {
+            None
+        };
+        
+        let read_dir = fs::read_dir(&dir)?;
+        self.stack.push((read_dir, depth, canonical));
+        Ok(())
+    }
+}
+
+impl Iterator for WalkDir {
+    type Item = io::Result<WalkEntry>;
+    
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Err(e) = self.open_pending() {
+            return Some(Err(e));
+        }
+        
+        loop {
+            let (read_dir, depth, _) = self.stack.last_mut()?;
+            let depth = *depth;
+            
+            let entry = match read_dir.next() {
+                Some(Ok(entry)) => entry,
+                Some(Err(e)) => return Some(Err(e)),
+                None => {
+                    self.stack.pop();
+                    continue;
                 }
+            };
+            
+            let path = entry.path();
+            let mut file_type = match entry.file_type() {
+                Ok(file_type) => file_type,
+                Err(e) => return Some(Err(e)),
+            };
+            if file_type.is_symlink() && self.follow_links {
+                file_type = match fs::metadata(&path) {
+                    Ok(metadata) => metadata.file_type(),
+                    Err(e) => return Some(Err(e)),
+                };
             }
+            
+            if file_type.is_dir() && depth < self.max_depth {
+                self.pending = Some((path.clone(), depth + 1));
+            }
+            
+            return Some(Ok(WalkEntry { path, depth, file_type }));
         }
     }
 }
 
+// 递归统计目录下所有普通文件的大小，不跟随符号链接，避免重复计算和循环
+fn dir_size(path: &Path) -> io::Result<u64> {
+    let mut total = 0;
+    for entry in WalkDir::new(path) {
+        let entry = entry?;
+        if entry.is_file() {
+            total += fs::symlink_metadata(entry.path())?.len();
+        }
+    }
+    Ok(total)
+}
+
+// 目录下最大的n个文件，按大小从大到小排列
+fn largest_files(path: &Path, n: usize) -> io::Result<Vec<(PathBuf, u64)>> {
+    let mut files = Vec::new();
+    for entry in WalkDir::new(path) {
+        let entry = entry?;
+        if entry.is_file() {
+            let size = fs::symlink_metadata(entry.path())?.len();
+            files.push((entry.path().to_path_buf(), size));
+        }
+    }
+    
+    // 大小相同时按路径排序，保证结果稳定
+    files.sort_by(|(a_path, a_size), (b_path, b_size)| {
+        b_size.cmp(a_size).then_with(|| a_path.cmp(b_path))
+    });
+    files.truncate(n);
+    Ok(files)
+}
+
 // 路径处理
 fn path_operations() {
     let path = Path::new("/home/user/documents/file.txt");
@@ -594,8 +772,92 @@ fn large_file_handling() {
         println!("  文件大小: {:.2} MB", size_mb);
     }
     
+    // 带进度的复制：模拟中断后续传
+    let copy_target = "large_test_copy.txt";
+    let mut last_percent = 0;
+    let result = copy_with_progress(Path::new(large_file), Path::new(copy_target), |done, total| {
+        let percent = (done * 100).checked_div(total).unwrap_or(100);
+        if percent >= last_percent + 50 || done == total {
+            println!("  复制进度: {}%", percent);
+            last_percent = percent;
+        }
+    });
+    match result {
+        Ok(copied) => println!("  复制完成: {} 字节", copied),
+        Err(e) => println!("  复制失败: {}", e),
+    }
+    if let Ok(file) = OpenOptions::new().write(true).open(copy_target) {
+        let _ = file.set_len(1000);
+    }
+    match copy_resumable(Path::new(large_file), Path::new(copy_target), |_, _| {}) {
+        Ok(copied) => println!("  续传完成: 补齐 {} 字节", copied),
+        Err(e) => println!("  续传失败: {}", e),
+    }
+    
     // 清理
     let _ = fs::remove_file(large_file);
+    let _ = fs::remove_file(copy_target);
+}
+
+// 分块复制文件，每复制一块调用on_progress(已完成字节数, 总字节数)，返回复制的字节数
+fn copy_with_progress(src: &Path, dst: &Path, on_progress: impl FnMut(u64, u64)) -> io::Result<u64> {
+    let mut reader = File::open(src)?;
+    let total = reader.metadata()?.len();
+    let mut writer = File::create(dst)?;
+    copy_chunks(&mut reader, &mut writer, 0, total, on_progress)
+}
+
+// 断点续传：dst比src短时，跳过dst已有的部分只复制剩余内容（不校验已有部分）；
+// dst不存在或比src长时重新完整复制。返回本次复制的字节数
+fn copy_resumable(src: &Path, dst: &Path, mut on_progress: impl FnMut(u64, u64)) -> io::Result<u64> {
+    let mut reader = File::open(src)?;
+    let total = reader.metadata()?.len();
+    let existing = match fs::metadata(dst) {
+        Ok(metadata) => metadata.len(),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => 0,
+        Err(e) => return Err(e),
+    };
+    
+    if existing > total {
+        return copy_with_progress(src, dst, on_progress);
+    }
+    if existing == total {
+        on_progress(total, total);
+        return Ok(0);
+    }
+    
+    // 保留已复制的部分，从existing处接着写
+    let mut writer = OpenOptions::new().write(true).create(true).truncate(false).open(dst)?;
+    reader.seek(SeekFrom::Start(existing))?;
+    writer.seek(SeekFrom::Start(existing))?;
+    copy_chunks(&mut reader, &mut writer, existing, total, on_progress)
+}
+
+fn copy_chunks(
+    reader: &mut File,
+    writer: &mut File,
+    start: u64,
+    total: u64,
+    mut on_progress: impl FnMut(u64, u64),
+) -> io::Result<u64> {
+    let mut buffer = vec![0; 64 * 1024];
+    let mut done = start;
+    on_progress(done, total);
+    
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        writer.write_all(&buffer[..read])?;
+        done += read as u64;
+        on_progress(done, total);
+    }
+    
+    writer.flush()?;
+    Ok(done - start)
 }
 
 // 文件比较
@@ -654,43 +916,106 @@ fn file_monitoring() {
 
 // 基本的文件轮询监控
 fn basic_file_polling() {
-    use std::thread;
-    use std::time::Duration;
-    
-    let monitor_file = "monitor_test.txt";
+    let monitor_file = std::env::temp_dir().join("monitor_test.txt");
+    let _ = fs::remove_file(&monitor_file);
     
-    // 创建初始文件
-    let _ = fs::write(monitor_file, "初始内容");
+    println!("  开始监控文件: {}", monitor_file.display());
+    let watcher = FileWatcher::new(&monitor_file, Duration::from_millis(20));
     
-    let mut last_modified = if let Ok(metadata) = fs::metadata(monitor_file) {
-        metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH)
-    } else {
-        SystemTime::UNIX_EPOCH
+    let wait_event = |action: &str| match watcher.events().recv_timeout(Duration::from_secs(1)) {
+        Ok(event) => println!("  {}文件后收到事件: {:?}", action, event),
+        Err(_) => println!("  {}文件后没有收到事件", action),
     };
     
-    println!("  开始监控文件: {}", monitor_file);
+    // 依次创建、修改、删除文件，每步之后等待事件
+    let _ = fs::write(&monitor_file, "初始内容");
+    wait_event("创建");
     
-    // 模拟监控循环
-    for i in 0..3 {
-        thread::sleep(Duration::from_millis(100));
-        
-        if i == 1 {
-            // 在第二次检查时修改文件
-            let _ = fs::write(monitor_file, "修改后的内容");
-        }
+    let _ = fs::write(&monitor_file, "修改后的内容");
+    wait_event("修改");
+    
+    let _ = fs::remove_file(&monitor_file);
+    wait_event("删除");
+    
+    watcher.stop();
+}
+
+// 文件变化事件
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Event {
+    Created,
+    Modified,
+    Removed,
+}
+
+// 轮询式文件监控：后台线程定期比较修改时间和大小，通过通道发送事件
+// 文件一开始不存在也可以监控，出现时发送Created
+struct FileWatcher {
+    events: mpsc::Receiver<Event>,
+    stop_flag: Arc<AtomicBool>,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+impl FileWatcher {
+    fn new<P: AsRef<Path>>(path: P, interval: Duration) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let (sender, events) = mpsc::channel();
+        let stop_flag = Arc::new(AtomicBool::new(false));
         
-        if let Ok(metadata) = fs::metadata(monitor_file) {
-            if let Ok(modified) = metadata.modified() {
-                if modified > last_modified {
-                    println!("  检测到文件修改!");
-                    last_modified = modified;
+        // 在返回之前取得初始快照，避免启动之后立刻发生的变化被当作初始状态
+        let mut last = Self::snapshot(&path);
+        let stop = stop_flag.clone();
+        let handle = thread::spawn(move || {
+            while !stop.load(Ordering::SeqCst) {
+                thread::sleep(interval);
+                
+                let current = Self::snapshot(&path);
+                let event = match (&last, &current) {
+                    (None, Some(_)) => Some(Event::Created),
+                    (Some(_), None) => Some(Event::Removed),
+                    (Some(old), Some(new)) if old != new => Some(Event::Modified),
+                    _ => None,
+                };
+                last = current;
+                
+                if let Some(event) = event {
+                    if sender.send(event).is_err() {
+                        break; // 接收端已丢弃
+                    }
                 }
             }
-        }
+        });
+        
+        FileWatcher { events, stop_flag, handle: Some(handle) }
     }
     
-    // 清理
-    let _ = fs::remove_file(monitor_file);
+    // 文件的修改时间和大小，文件不存在时为None
+    fn snapshot(path: &Path) -> Option<(SystemTime, u64)> {
+        let metadata = fs::metadata(path).ok()?;
+        Some((metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH), metadata.len()))
+    }
+    
+    fn events(&self) -> &mpsc::Receiver<Event> {
+        &self.events
+    }
+    
+    // 停止监控并等待后台线程结束
+    fn stop(mut self) {
+        self.shutdown();
+    }
+    
+    fn shutdown(&mut self) {
+        self.stop_flag.store(true, Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for FileWatcher {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
 }
 
 // 临时文件处理
@@ -725,45 +1050,153 @@ fn temporary_file_handling() {
     temp_file_best_practices();
 }
 
-// 临时文件最佳实践
-fn temp_file_best_practices() {
-    println!("  临时文件最佳实践:");
-    println!("    1. 使用唯一的文件名避免冲突");
-    println!("    2. 及时清理临时文件");
-    println!("    3. 使用 RAII 确保清理");
-    println!("    4. 考虑使用 tempfile 库");
+// RAII 临时文件：离开作用域时自动删除，persist后改为保留
+struct TempFile {
+    path: PathBuf,
+    file: Option<File>,
+}
+
+impl TempFile {
+    fn new(name: &str) -> io::Result<Self> {
+        Self::in_dir(&std::env::temp_dir(), name)
+    }
     
-    // RAII 临时文件示例
-    struct TempFile {
-        path: PathBuf,
+    // 在指定目录创建临时文件，已存在同名文件时报错而不是覆盖
+    fn in_dir(dir: &Path, name: &str) -> io::Result<Self> {
+        let path = dir.join(name);
+        let file = OpenOptions::new().write(true).create_new(true).open(&path)?;
+        Ok(TempFile { path, file: Some(file) })
     }
     
-    impl TempFile {
-        fn new(name: &str) -> std::io::Result<Self> {
-            let path = std::env::temp_dir().join(name);
-            File::create(&path)?;
-            Ok(TempFile { path })
-        }
-        
-        fn path(&self) -> &Path {
-            &self.path
-        }
+    // 在系统临时目录创建名字唯一的临时文件，并发创建也不会冲突
+    fn new_unique(prefix: &str) -> io::Result<Self> {
+        Self::in_dir(&std::env::temp_dir(), &unique_temp_name(prefix))
+    }
+    
+    fn path(&self) -> &Path {
+        &self.path
+    }
+    
+    fn file(&mut self) -> &mut File {
+        self.file.as_mut().expect("临时文件已关闭")
     }
     
-    impl Drop for TempFile {
-        fn drop(&mut self) {
+    // 关闭文件并重命名到target，成功后不再自动删除
+    fn persist(mut self, target: &Path) -> io::Result<()> {
+        drop(self.file.take());
+        fs::rename(&self.path, target)?;
+        self.path = PathBuf::new();
+        Ok(())
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        drop(self.file.take());
+        if !self.path.as_os_str().is_empty() {
             let _ = fs::remove_file(&self.path);
         }
     }
+}
+
+// 唯一名字：进程ID区分进程，计数器区分同一进程内的调用，纳秒时间戳区分进程ID复用
+fn unique_temp_name(prefix: &str) -> String {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    format!(
+        "{}.{}.{}.{}",
+        prefix,
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed),
+        nanos
+    )
+}
+
+// RAII 临时目录：离开作用域时递归删除整个目录
+struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    fn new_unique(prefix: &str) -> io::Result<Self> {
+        let path = std::env::temp_dir().join(unique_temp_name(prefix));
+        fs::create_dir(&path)?;
+        Ok(TempDir { path })
+    }
+    
+    fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+// 原子写入：先写同目录下的临时文件并fsync，再rename覆盖目标文件，最后fsync所在目录
+// 读者要么看到旧内容，要么看到完整的新内容；任何一步失败都会删除临时文件
+fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    // rename只有在同一文件系统内才是原子的，所以临时文件必须和目标在同一目录
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let file_name = path.file_name().ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidInput, "目标路径缺少文件名")
+    })?;
+    let temp_name = format!("{}.tmp", unique_temp_name(&format!(".{}", file_name.to_string_lossy())));
+    
+    let mut temp = TempFile::in_dir(dir, &temp_name)?;
+    temp.file().write_all(contents)?;
+    temp.file().sync_all()?;
+    temp.persist(path)?;
+    
+    // rename修改的是目录项，目录本身也要落盘，否则崩溃后rename可能丢失
+    #[cfg(unix)]
+    fs::File::open(dir)?.sync_all()?;
+    Ok(())
+}
+
+// 临时文件最佳实践
+fn temp_file_best_practices() {
+    println!("  临时文件最佳实践:");
+    println!("    1. 使用唯一的文件名避免冲突");
+    println!("    2. 及时清理临时文件");
+    println!("    3. 使用 RAII 确保清理");
+    println!("    4. 考虑使用 tempfile 库");
     
     // 使用 RAII 临时文件
     {
         if let Ok(temp) = TempFile::new("raii_temp.txt") {
             println!("    RAII临时文件: {}", temp.path().display());
         } // 文件在此处自动清理
+        
+        // 唯一名字，并发场景下不会冲突
+        if let Ok(temp) = TempFile::new_unique("raii_temp") {
+            println!("    唯一临时文件: {}", temp.path().display());
+        }
+        
+        if let Ok(dir) = TempDir::new_unique("raii_dir") {
+            let _ = fs::create_dir(dir.path().join("nested"));
+            let _ = fs::write(dir.path().join("nested/data.txt"), "临时数据");
+            println!("    RAII临时目录: {}", dir.path().display());
+        } // 目录及其内容在此处自动清理
     }
     
     println!("    RAII临时文件已自动清理");
+    
+    // 原子写入，避免崩溃时留下只写了一半的文件
+    let config = std::env::temp_dir().join("atomic_write_demo.conf");
+    match write_atomic(&config, b"version = 2\n") {
+        Ok(_) => println!("    原子写入完成: {}", config.display()),
+        Err(e) => println!("    原子写入失败: {}", e),
+    }
+    let _ = fs::remove_file(&config);
 }
 
 // 文件搜索和过滤
@@ -787,6 +1220,35 @@ fn file_search_and_filter() {
     // 递归搜索特定内容
     search_content(test_root, "测试");
     
+    // 按通配符模式搜索
+    match glob(Path::new(test_root), "**/*.txt") {
+        Ok(matches) => {
+            println!("  匹配 **/*.txt 的文件:");
+            print_matches(&matches);
+        }
+        Err(e) => println!("  通配符搜索失败: {}", e),
+    }
+    
+    // 逐行搜索文件内容，输出行号
+    let opts = GrepOpts { ignore_case: true, wildcard: true, ..GrepOpts::default() };
+    if let Ok(file) = File::open(format!("{}/file2.rs", test_root)) {
+        println!("  grep -i 'FN *()' file2.rs:");
+        for (line_number, line) in grep(BufReader::new(file), "FN *()", opts).flatten() {
+            println!("    {}: {}", line_number, line);
+        }
+    }
+    
+    // 查找内容相同的文件
+    let _ = fs::copy(format!("{}/file1.txt", test_root), format!("{}/subdir/copy.txt", test_root));
+    match find_duplicates(Path::new(test_root)) {
+        Ok(groups) => {
+            for group in groups {
+                println!("  内容相同的文件: {:?}", group);
+            }
+        }
+        Err(e) => println!("  查找重复文件失败: {}", e),
+    }
+    
     // 清理
     let _ = fs::remove_dir_all(test_root);
 }
@@ -805,61 +1267,314 @@ fn setup_search_test_structure(root: &str) {
 // 按扩展名搜索
 fn search_by_extension(root: &str, ext: &str) {
     println!("  搜索 .{} 文件:", ext);
-    search_files_recursive(Path::new(root), |path| {
-        path.extension().map_or(false, |e| e == ext)
-    });
+    let predicate = by_extension(ext);
+    print_matches(&search_files_recursive(Path::new(root), |path| predicate.matches(path)));
 }
 
 // 按大小过滤
 fn filter_by_size(root: &str, min_size: u64) {
     println!("  搜索大于 {} 字节的文件:", min_size);
-    search_files_recursive(Path::new(root), |path| {
-        fs::metadata(path).map_or(false, |meta| meta.len() > min_size)
-    });
+    let predicate = larger_than(min_size);
+    print_matches(&search_files_recursive(Path::new(root), |path| predicate.matches(path)));
+    
+    // 组合条件
+    println!("  搜索大于 {} 字节的 .txt 或任意 .rs 文件:", min_size);
+    let predicate = by_extension("txt").and(larger_than(min_size)).or(by_extension("rs"));
+    print_matches(&search_files_recursive(Path::new(root), |path| predicate.matches(path)));
 }
 
 // 按时间过滤
 fn filter_by_time(root: &str) {
-    println!("  搜索最近创建的文件:");
+    println!("  搜索最近修改的文件:");
     let now = SystemTime::now();
     let five_minutes_ago = now - Duration::from_secs(300);
     
-    search_files_recursive(Path::new(root), |path| {
-        fs::metadata(path)
-            .and_then(|meta| meta.created())
-            .map_or(false, |created| created > five_minutes_ago)
-    });
+    let predicate = modified_after(five_minutes_ago);
+    print_matches(&search_files_recursive(Path::new(root), |path| predicate.matches(path)));
 }
 
 // 搜索文件内容
 fn search_content(root: &str, pattern: &str) {
     println!("  搜索包含 '{}' 的文件:", pattern);
-    search_files_recursive(Path::new(root), |path| {
+    let matches = search_files_recursive(Path::new(root), |path| {
         if let Ok(content) = fs::read_to_string(path) {
             content.contains(pattern)
         } else {
             false
         }
     });
+    print_matches(&matches);
+}
+
+fn print_matches(matches: &[PathBuf]) {
+    for path in matches {
+        println!("    找到: {}", path.display());
+    }
 }
 
-// 递归搜索文件
-fn search_files_recursive<F>(dir: &Path, predicate: F) 
+// 递归搜索文件，返回满足条件的文件路径（按路径排序），无法访问的条目被跳过
+fn search_files_recursive<F>(dir: &Path, predicate: F) -> Vec<PathBuf>
 where
-    F: Fn(&Path) -> bool + Copy,
+    F: Fn(&Path) -> bool,
 {
-    if let Ok(entries) = fs::read_dir(dir) {
-        for entry in entries {
-            if let Ok(entry) = entry {
-                let path = entry.path();
-                if path.is_dir() {
-                    search_files_recursive(&path, predicate);
-                } else if predicate(&path) {
-                    println!("    找到: {}", path.display());
+    let mut matches: Vec<PathBuf> = WalkDir::new(dir)
+        .filter_map(Result::ok)
+        .filter(|entry| entry.is_file() && predicate(entry.path()))
+        .map(|entry| entry.path().to_path_buf())
+        .collect();
+    matches.sort();
+    matches
+}
+
+// 可组合的文件过滤条件
+struct FilePredicate(Box<dyn Fn(&Path) -> bool>);
+
+impl FilePredicate {
+    fn new<F: Fn(&Path) -> bool + 'static>(predicate: F) -> Self {
+        FilePredicate(Box::new(predicate))
+    }
+    
+    fn matches(&self, path: &Path) -> bool {
+        (self.0)(path)
+    }
+    
+    fn and(self, other: FilePredicate) -> FilePredicate {
+        FilePredicate::new(move |path| self.matches(path) && other.matches(path))
+    }
+    
+    fn or(self, other: FilePredicate) -> FilePredicate {
+        FilePredicate::new(move |path| self.matches(path) || other.matches(path))
+    }
+}
+
+fn by_extension(ext: &str) -> FilePredicate {
+    let ext = ext.trim_start_matches('.').to_string();
+    FilePredicate::new(move |path| path.extension().is_some_and(|e| e == ext.as_str()))
+}
+
+fn larger_than(size: u64) -> FilePredicate {
+    FilePredicate::new(move |path| fs::metadata(path).is_ok_and(|meta| meta.len() > size))
+}
+
+fn modified_after(time: SystemTime) -> FilePredicate {
+    FilePredicate::new(move |path| {
+        fs::metadata(path)
+            .and_then(|meta| meta.modified())
+            .is_ok_and(|modified| modified > time)
+    })
+}
+
+// 通配符匹配，路径以 / 分隔：
+// * 匹配段内任意个字符，? 匹配段内单个字符，** 匹配零个或多个完整的段
+fn glob_match(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<&str> = pattern.split('/').collect();
+    let path: Vec<&str> = path.split('/').collect();
+    match_segments(&pattern, &path)
+}
+
+// ** 匹配任意个段；其余段按match_segment逐段比较
+fn match_segments(pattern: &[&str], path: &[&str]) -> bool {
+    wildcard_match(pattern, path, |p| *p == "**", |p, name| {
+        let segment: Vec<char> = p.chars().collect();
+        let name: Vec<char> = name.chars().collect();
+        match_segment(&segment, &name)
+    })
+}
+
+fn match_segment(pattern: &[char], name: &[char]) -> bool {
+    wildcard_match(pattern, name, |&c| c == '*', |&p, &c| p == '?' || p == c)
+}
+
+// 贪心通配符匹配：遇到星号先让它匹配零个元素，后面失败时只回到最近一个星号，
+// 让它多吞一个元素再试。更早的星号不需要再回溯，所以是O(模式长度 × 文本长度)
+fn wildcard_match<P, T>(
+    pattern: &[P],
+    text: &[T],
+    is_star: impl Fn(&P) -> bool,
+    matches_one: impl Fn(&P, &T) -> bool,
+) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // 最近一个星号的位置，以及它当前匹配到的文本位置
+    let mut star: Option<(usize, usize)> = None;
+    
+    while t < text.len() {
+        match pattern.get(p) {
+            Some(item) if is_star(item) => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(item) if matches_one(item, &text[t]) => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    star = Some((star_p, star_t + 1));
+                    p = star_p + 1;
+                    t = star_t + 1;
                 }
+                None => return false,
+            },
+        }
+    }
+    
+    pattern[p..].iter().all(is_star)
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct GrepOpts {
+    // 忽略大小写
+    ignore_case: bool,
+    // 输出不匹配的行
+    invert: bool,
+    // pattern中的 * 和 ? 作为通配符，否则按普通字符串查找
+    wildcard: bool,
+}
+
+// 逐行读取reader，产出匹配行的(行号, 内容)，行号从1开始。
+// 和grep一样是在行内查找，通配符模式相当于在两端各加一个 *
+fn grep<R: BufRead>(
+    reader: R,
+    pattern: &str,
+    opts: GrepOpts,
+) -> impl Iterator<Item = io::Result<(usize, String)>> {
+    let pattern = if opts.ignore_case { pattern.to_lowercase() } else { pattern.to_string() };
+    let wildcard: Vec<char> = format!("*{}*", pattern).chars().collect();
+    
+    reader.lines().enumerate().filter_map(move |(index, line)| {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => return Some(Err(e)),
+        };
+        
+        let matched = {
+            let folded;
+            let haystack = if opts.ignore_case {
+                folded = line.to_lowercase();
+                &folded
+            } else {
+                &line
+            };
+            if opts.wildcard {
+                let chars: Vec<char> = haystack.chars().collect();
+                match_segment(&wildcard, &chars)
+            } else {
+                haystack.contains(pattern.as_str())
             }
+        };
+        
+        if matched != opts.invert {
+            Some(Ok((index + 1, line)))
+        } else {
+            None
+        }
+    })
+}
+
+// 遍历root，返回相对路径匹配pattern的条目（按路径排序）
+fn glob(root: &Path, pattern: &str) -> io::Result<Vec<PathBuf>> {
+    let mut matches = Vec::new();
+    for entry in WalkDir::new(root) {
+        let entry = entry?;
+        let relative = entry.path().strip_prefix(root).unwrap_or(entry.path());
+        let relative: Vec<String> = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        if glob_match(pattern, &relative.join("/")) {
+            matches.push(entry.path().to_path_buf());
         }
     }
+    matches.sort();
+    Ok(matches)
+}
+
+// 查找内容相同的文件：先按大小分组，同大小的再按哈希分组，最后逐字节比较排除哈希碰撞
+// 返回包含两个及以上文件的分组，组内和组间都按路径排序
+fn find_duplicates(root: &Path) -> io::Result<Vec<Vec<PathBuf>>> {
+    let mut by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+    for entry in WalkDir::new(root) {
+        let entry = entry?;
+        if entry.is_file() {
+            let size = fs::symlink_metadata(entry.path())?.len();
+            by_size.entry(size).or_default().push(entry.path().to_path_buf());
+        }
+    }
+    
+    let mut duplicates = Vec::new();
+    for candidates in by_size.into_values().filter(|paths| paths.len() > 1) {
+        let mut by_hash: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+        for path in candidates {
+            by_hash.entry(fnv1a_file(&path)?).or_default().push(path);
+        }
+        
+        for same_hash in by_hash.into_values().filter(|paths| paths.len() > 1) {
+            // 每组以第一个文件为代表，和代表逐字节相同才加入该组
+            let mut groups: Vec<Vec<PathBuf>> = Vec::new();
+            for path in same_hash {
+                let mut found = None;
+                for (i, group) in groups.iter().enumerate() {
+                    if files_equal(&group[0], &path)? {
+                        found = Some(i);
+                        break;
+                    }
+                }
+                match found {
+                    Some(i) => groups[i].push(path),
+                    None => groups.push(vec![path]),
+                }
+            }
+            duplicates.extend(groups.into_iter().filter(|group| group.len() > 1));
+        }
+    }
+    
+    for group in &mut duplicates {
+        group.sort();
+    }
+    duplicates.sort();
+    Ok(duplicates)
+}
+
+// 64位FNV-1a哈希，分块读取文件，不需要把整个文件读入内存
+fn fnv1a_file(path: &Path) -> io::Result<u64> {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+    
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut hash = OFFSET_BASIS;
+    loop {
+        let chunk = reader.fill_buf()?;
+        if chunk.is_empty() {
+            return Ok(hash);
+        }
+        for &byte in chunk {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(PRIME);
+        }
+        let len = chunk.len();
+        reader.consume(len);
+    }
+}
+
+// 逐块比较两个文件的内容
+fn files_equal(a: &Path, b: &Path) -> io::Result<bool> {
+    let mut a = BufReader::new(File::open(a)?);
+    let mut b = BufReader::new(File::open(b)?);
+    loop {
+        let a_chunk = a.fill_buf()?;
+        let b_chunk = b.fill_buf()?;
+        if a_chunk.is_empty() || b_chunk.is_empty() {
+            return Ok(a_chunk.is_empty() && b_chunk.is_empty());
+        }
+        
+        // 两边缓冲区的数据量可能不同，只比较公共部分
+        let len = a_chunk.len().min(b_chunk.len());
+        if a_chunk[..len] != b_chunk[..len] {
+            return Ok(false);
+        }
+        a.consume(len);
+        b.consume(len);
+    }
 }
 
 // 最佳实践
@@ -982,6 +1697,205 @@ mod tests {
         assert_eq!(joined, path);
     }
     
+    // 在唯一的临时目录下创建测试目录结构，测试结束时由TempDir删除
+    struct Fixture {
+        root: PathBuf,
+        _dir: TempDir,
+    }
+    
+    impl Fixture {
+        fn new(name: &str, files: &[(&str, &str)]) -> Self {
+            let dir = TempDir::new_unique(name).unwrap();
+            let root = dir.path().to_path_buf();
+            for (relative, content) in files {
+                let path = root.join(relative);
+                fs::create_dir_all(path.parent().unwrap()).unwrap();
+                fs::write(path, content).unwrap();
+            }
+            Fixture { root, _dir: dir }
+        }
+        
+        fn relative(&self, path: &Path) -> String {
+            path.strip_prefix(&self.root).unwrap().to_string_lossy().replace('\\', "/")
+        }
+    }
+    
+    #[test]
+    fn test_walk_dir_yields_all_entries() {
+        let fixture = Fixture::new("walk_all", &[
+            ("a.txt", "a"),
+            ("sub/b.txt", "b"),
+            ("sub/deep/c.txt", "c"),
+        ]);
+        
+        let mut entries: Vec<(String, usize, bool)> = WalkDir::new(&fixture.root)
+            .map(|entry| entry.unwrap())
+            .map(|entry| (fixture.relative(entry.path()), entry.depth(), entry.is_dir()))
+            .collect();
+        entries.sort();
+        
+        assert_eq!(entries, vec![
+            ("a.txt".to_string(), 1, false),
+            ("sub".to_string(), 1, true),
+            ("sub/b.txt".to_string(), 2, false),
+            ("sub/deep".to_string(), 2, true),
+            ("sub/deep/c.txt".to_string(), 3, false),
+        ]);
+    }
+    
+    #[test]
+    fn test_walk_dir_max_depth() {
+        let fixture = Fixture::new("walk_depth", &[("a.txt", "a"), ("sub/b.txt", "b")]);
+        
+        let mut names: Vec<String> = WalkDir::new(&fixture.root)
+            .max_depth(1)
+            .map(|entry| fixture.relative(entry.unwrap().path()))
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["a.txt", "sub"]);
+    }
+    
+    #[test]
+    fn test_walk_dir_reports_errors() {
+        let mut walker = WalkDir::new("walk_dir_missing_root");
+        assert_eq!(walker.next().unwrap().unwrap_err().kind(), io::ErrorKind::NotFound);
+        assert!(walker.next().is_none());
+    }
+    
+    #[test]
+    fn test_search_with_combined_predicates() {
+        let fixture = Fixture::new("search_predicates", &[
+            ("small.txt", "tiny"),
+            ("big.txt", "this file is larger than ten bytes"),
+            ("big.rs", "this file is larger than ten bytes"),
+            ("sub/nested.txt", "nested file with enough content"),
+        ]);
+        
+        let predicate = by_extension("txt").and(larger_than(10));
+        let found: Vec<String> = search_files_recursive(&fixture.root, |path| predicate.matches(path))
+            .iter()
+            .map(|path| fixture.relative(path))
+            .collect();
+        assert_eq!(found, vec!["big.txt", "sub/nested.txt"]);
+        
+        let predicate = by_extension("rs").or(by_extension(".txt").and(larger_than(100)));
+        let found = search_files_recursive(&fixture.root, |path| predicate.matches(path));
+        assert_eq!(found, vec![fixture.root.join("big.rs")]);
+        
+        let an_hour_ago = SystemTime::now() - std::time::Duration::from_secs(3600);
+        let predicate = modified_after(an_hour_ago);
+        assert_eq!(search_files_recursive(&fixture.root, |path| predicate.matches(path)).len(), 4);
+        
+        let predicate = modified_after(SystemTime::now() + std::time::Duration::from_secs(3600));
+        assert!(search_files_recursive(&fixture.root, |path| predicate.matches(path)).is_empty());
+    }
+    
+    #[test]
+    fn test_write_atomic_readers_see_complete_versions() {
+        let fixture = Fixture::new("atomic_write", &[]);
+        let target = fixture.root.join("data.txt");
+        
+        let first = "A".repeat(64 * 1024);
+        let second = "B".repeat(128 * 1024);
+        write_atomic(&target, first.as_bytes()).unwrap();
+        
+        let done = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let reader = {
+            let target = target.clone();
+            let done = done.clone();
+            let (first, second) = (first.clone(), second.clone());
+            std::thread::spawn(move || {
+                let mut reads = 0;
+                while !done.load(std::sync::atomic::Ordering::SeqCst) {
+                    let content = fs::read_to_string(&target).unwrap();
+                    assert!(content == first || content == second, "读到了不完整的文件");
+                    reads += 1;
+                }
+                reads
+            })
+        };
+        
+        for i in 0..50 {
+            let contents = if i % 2 == 0 { &second } else { &first };
+            write_atomic(&target, contents.as_bytes()).unwrap();
+        }
+        done.store(true, std::sync::atomic::Ordering::SeqCst);
+        assert!(reader.join().unwrap() > 0);
+        
+        assert_eq!(fs::read_to_string(&target).unwrap(), first);
+        
+        // 没有残留的临时文件
+        let names: Vec<_> = fs::read_dir(&fixture.root)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(names, vec!["data.txt"]);
+    }
+    
+    #[test]
+    fn test_write_atomic_cleans_up_on_failure() {
+        let fixture = Fixture::new("atomic_write_fail", &[]);
+        // 目标是一个非空目录，rename会失败
+        let target = fixture.root.join("target");
+        fs::create_dir_all(target.join("child")).unwrap();
+        
+        assert!(write_atomic(&target, b"data").is_err());
+        
+        let names: Vec<_> = fs::read_dir(&fixture.root)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(names, vec!["target"]);
+    }
+    
+    #[test]
+    fn test_file_watcher_event_sequence() {
+        let fixture = Fixture::new("file_watcher", &[]);
+        let path = fixture.root.join("watched.txt");
+        
+        // 文件还不存在时开始监控
+        let watcher = FileWatcher::new(&path, Duration::from_millis(10));
+        let next_event = || watcher.events().recv_timeout(Duration::from_secs(2)).unwrap();
+        // 先写临时文件再rename，轮询不会看到写了一半的文件而多产生一次Modified
+        let replace = |content: &str| {
+            let temp = fixture.root.join("watched.tmp");
+            fs::write(&temp, content).unwrap();
+            fs::rename(&temp, &path).unwrap();
+        };
+        
+        replace("v1");
+        assert_eq!(next_event(), Event::Created);
+        
+        // 内容长度不同，即使修改时间精度不足也能检测到
+        replace("version 2");
+        assert_eq!(next_event(), Event::Modified);
+        
+        fs::remove_file(&path).unwrap();
+        assert_eq!(next_event(), Event::Removed);
+        
+        // 没有变化时不产生事件
+        assert!(watcher.events().recv_timeout(Duration::from_millis(100)).is_err());
+        watcher.stop();
+    }
+    
+    #[cfg(unix)]
+    #[test]
+    fn test_walk_dir_symlinks() {
+        let fixture = Fixture::new("walk_links", &[("sub/b.txt", "b")]);
+        // sub/loop 指回根目录
+        std::os::unix::fs::symlink(&fixture.root, fixture.root.join("sub/loop")).unwrap();
+        
+        // 默认不跟随链接，链接本身作为非目录条目返回
+        let entries: Vec<WalkEntry> = WalkDir::new(&fixture.root).map(|e| e.unwrap()).collect();
+        assert_eq!(entries.len(), 3);
+        assert!(entries.iter().any(|e| e.file_name() == "loop" && !e.is_dir()));
+        
+        // 跟随链接时检测到循环并报告错误，而不是无限遍历
+        let results: Vec<io::Result<WalkEntry>> = WalkDir::new(&fixture.root).follow_links(true).collect();
+        assert_eq!(results.iter().filter(|r| r.is_err()).count(), 1);
+        assert!(results.iter().flatten().any(|e| e.is_file() && e.file_name() == "b.txt"));
+    }
+    
     #[test]
     fn test_metadata() {
         let test_file = "test_metadata.txt";
@@ -993,4 +1907,218 @@ mod tests {
         
         fs::remove_file(test_file).unwrap();
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_temp_file_unique_names_concurrently() {
+        let temps: Vec<TempFile> = thread::scope(|s| {
+            let handles: Vec<_> = (0..10)
+                .map(|_| {
+                    s.spawn(|| {
+                        (0..10)
+                            .map(|_| TempFile::new_unique("unique_test").unwrap())
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            handles.into_iter().flat_map(|h| h.join().unwrap()).collect()
+        });
+        
+        let paths: Vec<PathBuf> = temps.iter().map(|t| t.path().to_path_buf()).collect();
+        let distinct: std::collections::HashSet<&PathBuf> = paths.iter().collect();
+        assert_eq!(distinct.len(), 100);
+        assert!(paths.iter().all(|p| p.exists()));
+        
+        drop(temps);
+        assert!(paths.iter().all(|p| !p.exists()));
+    }
+    
+    #[test]
+    fn test_temp_dir_removed_recursively() {
+        let dir = TempDir::new_unique("temp_dir_test").unwrap();
+        let root = dir.path().to_path_buf();
+        fs::create_dir_all(root.join("a/b")).unwrap();
+        fs::write(root.join("a/b/c.txt"), "data").unwrap();
+        
+        drop(dir);
+        assert!(!root.exists());
+    }
+    
+    #[test]
+    fn test_dir_size_and_largest_files() {
+        let fixture = Fixture::new("dir_size", &[
+            ("a.txt", &"a".repeat(10)),
+            ("sub/b.txt", &"b".repeat(100)),
+            ("sub/deep/c.txt", &"c".repeat(50)),
+            ("d.txt", "d"),
+        ]);
+        // 符号链接不计入大小
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(fixture.root.join("sub/b.txt"), fixture.root.join("link.txt")).unwrap();
+        
+        assert_eq!(dir_size(&fixture.root).unwrap(), 161);
+        
+        let largest = largest_files(&fixture.root, 2).unwrap();
+        let largest: Vec<(String, u64)> = largest.iter().map(|(p, size)| (fixture.relative(p), *size)).collect();
+        assert_eq!(largest, vec![("sub/b.txt".to_string(), 100), ("sub/deep/c.txt".to_string(), 50)]);
+    }
+    
+    #[test]
+    fn test_find_duplicates() {
+        let fixture = Fixture::new("find_duplicates", &[
+            ("a.txt", "same content"),
+            ("sub/b.txt", "same content"),
+            // 大小相同但内容不同
+            ("c.txt", "diff content"),
+        ]);
+        
+        let groups = find_duplicates(&fixture.root).unwrap();
+        assert_eq!(groups.len(), 1);
+        let group: Vec<String> = groups[0].iter().map(|p| fixture.relative(p)).collect();
+        assert_eq!(group, vec!["a.txt", "sub/b.txt"]);
+    }
+    
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*.txt", "notes.txt"));
+        assert!(glob_match("*.txt", ".txt"));
+        assert!(!glob_match("*.txt", "notes.rs"));
+        // * 不跨越目录
+        assert!(!glob_match("*.txt", "docs/notes.txt"));
+        
+        assert!(glob_match("**/*.rs", "main.rs"));
+        assert!(glob_match("**/*.rs", "src/a/b/lib.rs"));
+        assert!(glob_match("src/**/*.rs", "src/lib.rs"));
+        assert!(!glob_match("src/**/*.rs", "tests/lib.rs"));
+        assert!(!glob_match("**/*.rs", "src/lib.rs.bak"));
+        
+        assert!(glob_match("file?.txt", "file1.txt"));
+        assert!(!glob_match("file?.txt", "file.txt"));
+        assert!(!glob_match("file?.txt", "file12.txt"));
+        assert!(glob_match("???", "世界!"));
+        assert!(glob_match("a*b*c", "aXbYbZc"));
+        assert!(!glob_match("a*b*c", "aXbYbZ"));
+        assert!(glob_match("**", ""));
+        assert!(glob_match("src/**", "src/a/b"));
+    }
+    
+    #[test]
+    fn test_glob_match_no_exponential_backtracking() {
+        // 递归回溯实现在这里需要尝试指数级的组合
+        let start = std::time::Instant::now();
+        let name = "a".repeat(40);
+        assert!(!glob_match("*a*a*a*a*a*a*a*b", &name));
+        assert!(glob_match("*a*a*a*a*a*a*a*", &name));
+        
+        let path = vec!["a"; 40].join("/");
+        assert!(!glob_match("**/a/**/a/**/a/**/a/**/b", &path));
+        assert!(glob_match("**/a/**/a/**/a/**/a/**", &path));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+    
+    #[test]
+    fn test_glob_walks_directory() {
+        let fixture = Fixture::new("glob", &[
+            ("a.txt", ""),
+            ("b.rs", ""),
+            ("src/lib.rs", ""),
+            ("src/nested/mod.rs", ""),
+            ("src/readme.txt", ""),
+        ]);
+        
+        let relative = |pattern: &str| -> Vec<String> {
+            glob(&fixture.root, pattern).unwrap().iter().map(|p| fixture.relative(p)).collect()
+        };
+        assert_eq!(relative("*.txt"), vec!["a.txt"]);
+        assert_eq!(relative("**/*.rs"), vec!["b.rs", "src/lib.rs", "src/nested/mod.rs"]);
+        assert_eq!(relative("src/*"), vec!["src/lib.rs", "src/nested", "src/readme.txt"]);
+        assert!(relative("*.md").is_empty());
+    }
+    
+    #[test]
+    fn test_copy_with_progress_reports_monotonic_progress() {
+        let fixture = Fixture::new("copy_progress", &[]);
+        let src = fixture.root.join("src.bin");
+        let dst = fixture.root.join("dst.bin");
+        let data: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        fs::write(&src, &data).unwrap();
+        
+        let mut progress = Vec::new();
+        let copied = copy_with_progress(&src, &dst, |done, total| progress.push((done, total))).unwrap();
+        
+        assert_eq!(copied, data.len() as u64);
+        assert_eq!(fs::read(&dst).unwrap(), data);
+        assert!(progress.len() > 2);
+        assert!(progress.windows(2).all(|w| w[0].0 < w[1].0));
+        assert!(progress.iter().all(|&(_, total)| total == data.len() as u64));
+        assert_eq!(progress.last(), Some(&(data.len() as u64, data.len() as u64)));
+    }
+    
+    #[test]
+    fn test_copy_resumable_appends_remainder() {
+        let fixture = Fixture::new("copy_resume", &[]);
+        let src = fixture.root.join("src.txt");
+        let dst = fixture.root.join("dst.txt");
+        fs::write(&src, "abcdefghij").unwrap();
+        // 已有部分故意和源文件不同，用来确认续传没有重新复制前缀
+        fs::write(&dst, "XXXX").unwrap();
+        
+        let mut first = None;
+        let copied = copy_resumable(&src, &dst, |done, _| {
+            first.get_or_insert(done);
+        })
+        .unwrap();
+        
+        assert_eq!(copied, 6);
+        assert_eq!(first, Some(4));
+        assert_eq!(fs::read_to_string(&dst).unwrap(), "XXXXefghij");
+        
+        // 已经完整时不再复制
+        assert_eq!(copy_resumable(&src, &dst, |_, _| {}).unwrap(), 0);
+    }
+    
+    const GREP_INPUT: &str = "Error: disk full\nwarning: low memory\nINFO started\nerror: timeout\n";
+    
+    fn run_grep(pattern: &str, opts: GrepOpts) -> Vec<(usize, String)> {
+        grep(io::Cursor::new(GREP_INPUT), pattern, opts)
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap()
+    }
+    
+    #[test]
+    fn test_grep_plain_and_inverted() {
+        assert_eq!(run_grep("error", GrepOpts::default()), [(4, "error: timeout".to_string())]);
+        
+        let inverted = run_grep("error", GrepOpts { invert: true, ..GrepOpts::default() });
+        let numbers: Vec<usize> = inverted.iter().map(|(n, _)| *n).collect();
+        assert_eq!(numbers, [1, 2, 3]);
+        
+        assert!(run_grep("missing", GrepOpts::default()).is_empty());
+    }
+    
+    #[test]
+    fn test_grep_ignore_case_and_wildcard() {
+        let opts = GrepOpts { ignore_case: true, ..GrepOpts::default() };
+        let numbers: Vec<usize> = run_grep("ERROR", opts).iter().map(|(n, _)| *n).collect();
+        assert_eq!(numbers, [1, 4]);
+        
+        // 通配符模式下 * 和 ? 有特殊含义，普通模式下按字面查找
+        let opts = GrepOpts { wildcard: true, ..GrepOpts::default() };
+        assert_eq!(run_grep("w?rning*memory", opts), [(2, "warning: low memory".to_string())]);
+        assert!(run_grep("w?rning", GrepOpts::default()).is_empty());
+        
+        let opts = GrepOpts { ignore_case: true, wildcard: true, invert: true };
+        let numbers: Vec<usize> = run_grep("E*:", opts).iter().map(|(n, _)| *n).collect();
+        assert_eq!(numbers, [2, 3]);
+    }
+    
+    #[test]
+    fn test_grep_wildcard_long_line() {
+        // 多个 * 遇到不匹配的长行时不能指数级回溯
+        let start = std::time::Instant::now();
+        let line = "a".repeat(200);
+        let opts = GrepOpts { wildcard: true, ..GrepOpts::default() };
+        assert_eq!(grep(io::Cursor::new(&line), "a*a*a*a*b", opts).count(), 0);
+        assert_eq!(grep(io::Cursor::new(&line), "a*a*a*a", opts).count(), 1);
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+}
@@ -31,12 +31,15 @@ std::env 主要功能：
 - 自动化脚本
 */
 
-use std::process::{Command, Stdio, Child};
+use std::process::{Command, Stdio, Child, ChildStdin, ExitStatus, Output};
 use std::env;
-use std::io::{Write, BufRead, BufReader};
+use std::io::{self, Read, Write, BufRead, BufReader};
 use std::thread;
 use std::time::Duration;
 use std::collections::HashMap;
+use std::sync::mpsc;
+use std::str::FromStr;
+use std::path::{Path, PathBuf};
 
 fn main() {
     println!("=== Rust标准库进程与环境操作 ===");
@@ -122,6 +125,35 @@ fn environment_variables() {
         Err(_) => println!("环境变量已删除"),
     }
     
+    // 带类型的环境变量读取
+    env::set_var("RUST_TEST_PORT", "8080");
+    match env_parsed::<u16>("RUST_TEST_PORT") {
+        Ok(Some(port)) => println!("端口: {}", port),
+        Ok(None) => println!("端口未设置"),
+        Err(e) => println!("端口配置错误: {}", e),
+    }
+    env::remove_var("RUST_TEST_PORT");
+    match env_or("RUST_TEST_WORKERS", 4usize) {
+        Ok(workers) => println!("工作线程数: {}", workers),
+        Err(e) => println!("工作线程数配置错误: {}", e),
+    }
+    
+    // 从 .env 文件加载环境变量
+    let dotenv = env::temp_dir().join(format!("demo_{}.env", std::process::id()));
+    let _ = std::fs::write(&dotenv, "# 示例配置\nexport RUST_DEMO_MODE=\"开发 模式\"\nRUST_DEMO_LEVEL=3 # 注释\n");
+    match load_dotenv(&dotenv, false) {
+        Ok(count) => println!(
+            "从 .env 加载了 {} 个变量: RUST_DEMO_MODE={:?}, RUST_DEMO_LEVEL={:?}",
+            count,
+            env::var("RUST_DEMO_MODE").unwrap_or_default(),
+            env_parsed::<u8>("RUST_DEMO_LEVEL")
+        ),
+        Err(e) => println!("加载 .env 失败: {}", e),
+    }
+    env::remove_var("RUST_DEMO_MODE");
+    env::remove_var("RUST_DEMO_LEVEL");
+    let _ = std::fs::remove_file(&dotenv);
+    
     // 遍历所有环境变量
     println!("环境变量总数: {}", env::vars().count());
     
@@ -144,6 +176,147 @@ fn environment_variables() {
     }
 }
 
+// 环境变量读取错误
+#[derive(Debug, PartialEq)]
+enum EnvError {
+    // 值不是合法的Unicode
+    NotUnicode(String),
+    // 值无法解析为目标类型
+    Invalid { key: String, value: String, message: String },
+}
+
+impl std::fmt::Display for EnvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EnvError::NotUnicode(key) => write!(f, "环境变量 {} 不是合法的Unicode", key),
+            EnvError::Invalid { key, value, message } => {
+                write!(f, "环境变量 {}={:?} 解析失败: {}", key, value, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for EnvError {}
+
+// 读取并解析环境变量：未设置时返回Ok(None)，设置了但无法解析时返回错误
+fn env_parsed<T>(key: &str) -> Result<Option<T>, EnvError>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    let value = match env::var(key) {
+        Ok(value) => value,
+        Err(env::VarError::NotPresent) => return Ok(None),
+        Err(env::VarError::NotUnicode(_)) => return Err(EnvError::NotUnicode(key.to_string())),
+    };
+    
+    value.trim().parse().map(Some).map_err(|e: T::Err| EnvError::Invalid {
+        key: key.to_string(),
+        value: value.clone(),
+        message: e.to_string(),
+    })
+}
+
+// 未设置时使用默认值；设置了错误的值仍然报错，而不是悄悄使用默认值
+fn env_or<T>(key: &str, default: T) -> Result<T, EnvError>
+where
+    T: FromStr,
+    T::Err: std::fmt::Display,
+{
+    Ok(env_parsed(key)?.unwrap_or(default))
+}
+
+// 加载 .env 文件并设置环境变量，返回实际设置的变量个数
+// override_existing为false时保留已经存在的变量
+fn load_dotenv(path: &Path, override_existing: bool) -> io::Result<usize> {
+    let content = std::fs::read_to_string(path)?;
+    
+    // 先解析整个文件，出错时不会留下只设置了一半的变量
+    let mut entries = Vec::new();
+    for (number, line) in content.lines().enumerate() {
+        match parse_dotenv_line(line) {
+            Ok(Some(entry)) => entries.push(entry),
+            Ok(None) => {}
+            Err(message) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{} 第{}行: {}", path.display(), number + 1, message),
+                ));
+            }
+        }
+    }
+    
+    let mut count = 0;
+    for (key, value) in entries {
+        if override_existing || env::var_os(&key).is_none() {
+            env::set_var(&key, value);
+            count += 1;
+        }
+    }
+    
+    Ok(count)
+}
+
+// 解析一行 KEY=VALUE，空行和注释行返回None
+// 支持 export 前缀、双引号（可转义）、单引号（原样）和未加引号值后面的 # 注释
+fn parse_dotenv_line(line: &str) -> Result<Option<(String, String)>, String> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+    
+    let line = line.strip_prefix("export ").map_or(line, str::trim_start);
+    let (key, raw) = line.split_once('=').ok_or_else(|| format!("缺少 '=': {}", line))?;
+    let key = key.trim();
+    if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(format!("非法的变量名: {:?}", key));
+    }
+    
+    let raw = raw.trim();
+    let value = if let Some(rest) = raw.strip_prefix('"') {
+        let mut value = String::new();
+        let mut chars = rest.chars();
+        loop {
+            match chars.next() {
+                Some('"') => break,
+                Some('\\') => match chars.next() {
+                    Some('n') => value.push('\n'),
+                    Some('t') => value.push('\t'),
+                    Some(c) => value.push(c),
+                    None => return Err("引号未闭合".to_string()),
+                },
+                Some(c) => value.push(c),
+                None => return Err("引号未闭合".to_string()),
+            }
+        }
+        check_after_quote(chars.as_str())?;
+        value
+    } else if let Some(rest) = raw.strip_prefix('\'') {
+        let end = rest.find('\'').ok_or("引号未闭合")?;
+        check_after_quote(&rest[end + 1..])?;
+        rest[..end].to_string()
+    } else if raw.starts_with('#') {
+        // KEY= # 注释 表示空值
+        String::new()
+    } else {
+        // 未加引号的值中，空白后的 # 开始注释
+        let end = raw.find(" #").unwrap_or(raw.len());
+        raw[..end].trim_end().to_string()
+    };
+    
+    Ok(Some((key.to_string(), value)))
+}
+
+// 闭合引号后只允许空白或 # 注释
+fn check_after_quote(rest: &str) -> Result<(), String> {
+    let rest = rest.trim_start();
+    if rest.is_empty() || rest.starts_with('#') {
+        Ok(())
+    } else {
+        Err(format!("引号后有多余内容: {}", rest))
+    }
+}
+
 // 命令行参数处理
 fn command_line_arguments() {
     // 获取所有参数
@@ -170,6 +343,128 @@ fn command_line_arguments() {
     simple_argument_parsing();
 }
 
+// 参数解析错误
+#[derive(Debug, PartialEq)]
+enum ArgError {
+    // 选项后面缺少值
+    MissingValue(String),
+    // 未声明的参数
+    UnknownArgument(String),
+}
+
+impl std::fmt::Display for ArgError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArgError::MissingValue(name) => write!(f, "{} 需要一个参数", name),
+            ArgError::UnknownArgument(arg) => write!(f, "未知参数: {}", arg),
+        }
+    }
+}
+
+impl std::error::Error for ArgError {}
+
+// 参数声明：长名、短名以及是否需要值
+struct ArgSpec {
+    long: String,
+    short: String,
+    takes_value: bool,
+}
+
+// 简单的命令行参数解析器，先声明flag/option再解析
+#[derive(Default)]
+struct ArgParser {
+    specs: Vec<ArgSpec>,
+}
+
+impl ArgParser {
+    fn new() -> Self {
+        Self::default()
+    }
+    
+    // 不带值的开关，如 --help / -h
+    fn flag(mut self, long: &str, short: &str) -> Self {
+        self.specs.push(ArgSpec { long: long.to_string(), short: short.to_string(), takes_value: false });
+        self
+    }
+    
+    // 带值的选项，如 --config foo.toml、-c foo.toml 或 --config=foo.toml
+    fn option(mut self, long: &str, short: &str) -> Self {
+        self.specs.push(ArgSpec { long: long.to_string(), short: short.to_string(), takes_value: true });
+        self
+    }
+    
+    fn find(&self, name: &str) -> Option<&ArgSpec> {
+        self.specs.iter().find(|spec| spec.long == name || spec.short == name)
+    }
+    
+    // args不包含程序名；不以'-'开头的参数作为位置参数保留
+    fn parse(&self, args: &[String]) -> Result<ParsedArgs, ArgError> {
+        let mut parsed = ParsedArgs {
+            aliases: self.specs.iter().map(|spec| (spec.short.clone(), spec.long.clone())).collect(),
+            ..ParsedArgs::default()
+        };
+        
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if !arg.starts_with('-') || arg == "-" {
+                parsed.positional.push(arg.clone());
+                continue;
+            }
+            
+            let (name, inline_value) = match arg.split_once('=') {
+                Some((name, value)) if name.starts_with("--") => (name, Some(value.to_string())),
+                _ => (arg.as_str(), None),
+            };
+            
+            let spec = self
+                .find(name)
+                .ok_or_else(|| ArgError::UnknownArgument(arg.clone()))?;
+            
+            if spec.takes_value {
+                let value = match inline_value {
+                    Some(value) => value,
+                    None => iter
+                        .next()
+                        .filter(|value| !value.starts_with('-') || value.as_str() == "-")
+                        .cloned()
+                        .ok_or_else(|| ArgError::MissingValue(spec.long.clone()))?,
+                };
+                parsed.values.insert(spec.long.clone(), value);
+            } else if inline_value.is_some() {
+                return Err(ArgError::UnknownArgument(arg.clone()));
+            } else {
+                parsed.flags.push(spec.long.clone());
+            }
+        }
+        
+        Ok(parsed)
+    }
+}
+
+// 解析结果，查询时长名和短名都可以使用
+#[derive(Debug, Default)]
+struct ParsedArgs {
+    flags: Vec<String>,
+    values: HashMap<String, String>,
+    positional: Vec<String>,
+    aliases: HashMap<String, String>,
+}
+
+impl ParsedArgs {
+    fn canonical<'a>(&'a self, name: &'a str) -> &'a str {
+        self.aliases.get(name).map(|long| long.as_str()).unwrap_or(name)
+    }
+    
+    fn has_flag(&self, name: &str) -> bool {
+        let name = self.canonical(name);
+        self.flags.iter().any(|flag| flag == name)
+    }
+    
+    fn value_of(&self, name: &str) -> Option<&str> {
+        self.values.get(self.canonical(name)).map(|value| value.as_str())
+    }
+}
+
 // 简单的参数解析
 fn simple_argument_parsing() {
     let args: Vec<String> = env::args().collect();
@@ -182,28 +477,27 @@ fn simple_argument_parsing() {
         return;
     }
     
-    let mut i = 1;
-    while i < args.len() {
-        match args[i].as_str() {
-            "--help" | "-h" => {
+    let parser = ArgParser::new()
+        .flag("--help", "-h")
+        .flag("--version", "-v")
+        .option("--config", "-c");
+    
+    match parser.parse(&args[1..]) {
+        Ok(parsed) => {
+            if parsed.has_flag("--help") {
                 println!("显示帮助信息");
             }
-            "--version" | "-v" => {
+            if parsed.has_flag("--version") {
                 println!("版本: 1.0.0");
             }
-            "--config" | "-c" => {
-                if i + 1 < args.len() {
-                    println!("配置文件: {}", args[i + 1]);
-                    i += 1; // 跳过配置文件参数
-                } else {
-                    println!("错误: --config 需要一个参数");
-                }
+            if let Some(config) = parsed.value_of("--config") {
+                println!("配置文件: {}", config);
             }
-            _ => {
-                println!("未知参数: {}", args[i]);
+            if !parsed.positional.is_empty() {
+                println!("位置参数: {:?}", parsed.positional);
             }
         }
-        i += 1;
+        Err(e) => println!("错误: {}", e),
     }
 }
 
@@ -245,33 +539,105 @@ fn basic_process_execution() {
     check_command_availability();
 }
 
+// 命令探测结果
+#[derive(Debug, PartialEq)]
+enum CommandStatus {
+    // 命令可用，附带版本输出的第一行
+    Available(String),
+    // 命令存在，但执行失败（无法执行或 --version 返回非零）
+    PresentButFailed,
+    // 在PATH中找不到命令
+    NotFound,
+}
+
+// 通过执行 `cmd --version` 判断命令是否可用
+fn probe_command(cmd: &str) -> CommandStatus {
+    let output = match Command::new(cmd)
+        .arg("--version")
+        .stdin(Stdio::null())
+        .output()
+    {
+        Ok(output) => output,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return CommandStatus::NotFound,
+        // 权限不足等错误说明文件存在但无法执行
+        Err(_) => return CommandStatus::PresentButFailed,
+    };
+    
+    if !output.status.success() {
+        return CommandStatus::PresentButFailed;
+    }
+    
+    // 部分程序（如旧版java）把版本信息写到stderr
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let first_line = stdout
+        .lines()
+        .chain(stderr.lines())
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or("未知版本");
+    CommandStatus::Available(first_line.to_string())
+}
+
 // 检查命令可用性
 fn check_command_availability() {
     let commands = ["git", "python3", "node", "cargo", "rustc"];
     
     println!("检查命令可用性:");
     for cmd in &commands {
-        let result = Command::new(cmd)
-            .arg("--version")
-            .output();
-        
-        match result {
-            Ok(output) => {
-                if output.status.success() {
-                    let version = String::from_utf8_lossy(&output.stdout);
-                    let first_line = version.lines().next().unwrap_or("未知版本");
-                    println!("  ✓ {}: {}", cmd, first_line);
-                } else {
-                    println!("  ✗ {}: 命令存在但版本检查失败", cmd);
-                }
-            }
-            Err(_) => {
-                println!("  ✗ {}: 命令不存在或不可执行", cmd);
+        // 先在PATH中查找，找不到就不必启动进程
+        match which(cmd) {
+            Some(path) => println!("  {} 位于 {}", cmd, path.display()),
+            None => {
+                println!("  ✗ {}: 在PATH中找不到", cmd);
+                continue;
             }
         }
+        match probe_command(cmd) {
+            CommandStatus::Available(version) => println!("  ✓ {}: {}", cmd, version),
+            CommandStatus::PresentButFailed => println!("  ✗ {}: 命令存在但版本检查失败", cmd),
+            CommandStatus::NotFound => println!("  ✗ {}: 命令不存在或不可执行", cmd),
+        }
     }
 }
 
+// 在PATH环境变量的目录中查找可执行文件，返回第一个匹配
+fn which(program: &str) -> Option<PathBuf> {
+    which_in(program, &env::var_os("PATH")?)
+}
+
+// 在给定的PATH值中查找，不读取也不修改进程的环境变量
+fn which_in(program: &str, path_var: &std::ffi::OsStr) -> Option<PathBuf> {
+    let suffix = env::consts::EXE_SUFFIX;
+    let file_name = if suffix.is_empty() || program.ends_with(suffix) {
+        program.to_string()
+    } else {
+        format!("{}{}", program, suffix)
+    };
+    
+    // 带路径分隔符的程序名不搜索PATH
+    if Path::new(program).components().count() > 1 {
+        let path = PathBuf::from(file_name);
+        return is_executable(&path).then_some(path);
+    }
+    
+    env::split_paths(path_var)
+        .map(|dir| dir.join(&file_name))
+        .find(|candidate| is_executable(candidate))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path)
+        .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
 // 进程输入输出控制
 fn process_io_control() {
     // 使用管道进行输入输出
@@ -282,6 +648,9 @@ fn process_io_control() {
     
     // 捕获错误输出
     error_capture_example();
+    
+    // 交互式子进程
+    interactive_process_example();
 }
 
 // 管道通信示例
@@ -332,6 +701,127 @@ fn pipe_communication_example() {
     }
 }
 
+// 交互式子进程：按"发送一行、读取一行响应"的方式与REPL类程序对话
+// 后台线程持续读取子进程stdout，send通过通道等待响应，从而支持超时
+struct InteractiveProcess {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    responses: mpsc::Receiver<io::Result<String>>,
+    reader: Option<thread::JoinHandle<()>>,
+    timeout: Duration,
+}
+
+impl InteractiveProcess {
+    fn spawn(command: &mut Command, timeout: Duration) -> io::Result<Self> {
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        
+        let stdin = child.stdin.take();
+        let stdout = child.stdout.take().ok_or_else(|| io::Error::other("无法获取子进程stdout"))?;
+        
+        let (tx, rx) = mpsc::channel();
+        let reader = thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                if tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        
+        Ok(InteractiveProcess {
+            child,
+            stdin,
+            responses: rx,
+            reader: Some(reader),
+            timeout,
+        })
+    }
+    
+    // 写入一行并等待子进程输出的下一行
+    fn send(&mut self, line: &str) -> io::Result<String> {
+        let stdin = self.stdin.as_mut().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::BrokenPipe, "子进程stdin已关闭")
+        })?;
+        
+        // 丢弃之前超时的请求迟到的响应，否则之后每次读到的都是上一轮的结果
+        while self.responses.try_recv().is_ok() {}
+        
+        writeln!(stdin, "{}", line)?;
+        stdin.flush()?;
+        
+        match self.responses.recv_timeout(self.timeout) {
+            Ok(response) => response,
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                Err(io::Error::new(io::ErrorKind::TimedOut, "等待子进程响应超时"))
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                Err(io::Error::new(io::ErrorKind::UnexpectedEof, "子进程已关闭输出"))
+            }
+        }
+    }
+    
+    // 关闭stdin并等待子进程退出，超时后强制终止
+    fn close(mut self) -> io::Result<ExitStatus> {
+        self.shutdown()
+    }
+    
+    fn shutdown(&mut self) -> io::Result<ExitStatus> {
+        drop(self.stdin.take());
+        
+        let start = std::time::Instant::now();
+        let status = loop {
+            if let Some(status) = self.child.try_wait()? {
+                break status;
+            }
+            if start.elapsed() > self.timeout {
+                let _ = self.child.kill();
+                break self.child.wait()?;
+            }
+            thread::sleep(Duration::from_millis(10));
+        };
+        
+        if let Some(reader) = self.reader.take() {
+            let _ = reader.join();
+        }
+        Ok(status)
+    }
+}
+
+impl Drop for InteractiveProcess {
+    fn drop(&mut self) {
+        if self.reader.is_some() {
+            let _ = self.shutdown();
+        }
+    }
+}
+
+// 交互式子进程示例
+fn interactive_process_example() {
+    println!("交互式子进程示例:");
+    
+    let mut process = match InteractiveProcess::spawn(&mut Command::new("cat"), Duration::from_secs(2)) {
+        Ok(process) => process,
+        Err(e) => {
+            println!("  启动cat进程失败: {}", e);
+            return;
+        }
+    };
+    
+    for question in ["第一轮", "第二轮"] {
+        match process.send(question) {
+            Ok(answer) => println!("  发送: {} -> 响应: {}", question, answer),
+            Err(e) => println!("  交互失败: {}", e),
+        }
+    }
+    
+    match process.close() {
+        Ok(status) => println!("  交互进程退出状态: {}", status),
+        Err(e) => println!("  关闭交互进程失败: {}", e),
+    }
+}
+
 // 输出重定向示例
 fn output_redirection_example() {
     println!("输出重定向示例:");
@@ -402,38 +892,13 @@ fn process_pipes_and_redirection() {
 fn command_chain_example() {
     println!("命令链示例 (ls | grep .rs):");
     
-    // 第一个命令：ls
-    let ls_child = Command::new("ls")
-        .arg(".")
-        .stdout(Stdio::piped())
-        .spawn();
+    // ls的输出连接到grep的输入
+    let mut ls = Command::new("ls");
+    ls.arg(".");
+    let mut grep = Command::new("grep");
+    grep.arg(".rs");
     
-    let ls_child = match ls_child {
-        Ok(child) => child,
-        Err(e) => {
-            println!("  启动ls失败: {}", e);
-            return;
-        }
-    };
-    
-    // 第二个命令：grep，使用第一个命令的输出作为输入
-    let grep_child = Command::new("grep")
-        .arg(".rs")
-        .stdin(Stdio::from(ls_child.stdout.unwrap()))
-        .stdout(Stdio::piped())
-        .spawn();
-    
-    let grep_child = match grep_child {
-        Ok(child) => child,
-        Err(e) => {
-            println!("  启动grep失败: {}", e);
-            return;
-        }
-    };
-    
-    // 读取最终输出
-    let output = grep_child.wait_with_output();
-    match output {
+    match Pipeline::new().add(ls).add(grep).run() {
         Ok(output) => {
             if output.status.success() {
                 let result = String::from_utf8_lossy(&output.stdout);
@@ -445,7 +910,63 @@ fn command_chain_example() {
                 println!("  grep命令失败");
             }
         }
-        Err(e) => println!("  等待grep输出失败: {}", e),
+        Err(e) => println!("  执行命令链失败: {}", e),
+    }
+}
+
+// 进程管道：前一个命令的标准输出连接到后一个命令的标准输入，相当于shell中的 a | b | c
+struct Pipeline {
+    commands: Vec<Command>,
+}
+
+impl Pipeline {
+    fn new() -> Self {
+        Pipeline { commands: Vec::new() }
+    }
+    
+    fn add(mut self, command: Command) -> Self {
+        self.commands.push(command);
+        self
+    }
+    
+    // 返回最后一个命令的输出和退出状态；任何命令启动失败时结束已启动的进程并返回错误
+    fn run(self) -> io::Result<Output> {
+        if self.commands.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "管道中没有命令"));
+        }
+        
+        let count = self.commands.len();
+        let mut children: Vec<Child> = Vec::with_capacity(count);
+        for (i, mut command) in self.commands.into_iter().enumerate() {
+            if let Some(previous) = children.last_mut() {
+                if let Some(stdout) = previous.stdout.take() {
+                    command.stdin(Stdio::from(stdout));
+                }
+            }
+            command.stdout(Stdio::piped());
+            if i == count - 1 {
+                command.stderr(Stdio::piped());
+            }
+            
+            match command.spawn() {
+                Ok(child) => children.push(child),
+                Err(e) => {
+                    for mut child in children {
+                        let _ = child.kill();
+                        let _ = child.wait();
+                    }
+                    return Err(e);
+                }
+            }
+        }
+        
+        let last = children.pop().expect("至少有一个命令");
+        let output = last.wait_with_output()?;
+        // 前面的命令在下游读完后会自行结束，回收它们避免僵尸进程
+        for mut child in children {
+            child.wait()?;
+        }
+        Ok(output)
     }
 }
 
@@ -534,47 +1055,74 @@ fn non_blocking_process_example() {
     }
 }
 
-// 进程超时控制
-fn process_timeout_example() {
-    println!("进程超时控制示例:");
+// 带超时的命令执行结果
+#[derive(Debug)]
+enum TimedOutput {
+    Completed(Output),
+    TimedOut,
+}
+
+// 执行命令并捕获输出，超时后杀死并回收子进程
+// stdout/stderr由后台线程持续读取，避免输出过多时填满管道导致子进程阻塞。
+// 子进程退出后，读取管道同样受timeout限制：留在后台的后代进程可能一直持有管道
+fn run_with_timeout(cmd: &mut Command, timeout: Duration) -> io::Result<TimedOutput> {
+    let mut child = cmd
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
     
-    // 启动可能长时间运行的进程
-    let mut child = match Command::new("sleep").arg("10").spawn() {
-        Ok(child) => child,
-        Err(_) => {
-            println!("  sleep命令不可用，跳过此示例");
-            return;
-        }
-    };
+    let stdout_rx = child.stdout.take().map(drain_pipe);
+    let stderr_rx = child.stderr.take().map(drain_pipe);
     
-    let timeout = Duration::from_secs(1);
     let start = std::time::Instant::now();
-    
-    loop {
-        match child.try_wait() {
-            Ok(Some(status)) => {
-                println!("  进程正常完成: {}", status);
-                return;
-            }
-            Ok(None) => {
-                if start.elapsed() > timeout {
-                    println!("  进程超时，强制终止");
-                    if let Err(e) = child.kill() {
-                        println!("  终止进程失败: {}", e);
-                    } else {
-                        let _ = child.wait(); // 清理僵尸进程
-                        println!("  进程已终止");
-                    }
-                    return;
-                }
-            }
-            Err(e) => {
-                println!("  检查进程状态失败: {}", e);
-                return;
-            }
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        
+        if start.elapsed() >= timeout {
+            let _ = child.kill();
+            child.wait()?; // 回收僵尸进程
+            // 子进程的后代可能仍持有管道，不等待读取线程结束
+            return Ok(TimedOutput::TimedOut);
         }
         
-        thread::sleep(Duration::from_millis(100));
+        thread::sleep(Duration::from_millis(10));
+    };
+    
+    // 读取线程读到EOF后通过通道交回输出；截止时间过了仍未读完就按超时处理
+    let collect = |rx: Option<mpsc::Receiver<Vec<u8>>>| match rx {
+        Some(rx) => rx.recv_timeout(timeout.saturating_sub(start.elapsed())).ok(),
+        None => Some(Vec::new()),
+    };
+    let (stdout, stderr) = match (collect(stdout_rx), collect(stderr_rx)) {
+        (Some(stdout), Some(stderr)) => (stdout, stderr),
+        _ => return Ok(TimedOutput::TimedOut),
+    };
+    
+    Ok(TimedOutput::Completed(Output { status, stdout, stderr }))
+}
+
+// 在后台线程中把管道读到结束，读完后把内容发到返回的通道
+fn drain_pipe<R: Read + Send + 'static>(mut pipe: R) -> mpsc::Receiver<Vec<u8>> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        let _ = pipe.read_to_end(&mut buffer);
+        let _ = tx.send(buffer);
+    });
+    rx
+}
+
+// 进程超时控制
+fn process_timeout_example() {
+    println!("进程超时控制示例:");
+    
+    // 启动可能长时间运行的进程
+    match run_with_timeout(Command::new("sleep").arg("10"), Duration::from_secs(1)) {
+        Ok(TimedOutput::Completed(output)) => println!("  进程正常完成: {}", output.status),
+        Ok(TimedOutput::TimedOut) => println!("  进程超时，已强制终止"),
+        Err(_) => println!("  sleep命令不可用，跳过此示例"),
     }
 }
 
@@ -690,13 +1238,11 @@ fn working_directory_operations() {
         Err(e) => println!("获取工作目录失败: {}", e),
     }
     
-    // 改变工作目录
-    let original_dir = env::current_dir().unwrap();
-    
-    // 尝试切换到上级目录
-    if let Some(parent) = original_dir.parent() {
-        match env::set_current_dir(parent) {
-            Ok(_) => {
+    // 改变工作目录：守卫离开作用域时自动切换回原目录，提前返回或panic也不例外
+    let parent = env::current_dir().ok().and_then(|dir| dir.parent().map(Path::to_path_buf));
+    if let Some(parent) = parent {
+        match CwdGuard::change_to(&parent) {
+            Ok(guard) => {
                 println!("切换到上级目录: {}", parent.display());
                 
                 // 验证切换
@@ -705,10 +1251,9 @@ fn working_directory_operations() {
                 }
                 
                 // 切换回原目录
-                if let Err(e) = env::set_current_dir(&original_dir) {
-                    println!("切换回原目录失败: {}", e);
-                } else {
-                    println!("已切换回原目录");
+                drop(guard);
+                if let Ok(dir) = env::current_dir() {
+                    println!("已切换回原目录: {}", dir.display());
                 }
             }
             Err(e) => println!("切换目录失败: {}", e),
@@ -719,6 +1264,26 @@ fn working_directory_operations() {
     working_directory_inheritance();
 }
 
+// 切换工作目录的守卫，drop时切换回创建时的目录
+struct CwdGuard {
+    original: PathBuf,
+}
+
+impl CwdGuard {
+    fn change_to<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let original = env::current_dir()?;
+        env::set_current_dir(path)?;
+        Ok(CwdGuard { original })
+    }
+}
+
+impl Drop for CwdGuard {
+    fn drop(&mut self) {
+        // drop中无法返回错误，原目录被删除时只能放弃
+        let _ = env::set_current_dir(&self.original);
+    }
+}
+
 // 工作目录继承
 fn working_directory_inheritance() {
     println!("工作目录继承测试:");
@@ -1085,4 +1650,327 @@ mod tests {
         assert!(!env::consts::ARCH.is_empty());
         assert!(!env::consts::FAMILY.is_empty());
     }
-}
\ No newline at end of file
+    
+    fn to_args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+    
+    fn test_parser() -> ArgParser {
+        ArgParser::new()
+            .flag("--help", "-h")
+            .flag("--verbose", "-v")
+            .option("--config", "-c")
+    }
+    
+    #[test]
+    fn test_arg_parser_option_and_flags() {
+        let parsed = test_parser()
+            .parse(&to_args(&["--config", "foo.toml", "-v", "input.txt"]))
+            .unwrap();
+        assert_eq!(parsed.value_of("--config"), Some("foo.toml"));
+        assert_eq!(parsed.value_of("-c"), Some("foo.toml"));
+        assert!(parsed.has_flag("--verbose"));
+        assert!(parsed.has_flag("-v"));
+        assert!(!parsed.has_flag("--help"));
+        assert_eq!(parsed.positional, vec!["input.txt"]);
+        
+        let parsed = test_parser().parse(&to_args(&["-c", "a.toml", "--config=b.toml"])).unwrap();
+        assert_eq!(parsed.value_of("--config"), Some("b.toml"));
+    }
+    
+    #[test]
+    fn test_arg_parser_missing_value() {
+        assert_eq!(
+            test_parser().parse(&to_args(&["--config"])).unwrap_err(),
+            ArgError::MissingValue("--config".to_string())
+        );
+        // 下一个参数是另一个选项时同样视为缺少值
+        assert_eq!(
+            test_parser().parse(&to_args(&["-c", "--help"])).unwrap_err(),
+            ArgError::MissingValue("--config".to_string())
+        );
+    }
+    
+    #[test]
+    fn test_arg_parser_unknown_flag() {
+        assert_eq!(
+            test_parser().parse(&to_args(&["--help", "--colour"])).unwrap_err(),
+            ArgError::UnknownArgument("--colour".to_string())
+        );
+        assert_eq!(
+            test_parser().parse(&to_args(&["--help=yes"])).unwrap_err(),
+            ArgError::UnknownArgument("--help=yes".to_string())
+        );
+    }
+    
+    #[test]
+    fn test_probe_command_available() {
+        // 运行测试的环境中必然有cargo
+        match probe_command("cargo") {
+            CommandStatus::Available(version) => assert!(version.starts_with("cargo")),
+            other => panic!("cargo应该可用: {:?}", other),
+        }
+    }
+    
+    #[test]
+    fn test_probe_command_not_found() {
+        assert_eq!(probe_command("definitely-not-a-real-command-xyz"), CommandStatus::NotFound);
+    }
+    
+    #[cfg(unix)]
+    #[test]
+    fn test_probe_command_present_but_failed() {
+        // false 无视参数，总是以非零状态退出
+        assert_eq!(probe_command("false"), CommandStatus::PresentButFailed);
+        
+        // 目录存在但不可执行，属于NotFound以外的启动错误
+        let dir = env::temp_dir();
+        assert_eq!(probe_command(dir.to_str().unwrap()), CommandStatus::PresentButFailed);
+    }
+    
+    #[test]
+    fn test_run_with_timeout_times_out() {
+        let start = std::time::Instant::now();
+        let result = run_with_timeout(Command::new("sleep").arg("5"), Duration::from_millis(200)).unwrap();
+        assert!(matches!(result, TimedOutput::TimedOut));
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+    
+    #[test]
+    fn test_run_with_timeout_completes() {
+        match run_with_timeout(Command::new("echo").arg("hi"), Duration::from_secs(5)).unwrap() {
+            TimedOutput::Completed(output) => {
+                assert!(output.status.success());
+                assert_eq!(String::from_utf8_lossy(&output.stdout), "hi\n");
+            }
+            TimedOutput::TimedOut => panic!("echo不应该超时"),
+        }
+    }
+    
+    #[cfg(unix)]
+    #[test]
+    fn test_run_with_timeout_large_output() {
+        // 输出远大于管道缓冲区，必须边运行边读取
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "head -c 1000000 /dev/zero; echo err >&2"]);
+        match run_with_timeout(&mut cmd, Duration::from_secs(5)).unwrap() {
+            TimedOutput::Completed(output) => {
+                assert_eq!(output.stdout.len(), 1_000_000);
+                assert_eq!(output.stderr, b"err\n");
+            }
+            TimedOutput::TimedOut => panic!("大量输出不应导致死锁"),
+        }
+    }
+    
+    #[cfg(unix)]
+    #[test]
+    fn test_run_with_timeout_background_descendant_holds_pipe() {
+        // sh立即退出，但后台的sleep继承了stdout，管道一直读不到EOF
+        let start = std::time::Instant::now();
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "sleep 100 & echo hi"]);
+        let result = run_with_timeout(&mut cmd, Duration::from_millis(300)).unwrap();
+        assert!(matches!(result, TimedOutput::TimedOut));
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+    
+    #[test]
+    fn test_interactive_process_roundtrip() {
+        let mut process = InteractiveProcess::spawn(&mut Command::new("cat"), Duration::from_secs(2)).unwrap();
+        assert_eq!(process.send("hello").unwrap(), "hello");
+    }
+    
+    #[test]
+    fn test_interactive_process_multiple_rounds() {
+        let mut process = InteractiveProcess::spawn(&mut Command::new("cat"), Duration::from_secs(2)).unwrap();
+        for i in 0..5 {
+            let line = format!("line {}", i);
+            assert_eq!(process.send(&line).unwrap(), line);
+        }
+        
+        let status = process.close().unwrap();
+        assert!(status.success());
+    }
+    
+    #[test]
+    fn test_interactive_process_timeout() {
+        // sh读取输入但从不输出，send应超时而不是永久阻塞
+        let mut process = InteractiveProcess::spawn(
+            Command::new("sh").arg("-c").arg("cat > /dev/null"),
+            Duration::from_millis(200),
+        ).unwrap();
+        
+        let err = process.send("no answer").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        assert!(process.close().unwrap().success());
+    }
+    
+    #[test]
+    fn test_interactive_process_late_reply_discarded() {
+        // 第一行延迟响应，之后原样回显
+        let mut process = InteractiveProcess::spawn(
+            Command::new("sh").arg("-c").arg("read line; sleep 0.3; echo \"$line\"; exec cat"),
+            Duration::from_millis(100),
+        ).unwrap();
+        
+        let err = process.send("late").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        
+        // 等迟到的响应到达后，下一次send仍应拿到自己的响应
+        thread::sleep(Duration::from_millis(500));
+        assert_eq!(process.send("next").unwrap(), "next");
+        assert_eq!(process.send("again").unwrap(), "again");
+    }
+    
+    #[test]
+    fn test_env_parsed() {
+        env::set_var("ENV_PARSED_PORT", "8080");
+        assert_eq!(env_parsed::<u16>("ENV_PARSED_PORT"), Ok(Some(8080)));
+        env::remove_var("ENV_PARSED_PORT");
+        
+        env::remove_var("ENV_PARSED_UNSET");
+        assert_eq!(env_parsed::<u16>("ENV_PARSED_UNSET"), Ok(None));
+        assert_eq!(env_or("ENV_PARSED_UNSET", 3u16), Ok(3));
+        
+        env::set_var("ENV_PARSED_BAD", "99999");
+        match env_parsed::<u16>("ENV_PARSED_BAD") {
+            Err(EnvError::Invalid { key, value, .. }) => {
+                assert_eq!(key, "ENV_PARSED_BAD");
+                assert_eq!(value, "99999");
+            }
+            other => panic!("期望解析错误，实际: {:?}", other),
+        }
+        assert!(env_or("ENV_PARSED_BAD", 3u16).is_err());
+        env::remove_var("ENV_PARSED_BAD");
+    }
+    
+    #[test]
+    fn test_load_dotenv() {
+        let path = env::temp_dir().join(format!("test_{}.env", std::process::id()));
+        // 每行前面的缩进会被忽略
+        let content = r#"
+            # 注释行
+            DOTENV_PLAIN=hello # 行尾注释
+            export DOTENV_EXPORTED=yes
+            DOTENV_DOUBLE="two words \"quoted\" # not comment"
+            DOTENV_SINGLE='raw \n value'
+            DOTENV_EXISTING=from_file
+            DOTENV_EMPTY= # 只有注释
+            DOTENV_QUOTED_COMMENT="value" # 引号后的注释
+        "#;
+        std::fs::write(&path, content).unwrap();
+        env::set_var("DOTENV_EXISTING", "original");
+        
+        assert_eq!(load_dotenv(&path, false).unwrap(), 6);
+        assert_eq!(env::var("DOTENV_PLAIN").unwrap(), "hello");
+        assert_eq!(env::var("DOTENV_EXPORTED").unwrap(), "yes");
+        assert_eq!(env::var("DOTENV_DOUBLE").unwrap(), "two words \"quoted\" # not comment");
+        assert_eq!(env::var("DOTENV_SINGLE").unwrap(), "raw \\n value");
+        assert_eq!(env::var("DOTENV_EXISTING").unwrap(), "original");
+        assert_eq!(env::var("DOTENV_EMPTY").unwrap(), "");
+        assert_eq!(env::var("DOTENV_QUOTED_COMMENT").unwrap(), "value");
+        
+        // 允许覆盖时已有变量也被替换
+        assert_eq!(load_dotenv(&path, true).unwrap(), 7);
+        assert_eq!(env::var("DOTENV_EXISTING").unwrap(), "from_file");
+        
+        std::fs::write(&path, "DOTENV_OK=1\nnot a valid line\n").unwrap();
+        let err = load_dotenv(&path, true).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("第2行"));
+        // 出错时前面的行也不会生效
+        assert!(env::var_os("DOTENV_OK").is_none());
+        
+        // 闭合引号后面不能有其他内容
+        assert!(parse_dotenv_line(r#"K="a"b"#).is_err());
+        assert!(parse_dotenv_line("K='a' b").is_err());
+        assert_eq!(parse_dotenv_line("K='a'  # c").unwrap(), Some(("K".to_string(), "a".to_string())));
+        
+        for key in ["DOTENV_PLAIN", "DOTENV_EXPORTED", "DOTENV_DOUBLE", "DOTENV_SINGLE", "DOTENV_EXISTING", "DOTENV_EMPTY", "DOTENV_QUOTED_COMMENT", "DOTENV_OK"] {
+            env::remove_var(key);
+        }
+        let _ = std::fs::remove_file(&path);
+    }
+    
+    #[test]
+    fn test_cwd_guard_restores_on_early_return_and_panic() {
+        let original = env::current_dir().unwrap();
+        let target = env::temp_dir().canonicalize().unwrap();
+        
+        fn visit(target: &Path, early: bool) -> io::Result<PathBuf> {
+            let _guard = CwdGuard::change_to(target)?;
+            let inside = env::current_dir()?;
+            if early {
+                return Ok(inside);
+            }
+            std::fs::metadata(".")?;
+            Ok(inside)
+        }
+        
+        assert_eq!(visit(&target, true).unwrap(), target);
+        assert_eq!(env::current_dir().unwrap(), original);
+        assert_eq!(visit(&target, false).unwrap(), target);
+        assert_eq!(env::current_dir().unwrap(), original);
+        
+        let result = std::panic::catch_unwind(|| {
+            let _guard = CwdGuard::change_to(&target).unwrap();
+            panic!("作用域中途panic");
+        });
+        assert!(result.is_err());
+        assert_eq!(env::current_dir().unwrap(), original);
+        
+        // 切换失败时不改变当前目录
+        assert!(CwdGuard::change_to("/nonexistent/dir/for/cwd/guard").is_err());
+        assert_eq!(env::current_dir().unwrap(), original);
+    }
+    
+    #[cfg(unix)]
+    #[test]
+    fn test_which() {
+        use std::os::unix::fs::PermissionsExt;
+        
+        assert!(which("sh").is_some());
+        assert!(which("definitely-not-a-real-command-xyz").is_none());
+        
+        // 在临时PATH中放一个可执行文件和一个不可执行文件
+        let dir = env::temp_dir().join(format!("which_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let tool = dir.join("fixture-tool");
+        let plain = dir.join("fixture-plain");
+        std::fs::write(&tool, "#!/bin/sh\n").unwrap();
+        std::fs::write(&plain, "data").unwrap();
+        std::fs::set_permissions(&tool, std::fs::Permissions::from_mode(0o755)).unwrap();
+        std::fs::set_permissions(&plain, std::fs::Permissions::from_mode(0o644)).unwrap();
+        
+        let path_var = env::join_paths(["/nonexistent/bin".as_ref(), dir.as_path()]).unwrap();
+        assert_eq!(which_in("fixture-tool", &path_var), Some(tool.clone()));
+        assert_eq!(which_in("fixture-plain", &path_var), None);
+        assert_eq!(which_in("sh", &path_var), None);
+        assert_eq!(which_in(tool.to_str().unwrap(), &path_var), Some(tool));
+        
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+    
+    #[test]
+    fn test_pipeline_connects_commands() {
+        let mut printf = Command::new("printf");
+        printf.arg("a\\nb\\nc\\n");
+        let mut grep = Command::new("grep");
+        grep.arg("b");
+        
+        let output = Pipeline::new().add(printf).add(grep).run().unwrap();
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "b\n");
+        
+        // 中间的命令启动失败时返回错误
+        let mut printf = Command::new("printf");
+        printf.arg("x");
+        let result = Pipeline::new()
+            .add(printf)
+            .add(Command::new("definitely-not-a-real-command-xyz"))
+            .run();
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::NotFound);
+        
+        assert!(Pipeline::new().run().is_err());
+    }
+}
@@ -30,7 +30,7 @@
 use std::fmt;
 use std::str::FromStr;
 use std::collections::HashMap;
-use std::io::{self, Write};
+use std::io::{self, BufRead, Read, Write};
 
 fn main() {
     println!("=== Rust标准库数据序列化详解 ===");
@@ -283,46 +283,121 @@ fn string_conversion() {
     complex_string_representation();
 }
 
-// 自定义FromStr实现
-fn custom_fromstr_example() {
-    #[derive(Debug, PartialEq)]
-    struct Color {
-        r: u8,
-        g: u8,
-        b: u8,
+// 颜色解析错误
+#[derive(Debug, PartialEq)]
+enum ColorParseError {
+    // 十六进制位数不是3或6，或rgb()分量个数不是3
+    BadLength(usize),
+    // 含有非法的十六进制数字或超出范围的分量
+    BadDigit(String),
+    // 未知的颜色名称
+    UnknownName(String),
+}
+
+impl fmt::Display for ColorParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColorParseError::BadLength(len) => write!(f, "长度错误: {}，应为 #RGB、#RRGGBB 或 rgb(r,g,b)", len),
+            ColorParseError::BadDigit(digit) => write!(f, "无效的数字: {}", digit),
+            ColorParseError::UnknownName(name) => write!(f, "未知的颜色名称: {}", name),
+        }
+    }
+}
+
+impl std::error::Error for ColorParseError {}
+
+// RGB颜色，支持 #RRGGBB、#RGB、rgb(r,g,b) 和常用颜色名
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct Color {
+    r: u8,
+    g: u8,
+    b: u8,
+}
+
+impl Color {
+    fn to_hex(self) -> String {
+        format!("#{:02X}{:02X}{:02X}", self.r, self.g, self.b)
     }
     
-    impl FromStr for Color {
-        type Err = String;
+    fn from_hex(hex: &str) -> Result<Self, ColorParseError> {
+        if let Some(c) = hex.chars().find(|c| !c.is_ascii_hexdigit()) {
+            return Err(ColorParseError::BadDigit(c.to_string()));
+        }
         
-        fn from_str(s: &str) -> Result<Self, Self::Err> {
-            if !s.starts_with('#') || s.len() != 7 {
-                return Err("颜色格式错误，应为 #RRGGBB".to_string());
+        let parse = |digits: &str| u8::from_str_radix(digits, 16).unwrap();
+        match hex.len() {
+            // 简写形式，每位重复一次：#F80 -> #FF8800
+            3 => {
+                let nibble = |i: usize| parse(&hex[i..i + 1]) * 0x11;
+                Ok(Color { r: nibble(0), g: nibble(1), b: nibble(2) })
             }
-            
-            let r = u8::from_str_radix(&s[1..3], 16)
-                .map_err(|_| "红色分量解析失败")?;
-            let g = u8::from_str_radix(&s[3..5], 16)
-                .map_err(|_| "绿色分量解析失败")?;
-            let b = u8::from_str_radix(&s[5..7], 16)
-                .map_err(|_| "蓝色分量解析失败")?;
-            
-            Ok(Color { r, g, b })
+            6 => Ok(Color {
+                r: parse(&hex[0..2]),
+                g: parse(&hex[2..4]),
+                b: parse(&hex[4..6]),
+            }),
+            len => Err(ColorParseError::BadLength(len)),
         }
     }
     
-    impl fmt::Display for Color {
-        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-            write!(f, "RGB({}, {}, {})", self.r, self.g, self.b)
+    fn from_rgb_function(args: &str) -> Result<Self, ColorParseError> {
+        let parts: Vec<&str> = args.split(',').map(|part| part.trim()).collect();
+        if parts.len() != 3 {
+            return Err(ColorParseError::BadLength(parts.len()));
         }
+        
+        let component = |part: &str| {
+            part.parse::<u8>()
+                .map_err(|_| ColorParseError::BadDigit(part.to_string()))
+        };
+        Ok(Color {
+            r: component(parts[0])?,
+            g: component(parts[1])?,
+            b: component(parts[2])?,
+        })
     }
+}
+
+impl FromStr for Color {
+    type Err = ColorParseError;
     
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        
+        if let Some(hex) = s.strip_prefix('#') {
+            return Color::from_hex(hex);
+        }
+        
+        let lower = s.to_ascii_lowercase();
+        if let Some(args) = lower.strip_prefix("rgb(").and_then(|rest| rest.strip_suffix(')')) {
+            return Color::from_rgb_function(args);
+        }
+        
+        match lower.as_str() {
+            "red" => Ok(Color { r: 255, g: 0, b: 0 }),
+            "green" => Ok(Color { r: 0, g: 128, b: 0 }),
+            "blue" => Ok(Color { r: 0, g: 0, b: 255 }),
+            "black" => Ok(Color { r: 0, g: 0, b: 0 }),
+            "white" => Ok(Color { r: 255, g: 255, b: 255 }),
+            _ => Err(ColorParseError::UnknownName(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "RGB({}, {}, {})", self.r, self.g, self.b)
+    }
+}
+
+// 自定义FromStr实现
+fn custom_fromstr_example() {
     println!("自定义FromStr示例：");
     
-    let colors = ["#FF0000", "#00FF00", "#0000FF", "#INVALID"];
+    let colors = ["#FF0000", "#0F0", "rgb(0, 0, 255)", "white", "#INVALID", "purple"];
     for color_str in &colors {
         match color_str.parse::<Color>() {
-            Ok(color) => println!("  {} -> {}", color_str, color),
+            Ok(color) => println!("  {} -> {} ({})", color_str, color, color.to_hex()),
             Err(e) => println!("  {} -> 错误: {}", color_str, e),
         }
     }
@@ -401,6 +476,13 @@ fn binary_data_handling() {
     let reconstructed_float = f64::from_le_bytes(float_bytes);
     println!("  重建的浮点数: {}", reconstructed_float);
     
+    // 十六进制文本与字节互转
+    println!("  十六进制: {} / {}", hex_encode(&float_bytes, false), hex_encode(&float_bytes, true));
+    match hex_decode("0x40 09 21 FB") {
+        Ok(decoded) => println!("  十六进制解析: {:02X?}", decoded),
+        Err(e) => println!("  十六进制解析失败: {}", e),
+    }
+    
     // 字符串的UTF-8字节表示
     let text = "Hello, 世界! 🦀";
     let text_bytes = text.as_bytes();
@@ -412,37 +494,250 @@ fn binary_data_handling() {
         Err(e) => println!("  字符串重建失败: {}", e),
     }
     
+    // Base64：让二进制数据可以放进JSON、CSV等文本格式
+    let encoded = base64_encode(text_bytes);
+    println!("  Base64编码: {}", encoded);
+    match base64_decode(&encoded) {
+        Ok(decoded) => println!("  Base64解码: '{}'", String::from_utf8_lossy(&decoded)),
+        Err(e) => println!("  Base64解码失败: {}", e),
+    }
+    let url_encoded = base64_url_encode(&[0xFB, 0xFF, 0xBF]);
+    println!("  URL安全Base64: {} -> {:02X?}", url_encoded, base64_url_decode(&url_encoded));
+    
     // 复杂数据结构的二进制表示
     binary_struct_serialization();
+    
+    // 按位打包
+    bit_packing_example();
+    
+    // 变长整数
+    varint_example();
 }
 
-// 结构体的二进制序列化
-fn binary_struct_serialization() {
-    #[repr(C)]
-    #[derive(Debug, Clone, Copy)]
-    struct Point3D {
-        x: f32,
-        y: f32,
-        z: f32,
+// 字节序
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Endian {
+    Little,
+    Big,
+}
+
+// 二进制解码错误
+#[derive(Debug, PartialEq)]
+enum DecodeError {
+    // 字节数不是单个元素大小的整数倍
+    LengthMismatch { len: usize, element_size: usize },
+    // 文本编码中出现了不属于字母表的字符，position为字节偏移
+    InvalidCharacter { position: usize, character: char },
+    // 文本编码的长度不合法
+    InvalidLength(usize),
+    // Base64填充不正确
+    InvalidPadding,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::LengthMismatch { len, element_size } => {
+                write!(f, "数据长度 {} 不是 {} 的整数倍", len, element_size)
+            }
+            DecodeError::InvalidCharacter { position, character } => {
+                write!(f, "位置 {} 处的字符 {:?} 无效", position, character)
+            }
+            DecodeError::InvalidLength(len) => write!(f, "编码长度 {} 不合法", len),
+            DecodeError::InvalidPadding => write!(f, "填充不正确"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Point3D {
+    x: f32,
+    y: f32,
+    z: f32,
+}
+
+impl Point3D {
+    const SIZE: usize = 12;
+    
+    fn to_bytes(self) -> [u8; 12] {
+        self.to_bytes_with(Endian::Little)
     }
     
-    impl Point3D {
-        fn to_bytes(&self) -> [u8; 12] {
-            let mut bytes = [0u8; 12];
-            bytes[0..4].copy_from_slice(&self.x.to_le_bytes());
-            bytes[4..8].copy_from_slice(&self.y.to_le_bytes());
-            bytes[8..12].copy_from_slice(&self.z.to_le_bytes());
-            bytes
-        }
+    fn from_bytes(bytes: &[u8; 12]) -> Self {
+        Self::from_bytes_with(bytes, Endian::Little)
+    }
+    
+    fn to_bytes_with(self, endian: Endian) -> [u8; 12] {
+        let encode = |v: f32| match endian {
+            Endian::Little => v.to_le_bytes(),
+            Endian::Big => v.to_be_bytes(),
+        };
+        
+        let mut bytes = [0u8; 12];
+        bytes[0..4].copy_from_slice(&encode(self.x));
+        bytes[4..8].copy_from_slice(&encode(self.y));
+        bytes[8..12].copy_from_slice(&encode(self.z));
+        bytes
+    }
+    
+    fn from_bytes_with(bytes: &[u8; 12], endian: Endian) -> Self {
+        let decode = |i: usize| {
+            let raw = [bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]];
+            match endian {
+                Endian::Little => f32::from_le_bytes(raw),
+                Endian::Big => f32::from_be_bytes(raw),
+            }
+        };
+        Point3D { x: decode(0), y: decode(4), z: decode(8) }
+    }
+}
+
+// 把一组点按指定字节序依次追加到out
+fn write_points(points: &[Point3D], out: &mut Vec<u8>, endian: Endian) {
+    out.reserve(points.len() * Point3D::SIZE);
+    for point in points {
+        out.extend_from_slice(&point.to_bytes_with(endian));
+    }
+}
+
+fn read_points(bytes: &[u8], endian: Endian) -> Result<Vec<Point3D>, DecodeError> {
+    if !bytes.len().is_multiple_of(Point3D::SIZE) {
+        return Err(DecodeError::LengthMismatch {
+            len: bytes.len(),
+            element_size: Point3D::SIZE,
+        });
+    }
+    
+    Ok(bytes
+        .chunks_exact(Point3D::SIZE)
+        .map(|chunk| Point3D::from_bytes_with(chunk.try_into().unwrap(), endian))
+        .collect())
+}
+
+const BASE64_STANDARD: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const BASE64_URL_SAFE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+// 标准Base64（RFC 4648），输出带=填充
+fn base64_encode(data: &[u8]) -> String {
+    base64_encode_with(data, BASE64_STANDARD, true)
+}
+
+// 标准Base64解码，要求带完整的填充
+fn base64_decode(s: &str) -> Result<Vec<u8>, DecodeError> {
+    base64_decode_with(s, BASE64_STANDARD, true)
+}
+
+// URL安全的Base64：用-和_代替+和/，输出不带填充，可以直接放进URL和文件名
+fn base64_url_encode(data: &[u8]) -> String {
+    base64_encode_with(data, BASE64_URL_SAFE, false)
+}
+
+// URL安全的Base64解码，填充可有可无
+fn base64_url_decode(s: &str) -> Result<Vec<u8>, DecodeError> {
+    base64_decode_with(s, BASE64_URL_SAFE, false)
+}
+
+// 每3个字节拆成4个6位的组
+fn base64_encode_with(data: &[u8], alphabet: &[u8; 64], pad: bool) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let n = ((chunk[0] as u32) << 16)
+            | ((*chunk.get(1).unwrap_or(&0) as u32) << 8)
+            | *chunk.get(2).unwrap_or(&0) as u32;
         
-        fn from_bytes(bytes: &[u8; 12]) -> Self {
-            let x = f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
-            let y = f32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
-            let z = f32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]);
-            Point3D { x, y, z }
+        // n个字节对应n+1个字符
+        for i in 0..=chunk.len() {
+            out.push(alphabet[((n >> (18 - 6 * i)) & 0x3F) as usize] as char);
+        }
+        if pad {
+            for _ in chunk.len()..3 {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+fn base64_decode_with(
+    s: &str,
+    alphabet: &[u8; 64],
+    require_padding: bool,
+) -> Result<Vec<u8>, DecodeError> {
+    let bytes = s.as_bytes();
+    let padding = bytes.iter().rev().take_while(|&&b| b == b'=').count();
+    if padding > 2 {
+        return Err(DecodeError::InvalidPadding);
+    }
+    // 有填充时总长度必须是4的倍数；不带填充时剩1个字符凑不出一个字节
+    let data = &bytes[..bytes.len() - padding];
+    let padded = require_padding || padding > 0;
+    if (padded && !bytes.len().is_multiple_of(4)) || data.len() % 4 == 1 {
+        return Err(DecodeError::InvalidLength(bytes.len()));
+    }
+    
+    let mut out = Vec::with_capacity(data.len() * 3 / 4);
+    let mut acc: u32 = 0;
+    let mut bits = 0;
+    for (position, &b) in data.iter().enumerate() {
+        let value = alphabet.iter().position(|&a| a == b).ok_or_else(|| {
+            DecodeError::InvalidCharacter {
+                position,
+                character: s[position..].chars().next().unwrap_or('\u{FFFD}'),
+            }
+        })?;
+        acc = (acc << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+            acc &= (1 << bits) - 1;
+        }
+    }
+    Ok(out)
+}
+
+fn hex_encode(data: &[u8], uppercase: bool) -> String {
+    let digits: &[u8; 16] = if uppercase { b"0123456789ABCDEF" } else { b"0123456789abcdef" };
+    let mut out = String::with_capacity(data.len() * 2);
+    for &byte in data {
+        out.push(digits[(byte >> 4) as usize] as char);
+        out.push(digits[(byte & 0x0F) as usize] as char);
+    }
+    out
+}
+
+// 解析十六进制文本：允许可选的0x前缀，忽略任意位置的空白，大小写均可
+fn hex_decode(s: &str) -> Result<Vec<u8>, DecodeError> {
+    let trimmed = s.trim_start();
+    let start = if trimmed.starts_with("0x") || trimmed.starts_with("0X") {
+        s.len() - trimmed.len() + 2
+    } else {
+        0
+    };
+    
+    let mut digits = Vec::with_capacity(s.len());
+    for (offset, character) in s[start..].char_indices() {
+        if character.is_whitespace() {
+            continue;
         }
+        let digit = character.to_digit(16).ok_or(DecodeError::InvalidCharacter {
+            position: start + offset,
+            character,
+        })?;
+        digits.push(digit as u8);
     }
     
+    if digits.len() % 2 != 0 {
+        return Err(DecodeError::InvalidLength(digits.len()));
+    }
+    Ok(digits.chunks_exact(2).map(|pair| (pair[0] << 4) | pair[1]).collect())
+}
+
+// 结构体的二进制序列化
+fn binary_struct_serialization() {
     let point = Point3D { x: 1.0, y: 2.5, z: -3.7 };
     println!("  原始点: {:?}", point);
     
@@ -459,6 +754,143 @@ fn binary_struct_serialization() {
                    (point.z - reconstructed.z).abs() < epsilon;
     
     println!("  数据完整性: {}", if is_equal { "✓ 通过" } else { "✗ 失败" });
+    
+    // 批量序列化，使用大端字节序（网络字节序）
+    let points = [point, Point3D { x: 0.5, y: -1.0, z: 8.0 }];
+    let mut buffer = Vec::new();
+    write_points(&points, &mut buffer, Endian::Big);
+    println!("  批量序列化 {} 个点: {} 字节", points.len(), buffer.len());
+    match read_points(&buffer, Endian::Big) {
+        Ok(decoded) => println!("  批量反序列化: {:?}", decoded),
+        Err(e) => println!("  批量反序列化失败: {}", e),
+    }
+}
+
+// 按位写入：高位在前依次填满每个字节，适合不按字节对齐的紧凑编码
+struct BitWriter {
+    bytes: Vec<u8>,
+    bit_len: usize,
+}
+
+impl BitWriter {
+    fn new() -> Self {
+        BitWriter { bytes: Vec::new(), bit_len: 0 }
+    }
+    
+    // 写入value的低count位，超过count的高位被忽略
+    fn write_bits(&mut self, value: u64, count: u8) {
+        assert!(count <= 64, "一次最多写入64位");
+        for i in (0..count).rev() {
+            let offset = self.bit_len % 8;
+            if offset == 0 {
+                self.bytes.push(0);
+            }
+            if (value >> i) & 1 == 1 {
+                *self.bytes.last_mut().unwrap() |= 0x80 >> offset;
+            }
+            self.bit_len += 1;
+        }
+    }
+    
+    fn bit_len(&self) -> usize {
+        self.bit_len
+    }
+    
+    // 最后一个不满的字节低位补0
+    fn finish(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        BitReader { data, position: 0 }
+    }
+    
+    // 剩余的位不够时返回None，且不移动读取位置
+    fn read_bits(&mut self, count: u8) -> Option<u64> {
+        if count > 64 || self.position + count as usize > self.data.len() * 8 {
+            return None;
+        }
+        
+        let mut value = 0u64;
+        for _ in 0..count {
+            let bit = (self.data[self.position / 8] >> (7 - self.position % 8)) & 1;
+            value = (value << 1) | bit as u64;
+            self.position += 1;
+        }
+        Some(value)
+    }
+}
+
+fn bit_packing_example() {
+    // 版本(3位) + 标志(5位) + 长度(12位)，共20位打包进3个字节
+    let mut writer = BitWriter::new();
+    writer.write_bits(5, 3);
+    writer.write_bits(0b10001, 5);
+    writer.write_bits(1500, 12);
+    println!("  按位打包 {} 位", writer.bit_len());
+    let packed = writer.finish();
+    println!("  打包结果: {:02X?}", packed);
+    
+    let mut reader = BitReader::new(&packed);
+    let fields = (reader.read_bits(3), reader.read_bits(5), reader.read_bits(12));
+    println!("  解包字段: {:?}", fields);
+}
+
+// 无符号LEB128变长整数：每字节低7位存数据（低位组在前），最高位表示后面还有字节
+fn write_varint(mut value: u64, out: &mut Vec<u8>) {
+    while value >= 0x80 {
+        out.push((value as u8 & 0x7F) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+// u64最多占10个字节，更长的序列或者超出u64范围的第10个字节视为无效数据
+fn read_varint<R: Read>(r: &mut R) -> io::Result<u64> {
+    let mut value = 0u64;
+    for i in 0..10 {
+        let mut byte = [0u8; 1];
+        r.read_exact(&mut byte)?;
+        let data = (byte[0] & 0x7F) as u64;
+        if i == 9 && data > 1 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "varint超出u64范围"));
+        }
+        value |= data << (7 * i);
+        if byte[0] & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(io::Error::new(io::ErrorKind::InvalidData, "varint超过10个字节"))
+}
+
+// ZigZag编码把绝对值小的负数映射为小的无符号数：0,-1,1,-2 -> 0,1,2,3
+fn write_signed_varint(value: i64, out: &mut Vec<u8>) {
+    write_varint(((value << 1) ^ (value >> 63)) as u64, out);
+}
+
+fn read_signed_varint<R: Read>(r: &mut R) -> io::Result<i64> {
+    let n = read_varint(r)?;
+    Ok((n >> 1) as i64 ^ -((n & 1) as i64))
+}
+
+fn varint_example() {
+    let mut encoded = Vec::new();
+    for value in [1u64, 300, 1 << 40] {
+        write_varint(value, &mut encoded);
+    }
+    write_signed_varint(-2, &mut encoded);
+    println!("  变长整数编码 [1, 300, 2^40, -2]: {:02X?} ({} 字节)", encoded, encoded.len());
+    
+    let mut cursor = io::Cursor::new(&encoded);
+    let unsigned: Vec<u64> = (0..3).filter_map(|_| read_varint(&mut cursor).ok()).collect();
+    println!("  解码: {:?} {:?}", unsigned, read_signed_varint(&mut cursor));
 }
 
 // 自定义序列化格式
@@ -477,41 +909,6 @@ fn custom_serialization() {
 fn key_value_serialization() {
     println!("键值对序列化：");
     
-    struct Config {
-        host: String,
-        port: u16,
-        debug: bool,
-        timeout: f64,
-    }
-    
-    impl Config {
-        fn serialize(&self) -> String {
-            format!("host={}\nport={}\ndebug={}\ntimeout={}", 
-                    self.host, self.port, self.debug, self.timeout)
-        }
-        
-        fn deserialize(data: &str) -> Result<Self, String> {
-            let mut host = String::new();
-            let mut port = 0;
-            let mut debug = false;
-            let mut timeout = 0.0;
-            
-            for line in data.lines() {
-                if let Some((key, value)) = line.split_once('=') {
-                    match key {
-                        "host" => host = value.to_string(),
-                        "port" => port = value.parse().map_err(|_| "端口解析失败")?,
-                        "debug" => debug = value.parse().map_err(|_| "调试标志解析失败")?,
-                        "timeout" => timeout = value.parse().map_err(|_| "超时时间解析失败")?,
-                        _ => return Err(format!("未知配置项: {}", key)),
-                    }
-                }
-            }
-            
-            Ok(Config { host, port, debug, timeout })
-        }
-    }
-    
     let config = Config {
         host: "localhost".to_string(),
         port: 8080,
@@ -532,113 +929,721 @@ fn key_value_serialization() {
         }
         Err(e) => println!("  反序列化失败: {}", e),
     }
-}
-
-// JSON风格序列化
-fn json_style_serialization() {
-    println!("JSON风格序列化：");
     
-    #[derive(Debug)]
-    struct User {
-        id: u32,
-        name: String,
-        email: String,
-        active: bool,
+    // 含有特殊字符的值也能完整往返
+    let tricky = Config {
+        host: "a=b\nc".to_string(),
+        ..config
+    };
+    match Config::deserialize(&tricky.serialize()) {
+        Ok(restored) => println!("  特殊字符往返: {:?}", restored.host),
+        Err(e) => println!("  特殊字符往返失败: {}", e),
     }
     
-    impl User {
-        fn to_json(&self) -> String {
-            format!(r#"{{
-  "id": {},
-  "name": "{}",
-  "email": "{}",
-  "active": {}
-}}"#, self.id, self.name, self.email, self.active)
+    if let Err(e) = Config::deserialize("host=localhost\nproxy=none") {
+        println!("  {}", e);
+    }
+}
+
+// 配置反序列化错误
+#[derive(Debug, PartialEq)]
+enum ConfigError {
+    // 不认识的配置项
+    UnknownKey(String),
+    // 值无法解析或含有非法转义
+    InvalidValue { key: String, value: String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::UnknownKey(key) => write!(f, "未知配置项: {}", key),
+            ConfigError::InvalidValue { key, value } => {
+                write!(f, "配置项 {} 的值无效: {:?}", key, value)
+            }
         }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[derive(Debug, PartialEq)]
+struct Config {
+    host: String,
+    port: u16,
+    debug: bool,
+    timeout: f64,
+}
+
+impl Config {
+    // 每行一个 key=value，值中的 \、换行、回车和 = 会被转义
+    fn serialize(&self) -> String {
+        format!("host={}\nport={}\ndebug={}\ntimeout={}",
+                escape_config_value(&self.host), self.port, self.debug, self.timeout)
+    }
+    
+    fn deserialize(data: &str) -> Result<Self, ConfigError> {
+        let mut host = String::new();
+        let mut port = 0;
+        let mut debug = false;
+        let mut timeout = 0.0;
         
-        // 简化的JSON解析（实际应用中应使用专门的JSON库）
-        fn from_json_simple(json: &str) -> Result<Self, String> {
-            // 这是一个非常简化的解析器，仅用于演示
-            let mut id = 0;
-            let mut name = String::new();
-            let mut email = String::new();
-            let mut active = false;
-            
-            for line in json.lines() {
-                let line = line.trim();
-                if line.starts_with('"') && line.contains(':') {
-                    if let Some((key, value)) = line.split_once(':') {
-                        let key = key.trim().trim_matches('"');
-                        let value = value.trim().trim_end_matches(',');
-                        
-                        match key {
-                            "id" => id = value.parse().map_err(|_| "ID解析失败")?,
-                            "name" => name = value.trim_matches('"').to_string(),
-                            "email" => email = value.trim_matches('"').to_string(),
-                            "active" => active = value.parse().map_err(|_| "active解析失败")?,
-                            _ => {}
-                        }
-                    }
+        for line in data.lines() {
+            // 值中的 = 已被转义，第一个 = 一定是分隔符
+            if let Some((key, raw)) = line.split_once('=') {
+                let invalid = || ConfigError::InvalidValue {
+                    key: key.to_string(),
+                    value: raw.to_string(),
+                };
+                let value = unescape_config_value(raw).ok_or_else(invalid)?;
+                
+                match key {
+                    "host" => host = value,
+                    "port" => port = value.parse().map_err(|_| invalid())?,
+                    "debug" => debug = value.parse().map_err(|_| invalid())?,
+                    "timeout" => timeout = value.parse().map_err(|_| invalid())?,
+                    _ => return Err(ConfigError::UnknownKey(key.to_string())),
                 }
             }
-            
-            Ok(User { id, name, email, active })
         }
+        
+        Ok(Config { host, port, debug, timeout })
     }
-    
-    let user = User {
-        id: 1,
-        name: "张三".to_string(),
-        email: "zhangsan@example.com".to_string(),
-        active: true,
-    };
-    
-    let json = user.to_json();
-    println!("  序列化为JSON:\n{}", json);
-    
-    match User::from_json_simple(&json) {
-        Ok(parsed_user) => {
-            println!("  反序列化成功: {:?}", parsed_user);
+}
+
+// 转义配置值中的特殊字符
+fn escape_config_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '=' => escaped.push_str("\\="),
+            _ => escaped.push(c),
         }
-        Err(e) => println!("  反序列化失败: {}", e),
     }
+    escaped
 }
 
-// XML风格序列化
-fn xml_style_serialization() {
-    println!("XML风格序列化：");
-    
-    #[derive(Debug)]
-    struct Book {
-        title: String,
-        author: String,
-        year: u16,
-        pages: u32,
+// 还原转义，遇到未知转义或结尾的单个反斜杠返回None
+fn unescape_config_value(value: &str) -> Option<String> {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next()? {
+            '\\' => result.push('\\'),
+            'n' => result.push('\n'),
+            'r' => result.push('\r'),
+            '=' => result.push('='),
+            _ => return None,
+        }
     }
-    
-    impl Book {
-        fn to_xml(&self) -> String {
-            format!(r#"<book>
-  <title>{}</title>
-  <author>{}</author>
-  <year>{}</year>
-  <pages>{}</pages>
-</book>"#, self.title, self.author, self.year, self.pages)
+    Some(result)
+}
+
+// 按JSON规范转义字符串内容（不含两侧引号）
+fn json_escape(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            '\u{08}' => result.push_str("\\b"),
+            '\u{0C}' => result.push_str("\\f"),
+            c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
         }
     }
-    
-    let book = Book {
-        title: "Rust编程指南".to_string(),
-        author: "Rust专家".to_string(),
-        year: 2023,
-        pages: 500,
+    result
+}
+
+// json_escape的逆操作，支持 \uXXXX 及代理对
+fn json_unescape(s: &str) -> Result<String, String> {
+    let mut result = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    
+    let read_hex4 = |chars: &mut std::str::Chars| -> Result<u32, String> {
+        let hex: String = chars.by_ref().take(4).collect();
+        if hex.len() != 4 {
+            return Err("\\u转义不完整".to_string());
+        }
+        // from_str_radix 会接受前导 '+'，必须逐个检查是十六进制数字
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(format!("无效的\\u转义: {}", hex));
+        }
+        u32::from_str_radix(&hex, 16).map_err(|_| format!("无效的\\u转义: {}", hex))
     };
     
-    let xml = book.to_xml();
-    println!("  序列化为XML:\n{}", xml);
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        
+        match chars.next() {
+            Some('"') => result.push('"'),
+            Some('\\') => result.push('\\'),
+            Some('/') => result.push('/'),
+            Some('n') => result.push('\n'),
+            Some('r') => result.push('\r'),
+            Some('t') => result.push('\t'),
+            Some('b') => result.push('\u{08}'),
+            Some('f') => result.push('\u{0C}'),
+            Some('u') => {
+                let high = read_hex4(&mut chars)?;
+                let code = if (0xD800..0xDC00).contains(&high) {
+                    // 高位代理后必须跟 \uDC00-\uDFFF 的低位代理
+                    if chars.next() != Some('\\') || chars.next() != Some('u') {
+                        return Err("缺少低位代理".to_string());
+                    }
+                    let low = read_hex4(&mut chars)?;
+                    if !(0xDC00..0xE000).contains(&low) {
+                        return Err(format!("无效的低位代理: {:04x}", low));
+                    }
+                    0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+                } else {
+                    high
+                };
+                let c = char::from_u32(code).ok_or_else(|| format!("无效的码点: {:x}", code))?;
+                result.push(c);
+            }
+            Some(other) => return Err(format!("未知的转义序列: \\{}", other)),
+            None => return Err("字符串以反斜杠结尾".to_string()),
+        }
+    }
+    
+    Ok(result)
 }
 
-// CSV格式处理
+#[derive(Debug, PartialEq)]
+struct User {
+    id: u32,
+    name: String,
+    email: String,
+    active: bool,
+}
+
+impl User {
+    fn to_json(&self) -> String {
+        format!(r#"{{
+  "id": {},
+  "name": "{}",
+  "email": "{}",
+  "active": {}
+}}"#, self.id, json_escape(&self.name), json_escape(&self.email), self.active)
+    }
+    
+    // 简化的JSON解析（实际应用中应使用专门的JSON库）
+    fn from_json_simple(json: &str) -> Result<Self, String> {
+        // 这是一个非常简化的解析器，仅用于演示
+        let mut id = 0;
+        let mut name = String::new();
+        let mut email = String::new();
+        let mut active = false;
+        
+        // 去掉字符串值两侧的引号并反转义
+        let string_value = |value: &str| {
+            value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .ok_or_else(|| format!("字符串值缺少引号: {}", value))
+                .and_then(json_unescape)
+        };
+        
+        for line in json.lines() {
+            let line = line.trim();
+            if line.starts_with('"') && line.contains(':') {
+                if let Some((key, value)) = line.split_once(':') {
+                    let key = key.trim().trim_matches('"');
+                    let value = value.trim();
+                    let value = value.strip_suffix(',').unwrap_or(value);
+                    
+                    match key {
+                        "id" => id = value.parse().map_err(|_| "ID解析失败")?,
+                        "name" => name = string_value(value)?,
+                        "email" => email = string_value(value)?,
+                        "active" => active = value.parse().map_err(|_| "active解析失败")?,
+                        _ => {}
+                    }
+                }
+            }
+        }
+        
+        Ok(User { id, name, email, active })
+    }
+}
+
+// JSON解析错误，position为出错处的字节偏移
+#[derive(Debug, PartialEq)]
+struct JsonError {
+    position: usize,
+    message: String,
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "位置 {}: {}", self.position, self.message)
+    }
+}
+
+impl std::error::Error for JsonError {}
+
+// 通用JSON值，对象保留键的原始顺序
+#[derive(Debug, Clone, PartialEq)]
+enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    // 嵌套层数上限，防止恶意输入导致栈溢出
+    const MAX_DEPTH: usize = 128;
+    
+    fn parse(input: &str) -> Result<JsonValue, JsonError> {
+        let mut parser = JsonParser { input, pos: 0, depth: 0 };
+        let value = parser.parse_value()?;
+        parser.skip_whitespace();
+        if parser.pos != input.len() {
+            return Err(parser.error("值之后有多余的内容"));
+        }
+        Ok(value)
+    }
+    
+    fn get(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for JsonValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonValue::Null => write!(f, "null"),
+            JsonValue::Bool(b) => write!(f, "{}", b),
+            // JSON不能表示NaN和无穷大
+            JsonValue::Number(n) if !n.is_finite() => write!(f, "null"),
+            JsonValue::Number(n) => write!(f, "{}", n),
+            JsonValue::String(s) => write!(f, "\"{}\"", json_escape(s)),
+            JsonValue::Array(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+            JsonValue::Object(entries) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "\"{}\":{}", json_escape(key), value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+// 递归下降解析器
+struct JsonParser<'a> {
+    input: &'a str,
+    pos: usize,
+    depth: usize,
+}
+
+impl<'a> JsonParser<'a> {
+    fn error(&self, message: &str) -> JsonError {
+        JsonError { position: self.pos, message: message.to_string() }
+    }
+    
+    fn peek(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.pos).copied()
+    }
+    
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+    
+    fn expect_literal(&mut self, literal: &str, value: JsonValue) -> Result<JsonValue, JsonError> {
+        if self.input[self.pos..].starts_with(literal) {
+            self.pos += literal.len();
+            Ok(value)
+        } else {
+            Err(self.error("无效的字面量"))
+        }
+    }
+    
+    fn parse_value(&mut self) -> Result<JsonValue, JsonError> {
+        self.skip_whitespace();
+        match self.peek() {
+            None => Err(self.error("意外的输入结尾")),
+            Some(b'n') => self.expect_literal("null", JsonValue::Null),
+            Some(b't') => self.expect_literal("true", JsonValue::Bool(true)),
+            Some(b'f') => self.expect_literal("false", JsonValue::Bool(false)),
+            Some(b'"') => self.parse_string().map(JsonValue::String),
+            Some(b'[') => self.parse_array(),
+            Some(b'{') => self.parse_object(),
+            Some(b'-' | b'0'..=b'9') => self.parse_number(),
+            Some(_) => Err(self.error("意外的字符")),
+        }
+    }
+    
+    fn enter(&mut self) -> Result<(), JsonError> {
+        self.depth += 1;
+        if self.depth > JsonValue::MAX_DEPTH {
+            return Err(self.error("嵌套层数超过限制"));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+    
+    fn parse_array(&mut self) -> Result<JsonValue, JsonError> {
+        self.enter()?;
+        let mut items = Vec::new();
+        
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            self.depth -= 1;
+            return Ok(JsonValue::Array(items));
+        }
+        
+        loop {
+            items.push(self.parse_value()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    break;
+                }
+                _ => return Err(self.error("数组中缺少 ',' 或 ']'")),
+            }
+        }
+        
+        self.depth -= 1;
+        Ok(JsonValue::Array(items))
+    }
+    
+    fn parse_object(&mut self) -> Result<JsonValue, JsonError> {
+        self.enter()?;
+        let mut entries = Vec::new();
+        
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            self.depth -= 1;
+            return Ok(JsonValue::Object(entries));
+        }
+        
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some(b'"') {
+                return Err(self.error("对象的键必须是字符串"));
+            }
+            let key = self.parse_string()?;
+            
+            self.skip_whitespace();
+            if self.peek() != Some(b':') {
+                return Err(self.error("键之后缺少 ':'"));
+            }
+            self.pos += 1;
+            
+            let value = self.parse_value()?;
+            entries.push((key, value));
+            
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    break;
+                }
+                _ => return Err(self.error("对象中缺少 ',' 或 '}'")),
+            }
+        }
+        
+        self.depth -= 1;
+        Ok(JsonValue::Object(entries))
+    }
+    
+    fn parse_string(&mut self) -> Result<String, JsonError> {
+        // 跳过开头的引号，找到未转义的结束引号
+        let start = self.pos + 1;
+        let bytes = self.input.as_bytes();
+        let mut i = start;
+        loop {
+            match bytes.get(i) {
+                None => {
+                    self.pos = i;
+                    return Err(self.error("字符串缺少结束引号"));
+                }
+                Some(b'"') => break,
+                Some(b'\\') => i += 2,
+                Some(&b) if b < 0x20 => {
+                    self.pos = i;
+                    return Err(self.error("字符串中不能包含未转义的控制字符"));
+                }
+                Some(_) => i += 1,
+            }
+        }
+        
+        let raw = &self.input[start..i];
+        let value = json_unescape(raw).map_err(|e| JsonError { position: start, message: e })?;
+        self.pos = i + 1;
+        Ok(value)
+    }
+    
+    fn parse_number(&mut self) -> Result<JsonValue, JsonError> {
+        let start = self.pos;
+        let bytes = self.input.as_bytes();
+        let digits = |mut i: usize| {
+            while bytes.get(i).is_some_and(u8::is_ascii_digit) {
+                i += 1;
+            }
+            i
+        };
+        
+        let mut i = start;
+        if bytes[i] == b'-' {
+            i += 1;
+        }
+        
+        // 整数部分：0 或不以0开头的数字串
+        match bytes.get(i) {
+            Some(b'0') => i += 1,
+            Some(b'1'..=b'9') => i = digits(i),
+            _ => return Err(self.error("无效的数字")),
+        }
+        
+        if bytes.get(i) == Some(&b'.') {
+            let end = digits(i + 1);
+            if end == i + 1 {
+                return Err(self.error("小数点后缺少数字"));
+            }
+            i = end;
+        }
+        
+        if matches!(bytes.get(i), Some(b'e' | b'E')) {
+            i += 1;
+            if matches!(bytes.get(i), Some(b'+' | b'-')) {
+                i += 1;
+            }
+            let end = digits(i);
+            if end == i {
+                return Err(self.error("指数部分缺少数字"));
+            }
+            i = end;
+        }
+        
+        let number = self.input[start..i]
+            .parse::<f64>()
+            .map_err(|_| self.error("无效的数字"))?;
+        self.pos = i;
+        Ok(JsonValue::Number(number))
+    }
+}
+
+// JSON风格序列化
+fn json_style_serialization() {
+    println!("JSON风格序列化：");
+    
+    let user = User {
+        id: 1,
+        name: "张三 \"小张\"".to_string(),
+        email: "zhangsan@example.com".to_string(),
+        active: true,
+    };
+    
+    let json = user.to_json();
+    println!("  序列化为JSON:\n{}", json);
+    
+    match User::from_json_simple(&json) {
+        Ok(parsed_user) => {
+            println!("  反序列化成功: {:?}", parsed_user);
+        }
+        Err(e) => println!("  反序列化失败: {}", e),
+    }
+    
+    // 通用JSON值解析
+    match JsonValue::parse(&json) {
+        Ok(value) => {
+            println!("  通用解析: {}", value);
+            if let Some(JsonValue::String(name)) = value.get("name") {
+                println!("  name字段: {}", name);
+            }
+            
+            // 同一个值的MessagePack二进制形式更紧凑
+            let packed = to_msgpack(&value);
+            println!("  MessagePack: {} 字节 (JSON {} 字节)", packed.len(), json.len());
+        }
+        Err(e) => println!("  通用解析失败: {}", e),
+    }
+}
+
+// MessagePack编码（子集）：nil、bool、整数、float64、字符串、数组和map。
+// 没有小数部分且在整数范围内的数字按最短的整数格式编码，其余按float64编码
+fn to_msgpack(v: &JsonValue) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_msgpack(v, &mut out);
+    out
+}
+
+fn write_msgpack(v: &JsonValue, out: &mut Vec<u8>) {
+    match v {
+        JsonValue::Null => out.push(0xC0),
+        JsonValue::Bool(false) => out.push(0xC2),
+        JsonValue::Bool(true) => out.push(0xC3),
+        JsonValue::Number(n) => write_msgpack_number(*n, out),
+        JsonValue::String(s) => {
+            write_msgpack_len(s.len(), 0xA0, 32, [0xD9, 0xDA, 0xDB], out);
+            out.extend_from_slice(s.as_bytes());
+        }
+        JsonValue::Array(items) => {
+            // array没有8位长度格式
+            write_msgpack_len(items.len(), 0x90, 16, [0, 0xDC, 0xDD], out);
+            for item in items {
+                write_msgpack(item, out);
+            }
+        }
+        JsonValue::Object(entries) => {
+            write_msgpack_len(entries.len(), 0x80, 16, [0, 0xDE, 0xDF], out);
+            for (key, value) in entries {
+                write_msgpack(&JsonValue::String(key.clone()), out);
+                write_msgpack(value, out);
+            }
+        }
+    }
+}
+
+fn write_msgpack_number(n: f64, out: &mut Vec<u8>) {
+    // 2^64和-2^63都能被f64精确表示
+    const U64_END: f64 = 18446744073709551616.0;
+    const I64_MIN: f64 = -9223372036854775808.0;
+    
+    if n.fract() == 0.0 && (0.0..U64_END).contains(&n) {
+        write_msgpack_uint(n as u64, out);
+    } else if n.fract() == 0.0 && (I64_MIN..0.0).contains(&n) {
+        write_msgpack_int(n as i64, out);
+    } else {
+        out.push(0xCB);
+        out.extend_from_slice(&n.to_be_bytes());
+    }
+}
+
+fn write_msgpack_uint(n: u64, out: &mut Vec<u8>) {
+    if n < 0x80 {
+        // positive fixint
+        out.push(n as u8);
+    } else if n <= u8::MAX as u64 {
+        out.extend_from_slice(&[0xCC, n as u8]);
+    } else if n <= u16::MAX as u64 {
+        out.push(0xCD);
+        out.extend_from_slice(&(n as u16).to_be_bytes());
+    } else if n <= u32::MAX as u64 {
+        out.push(0xCE);
+        out.extend_from_slice(&(n as u32).to_be_bytes());
+    } else {
+        out.push(0xCF);
+        out.extend_from_slice(&n.to_be_bytes());
+    }
+}
+
+// 只用于负数，非负数统一按无符号格式编码
+fn write_msgpack_int(n: i64, out: &mut Vec<u8>) {
+    if n >= -32 {
+        // negative fixint：111xxxxx
+        out.push(n as i8 as u8);
+    } else if n >= i8::MIN as i64 {
+        out.extend_from_slice(&[0xD0, n as i8 as u8]);
+    } else if n >= i16::MIN as i64 {
+        out.push(0xD1);
+        out.extend_from_slice(&(n as i16).to_be_bytes());
+    } else if n >= i32::MIN as i64 {
+        out.push(0xD2);
+        out.extend_from_slice(&(n as i32).to_be_bytes());
+    } else {
+        out.push(0xD3);
+        out.extend_from_slice(&n.to_be_bytes());
+    }
+}
+
+// 长度头：小于fix_limit时放进fix格式的低位，否则依次尝试8/16/32位长度格式（0表示该格式不存在）
+fn write_msgpack_len(
+    len: usize,
+    fix_prefix: u8,
+    fix_limit: usize,
+    markers: [u8; 3],
+    out: &mut Vec<u8>,
+) {
+    if len < fix_limit {
+        out.push(fix_prefix | len as u8);
+    } else if markers[0] != 0 && len <= u8::MAX as usize {
+        out.extend_from_slice(&[markers[0], len as u8]);
+    } else if len <= u16::MAX as usize {
+        out.push(markers[1]);
+        out.extend_from_slice(&(len as u16).to_be_bytes());
+    } else {
+        out.push(markers[2]);
+        out.extend_from_slice(&(len as u32).to_be_bytes());
+    }
+}
+
+// XML风格序列化
+fn xml_style_serialization() {
+    println!("XML风格序列化：");
+    
+    #[derive(Debug)]
+    struct Book {
+        title: String,
+        author: String,
+        year: u16,
+        pages: u32,
+    }
+    
+    impl Book {
+        fn to_xml(&self) -> String {
+            format!(r#"<book>
+  <title>{}</title>
+  <author>{}</author>
+  <year>{}</year>
+  <pages>{}</pages>
+</book>"#, self.title, self.author, self.year, self.pages)
+        }
+    }
+    
+    let book = Book {
+        title: "Rust编程指南".to_string(),
+        author: "Rust专家".to_string(),
+        year: 2023,
+        pages: 500,
+    };
+    
+    let xml = book.to_xml();
+    println!("  序列化为XML:\n{}", xml);
+}
+
+// CSV格式处理
 fn csv_processing() {
     println!("CSV格式处理：");
     
@@ -701,6 +1706,264 @@ fn csv_processing() {
             Err(e) => println!("    解析第{}行失败: {}", i + 1, e),
         }
     }
+    
+    // 对齐的表格输出
+    let mut table = TableFormatter::new()
+        .header(&["ID", "姓名", "年龄", "成绩"])
+        .separator("  ")
+        .align(0, Align::Right)
+        .align(2, Align::Right)
+        .align(3, Align::Right);
+    for student in &students {
+        table.add_row(&[
+            &student.id.to_string(),
+            &student.name,
+            &student.age.to_string(),
+            &format!("{:.1}", student.grade),
+        ]);
+    }
+    println!("  表格输出:\n{}", table.render());
+    
+    // 带引号字段的CSV需要用CsvWriter/CsvReader处理，简单按逗号分割会出错
+    let mut writer = CsvWriter::new(Vec::new());
+    let _ = writer.write_record(&["4", "Smith, John", "22", "88.0"]);
+    let _ = writer.write_record_iter(
+        ["5", "说 \"你好\"", "20", "90.5"].iter().map(|field| field.to_string()),
+    );
+    let quoted = writer.into_inner();
+    println!("  带引号的CSV数据:\n{}", String::from_utf8_lossy(&quoted));
+    println!("  带引号的CSV记录:");
+    for record in CsvReader::new(&quoted[..]).records() {
+        match record {
+            Ok(fields) => println!("    {:?}", fields),
+            Err(e) => println!("    解析失败: {}", e),
+        }
+    }
+}
+
+// 流式CSV读取器，支持RFC 4180引号规则：
+// 双引号包围的字段可以包含逗号和换行，字段内的 "" 表示一个双引号
+struct CsvReader<R: BufRead> {
+    reader: R,
+    line: String,
+}
+
+impl<R: BufRead> CsvReader<R> {
+    fn new(reader: R) -> Self {
+        CsvReader { reader, line: String::new() }
+    }
+    
+    // 读取下一条记录，输入结束时返回None；空行被跳过
+    fn read_record(&mut self) -> io::Result<Option<Vec<String>>> {
+        let mut fields = Vec::new();
+        let mut field = String::new();
+        let mut in_quotes = false;
+        let mut started = false;
+        
+        loop {
+            self.line.clear();
+            if self.reader.read_line(&mut self.line)? == 0 {
+                if in_quotes {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "引号未闭合的CSV字段",
+                    ));
+                }
+                if !started {
+                    return Ok(None);
+                }
+                fields.push(field);
+                return Ok(Some(fields));
+            }
+            
+            // 引号外的空行直接跳过
+            if !in_quotes && self.line.trim_end_matches(['\r', '\n']).is_empty() {
+                continue;
+            }
+            started = true;
+            
+            let mut chars = self.line.chars().peekable();
+            while let Some(c) = chars.next() {
+                if in_quotes {
+                    match c {
+                        '"' if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            field.push('"');
+                        }
+                        '"' => in_quotes = false,
+                        _ => field.push(c),
+                    }
+                } else {
+                    match c {
+                        '"' if field.is_empty() => in_quotes = true,
+                        ',' => fields.push(std::mem::take(&mut field)),
+                        '\r' if chars.peek() == Some(&'\n') => {}
+                        '\n' => {}
+                        _ => field.push(c),
+                    }
+                }
+            }
+            
+            // 行尾不在引号内时记录结束，否则继续读取下一物理行
+            if !in_quotes {
+                fields.push(field);
+                return Ok(Some(fields));
+            }
+        }
+    }
+    
+    fn records(mut self) -> impl Iterator<Item = io::Result<Vec<String>>> {
+        std::iter::from_fn(move || self.read_record().transpose())
+    }
+}
+
+// CSV写入器：字段包含逗号、双引号或换行时加引号，内部的双引号写成 ""
+struct CsvWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> CsvWriter<W> {
+    fn new(writer: W) -> Self {
+        CsvWriter { writer }
+    }
+    
+    fn write_record(&mut self, fields: &[&str]) -> io::Result<()> {
+        self.write_record_iter(fields.iter().map(|field| field.to_string()))
+    }
+    
+    fn write_record_iter(&mut self, fields: impl Iterator<Item = String>) -> io::Result<()> {
+        // 只有一个空字段时，不加引号会写出空行，读回来就不是[""]了
+        let mut lone_empty = false;
+        for (i, field) in fields.enumerate() {
+            if i > 0 {
+                self.writer.write_all(b",")?;
+            }
+            lone_empty = i == 0 && field.is_empty();
+            
+            if field.contains([',', '"', '\r', '\n']) {
+                write!(self.writer, "\"{}\"", field.replace('"', "\"\""))?;
+            } else {
+                self.writer.write_all(field.as_bytes())?;
+            }
+        }
+        if lone_empty {
+            self.writer.write_all(b"\"\"")?;
+        }
+        self.writer.write_all(b"\n")
+    }
+    
+    fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+// 列对齐方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Align {
+    Left,
+    Right,
+}
+
+// 定宽文本表格：收集所有行后按每列最大显示宽度对齐输出
+struct TableFormatter {
+    header: Option<Vec<String>>,
+    rows: Vec<Vec<String>>,
+    aligns: HashMap<usize, Align>,
+    separator: String,
+}
+
+impl TableFormatter {
+    fn new() -> Self {
+        TableFormatter {
+            header: None,
+            rows: Vec::new(),
+            aligns: HashMap::new(),
+            separator: " | ".to_string(),
+        }
+    }
+    
+    fn header(mut self, columns: &[&str]) -> Self {
+        self.header = Some(columns.iter().map(|c| c.to_string()).collect());
+        self
+    }
+    
+    // 未设置的列默认左对齐
+    fn align(mut self, column: usize, align: Align) -> Self {
+        self.aligns.insert(column, align);
+        self
+    }
+    
+    fn separator(mut self, separator: &str) -> Self {
+        self.separator = separator.to_string();
+        self
+    }
+    
+    fn add_row(&mut self, row: &[&str]) {
+        self.rows.push(row.iter().map(|c| c.to_string()).collect());
+    }
+    
+    fn render(&self) -> String {
+        let all_rows = self.header.iter().chain(self.rows.iter());
+        let column_count = all_rows.clone().map(|row| row.len()).max().unwrap_or(0);
+        if column_count == 0 {
+            return String::new();
+        }
+        
+        let mut widths = vec![0; column_count];
+        for row in all_rows {
+            for (i, cell) in row.iter().enumerate() {
+                widths[i] = widths[i].max(display_width(cell));
+            }
+        }
+        
+        let mut result = String::new();
+        if let Some(header) = &self.header {
+            result.push_str(&self.render_row(header, &widths));
+            let rule: Vec<String> = widths.iter().map(|&w| "-".repeat(w)).collect();
+            result.push_str(&rule.join(&self.separator));
+            result.push('\n');
+        }
+        for row in &self.rows {
+            result.push_str(&self.render_row(row, &widths));
+        }
+        result
+    }
+    
+    fn render_row(&self, row: &[String], widths: &[usize]) -> String {
+        let cells: Vec<String> = widths
+            .iter()
+            .enumerate()
+            .map(|(i, &width)| {
+                let cell = row.get(i).map(String::as_str).unwrap_or("");
+                let padding = " ".repeat(width - display_width(cell));
+                match self.aligns.get(&i).copied().unwrap_or(Align::Left) {
+                    Align::Left => format!("{}{}", cell, padding),
+                    Align::Right => format!("{}{}", padding, cell),
+                }
+            })
+            .collect();
+        let mut line = cells.join(&self.separator).trim_end().to_string();
+        line.push('\n');
+        line
+    }
+}
+
+// 终端显示宽度：东亚宽字符（中日韩文字、全角符号等）占两列
+fn display_width(s: &str) -> usize {
+    s.chars()
+        .map(|c| match c as u32 {
+            0x1100..=0x115F
+            | 0x2E80..=0xA4CF
+            | 0xAC00..=0xD7A3
+            | 0xF900..=0xFAFF
+            | 0xFE30..=0xFE4F
+            | 0xFF00..=0xFF60
+            | 0xFFE0..=0xFFE6
+            | 0x1F300..=0x1FAFF
+            | 0x20000..=0x3FFFD => 2,
+            _ => 1,
+        })
+        .sum()
 }
 
 // 配置文件格式
@@ -715,38 +1978,103 @@ fn config_file_formats() {
     env_style_example();
 }
 
-// INI格式示例
-fn ini_format_example() {
-    println!("INI格式示例：");
+// INI解析错误，带出错的行号（从1开始）
+#[derive(Debug, PartialEq)]
+struct ParseError {
+    line: usize,
+    message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "第{}行: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// INI配置，出现在任何节之前的键值属于默认节 ""
+#[derive(Debug, PartialEq)]
+struct IniConfig {
+    sections: HashMap<String, HashMap<String, String>>,
+}
+
+impl IniConfig {
+    fn new() -> Self {
+        IniConfig { sections: HashMap::new() }
+    }
     
-    struct IniConfig {
-        sections: HashMap<String, HashMap<String, String>>,
+    fn set(&mut self, section: &str, key: &str, value: &str) {
+        self.sections
+            .entry(section.to_string())
+            .or_default()
+            .insert(key.to_string(), value.to_string());
     }
     
-    impl IniConfig {
-        fn new() -> Self {
-            IniConfig { sections: HashMap::new() }
-        }
+    fn get(&self, section: &str, key: &str) -> Option<&str> {
+        self.sections.get(section)?.get(key).map(|value| value.as_str())
+    }
+    
+    fn to_ini(&self) -> String {
+        // 默认节没有节头，必须写在最前面
+        let mut sections: Vec<_> = self.sections.iter().collect();
+        sections.sort_by_key(|(section, _)| !section.is_empty());
         
-        fn set(&mut self, section: &str, key: &str, value: &str) {
-            self.sections
-                .entry(section.to_string())
-                .or_insert_with(HashMap::new)
-                .insert(key.to_string(), value.to_string());
+        let mut result = String::new();
+        for (section, kvs) in sections {
+            if !section.is_empty() {
+                result.push_str(&format!("[{}]\n", section));
+            }
+            for (key, value) in kvs {
+                result.push_str(&format!("{}={}\n", key, value));
+            }
+            result.push('\n');
         }
+        result
+    }
+    
+    fn from_ini(text: &str) -> Result<IniConfig, ParseError> {
+        let mut config = IniConfig::new();
+        let mut section = String::new();
         
-        fn to_ini(&self) -> String {
-            let mut result = String::new();
-            for (section, kvs) in &self.sections {
-                result.push_str(&format!("[{}]\n", section));
-                for (key, value) in kvs {
-                    result.push_str(&format!("{}={}\n", key, value));
+        for (index, raw_line) in text.lines().enumerate() {
+            let line = raw_line.trim();
+            let error = |message: &str| ParseError {
+                line: index + 1,
+                message: format!("{}: {}", message, raw_line),
+            };
+            
+            // 跳过空行和注释
+            if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+                continue;
+            }
+            
+            if let Some(rest) = line.strip_prefix('[') {
+                let name = rest.strip_suffix(']').ok_or_else(|| error("节头缺少 ']'"))?;
+                let name = name.trim();
+                if name.is_empty() {
+                    return Err(error("节名不能为空"));
                 }
-                result.push('\n');
+                section = name.to_string();
+                config.sections.entry(section.clone()).or_default();
+                continue;
             }
-            result
+            
+            let (key, value) = line.split_once('=').ok_or_else(|| error("缺少 '='"))?;
+            let key = key.trim();
+            if key.is_empty() {
+                return Err(error("键不能为空"));
+            }
+            config.set(&section, key, value.trim());
         }
+        
+        Ok(config)
     }
+}
+
+// INI格式示例
+fn ini_format_example() {
+    println!("INI格式示例：");
     
     let mut config = IniConfig::new();
     config.set("database", "host", "localhost");
@@ -757,6 +2085,20 @@ fn ini_format_example() {
     
     let ini_data = config.to_ini();
     println!("  INI配置:\n{}", ini_data);
+    
+    // 解析INI文本
+    let text = "; 应用配置\nname = demo\n\n[server]\nport = 9090\n# 注释\nhost=127.0.0.1\n";
+    match IniConfig::from_ini(text) {
+        Ok(parsed) => println!(
+            "  解析结果: name={:?}, server.port={:?}",
+            parsed.get("", "name"),
+            parsed.get("server", "port")
+        ),
+        Err(e) => println!("  解析失败: {}", e),
+    }
+    if let Err(e) = IniConfig::from_ini("[server]\nport 9090\n") {
+        println!("  解析失败: {}", e);
+    }
 }
 
 // TOML风格示例
@@ -811,100 +2153,203 @@ fn network_protocol_serialization() {
     
     // 长度前缀协议
     length_prefixed_protocol();
+    
+    // TLV编码
+    tlv_protocol_example();
 }
 
-// HTTP消息示例
-fn http_message_example() {
-    println!("HTTP消息序列化：");
+// HTTP请求，头部按插入顺序保存，保证序列化结果确定
+struct HttpRequest {
+    method: String,
+    path: String,
+    version: String,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl HttpRequest {
+    fn new(method: &str, path: &str) -> Self {
+        HttpRequest {
+            method: method.to_string(),
+            path: path.to_string(),
+            version: "HTTP/1.1".to_string(),
+            headers: Vec::new(),
+            body: String::new(),
+        }
+    }
     
-    struct HttpRequest {
-        method: String,
-        path: String,
-        version: String,
-        headers: HashMap<String, String>,
-        body: String,
+    fn header(&mut self, key: &str, value: &str) -> &mut Self {
+        self.headers.push((key.to_string(), value.to_string()));
+        self
     }
     
-    impl HttpRequest {
-        fn serialize(&self) -> String {
-            let mut result = format!("{} {} {}\r\n", self.method, self.path, self.version);
-            
-            for (key, value) in &self.headers {
-                result.push_str(&format!("{}: {}\r\n", key, value));
-            }
-            
-            result.push_str("\r\n");
-            result.push_str(&self.body);
-            
-            result
-        }
+    fn body(&mut self, body: &str) -> &mut Self {
+        self.body = body.to_string();
+        self
     }
     
-    let mut headers = HashMap::new();
-    headers.insert("Host".to_string(), "example.com".to_string());
-    headers.insert("Content-Type".to_string(), "application/json".to_string());
-    headers.insert("Content-Length".to_string(), "13".to_string());
+    // 未设置Content-Length时根据body的字节数自动补上
+    fn serialize(&self) -> String {
+        let mut result = format!("{} {} {}\r\n", self.method, self.path, self.version);
+        
+        for (key, value) in &self.headers {
+            result.push_str(&format!("{}: {}\r\n", key, value));
+        }
+        
+        let has_length = self
+            .headers
+            .iter()
+            .any(|(key, _)| key.eq_ignore_ascii_case("Content-Length"));
+        if !has_length {
+            result.push_str(&format!("Content-Length: {}\r\n", self.body.len()));
+        }
+        
+        result.push_str("\r\n");
+        result.push_str(&self.body);
+        
+        result
+    }
+}
+
+// HTTP消息示例
+fn http_message_example() {
+    println!("HTTP消息序列化：");
     
-    let request = HttpRequest {
-        method: "POST".to_string(),
-        path: "/api/users".to_string(),
-        version: "HTTP/1.1".to_string(),
-        headers,
-        body: r#"{"name":"张三"}"#.to_string(),
-    };
+    let mut request = HttpRequest::new("POST", "/api/users");
+    request
+        .header("Host", "example.com")
+        .header("Content-Type", "application/json")
+        .body(r#"{"name":"张三"}"#);
     
     let serialized = request.serialize();
     println!("  HTTP请求:\n{}", serialized);
 }
 
-// 自定义协议示例
-fn custom_protocol_example() {
-    println!("自定义协议示例：");
+// CRC32 (IEEE 802.3) 查找表，编译期生成
+const CRC32_TABLE: [u32; 256] = build_crc32_table();
+
+const fn build_crc32_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in data {
+        crc = (crc >> 8) ^ CRC32_TABLE[((crc ^ byte as u32) & 0xFF) as usize];
+    }
+    !crc
+}
+
+// 消息反序列化错误
+#[derive(Debug, PartialEq)]
+enum MessageError {
+    // 数据不足一个完整的帧
+    Truncated,
+    // 不支持的协议版本
+    BadVersion(u8),
+    // 校验和不匹配
+    BadCrc { expected: u32, actual: u32 },
+}
+
+impl fmt::Display for MessageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MessageError::Truncated => write!(f, "数据不完整"),
+            MessageError::BadVersion(version) => write!(f, "不支持的协议版本: {}", version),
+            MessageError::BadCrc { expected, actual } => {
+                write!(f, "CRC校验失败: 期望 {:08X}，实际 {:08X}", expected, actual)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MessageError {}
+
+// 帧格式: [版本 1][类型 1][序列号 4][载荷长度 4][载荷][CRC32 4]
+// 多字节整数均为大端，CRC覆盖CRC之前的所有字节
+#[derive(Debug, PartialEq)]
+struct Message {
+    msg_type: u8,
+    sequence: u32,
+    payload: Vec<u8>,
+}
+
+impl Message {
+    const VERSION: u8 = 1;
+    const HEADER_LEN: usize = 10;
+    const CRC_LEN: usize = 4;
     
-    #[derive(Debug)]
-    struct Message {
-        msg_type: u8,
-        sequence: u32,
-        payload: Vec<u8>,
+    fn serialize(&self) -> Vec<u8> {
+        let mut result = Vec::with_capacity(Self::HEADER_LEN + self.payload.len() + Self::CRC_LEN);
+        
+        // 协议版本 (1字节)
+        result.push(Self::VERSION);
+        
+        // 消息类型 (1字节)
+        result.push(self.msg_type);
+        
+        // 序列号 (4字节，大端)
+        result.extend_from_slice(&self.sequence.to_be_bytes());
+        
+        // 载荷长度 (4字节，大端)
+        result.extend_from_slice(&(self.payload.len() as u32).to_be_bytes());
+        
+        // 载荷数据
+        result.extend_from_slice(&self.payload);
+        
+        // 校验和 (4字节，大端)
+        let crc = crc32(&result);
+        result.extend_from_slice(&crc.to_be_bytes());
+        
+        result
     }
     
-    impl Message {
-        fn serialize(&self) -> Vec<u8> {
-            let mut result = Vec::new();
-            
-            // 消息类型 (1字节)
-            result.push(self.msg_type);
-            
-            // 序列号 (4字节，大端)
-            result.extend_from_slice(&self.sequence.to_be_bytes());
-            
-            // 载荷长度 (4字节，大端)
-            result.extend_from_slice(&(self.payload.len() as u32).to_be_bytes());
-            
-            // 载荷数据
-            result.extend_from_slice(&self.payload);
-            
-            result
+    fn deserialize(data: &[u8]) -> Result<Self, MessageError> {
+        if data.len() < Self::HEADER_LEN + Self::CRC_LEN {
+            return Err(MessageError::Truncated);
         }
         
-        fn deserialize(data: &[u8]) -> Result<Self, String> {
-            if data.len() < 9 {
-                return Err("数据太短".to_string());
-            }
-            
-            let msg_type = data[0];
-            let sequence = u32::from_be_bytes([data[1], data[2], data[3], data[4]]);
-            let payload_len = u32::from_be_bytes([data[5], data[6], data[7], data[8]]) as usize;
-            
-            if data.len() < 9 + payload_len {
-                return Err("载荷数据不完整".to_string());
-            }
-            
-            let payload = data[9..9 + payload_len].to_vec();
-            
-            Ok(Message { msg_type, sequence, payload })
+        if data[0] != Self::VERSION {
+            return Err(MessageError::BadVersion(data[0]));
+        }
+        
+        let msg_type = data[1];
+        let sequence = u32::from_be_bytes([data[2], data[3], data[4], data[5]]);
+        let payload_len = u32::from_be_bytes([data[6], data[7], data[8], data[9]]) as usize;
+        
+        let body_end = Self::HEADER_LEN + payload_len;
+        if data.len() < body_end + Self::CRC_LEN {
+            return Err(MessageError::Truncated);
+        }
+        
+        let crc_bytes = &data[body_end..body_end + Self::CRC_LEN];
+        let expected = u32::from_be_bytes([crc_bytes[0], crc_bytes[1], crc_bytes[2], crc_bytes[3]]);
+        let actual = crc32(&data[..body_end]);
+        if expected != actual {
+            return Err(MessageError::BadCrc { expected, actual });
         }
+        
+        let payload = data[Self::HEADER_LEN..body_end].to_vec();
+        
+        Ok(Message { msg_type, sequence, payload })
     }
+}
+
+// 自定义协议示例
+fn custom_protocol_example() {
+    println!("自定义协议示例：");
     
     let message = Message {
         msg_type: 1,
@@ -915,49 +2360,140 @@ fn custom_protocol_example() {
     let serialized = message.serialize();
     println!("  序列化消息: {:02X?}", serialized);
     
-    match Message::deserialize(&serialized) {
-        Ok(deserialized) => {
-            println!("  反序列化成功: {:?}", deserialized);
-            println!("  载荷内容: {}", String::from_utf8_lossy(&deserialized.payload));
-        }
-        Err(e) => println!("  反序列化失败: {}", e),
+    match Message::deserialize(&serialized) {
+        Ok(deserialized) => {
+            println!("  反序列化成功: {:?}", deserialized);
+            println!("  载荷内容: {}", String::from_utf8_lossy(&deserialized.payload));
+        }
+        Err(e) => println!("  反序列化失败: {}", e),
+    }
+    
+    // 传输中损坏的数据会被CRC校验拦截
+    let mut corrupted = serialized.clone();
+    corrupted[Message::HEADER_LEN] ^= 0x01;
+    if let Err(e) = Message::deserialize(&corrupted) {
+        println!("  损坏的消息: {}", e);
+    }
+}
+
+// 长度前缀编码：[长度 4字节小端][UTF-8数据]
+fn encode_string(s: &str) -> Vec<u8> {
+    let bytes = s.as_bytes();
+    let mut result = Vec::new();
+    
+    // 长度前缀 (4字节)
+    result.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+    
+    // 字符串数据
+    result.extend_from_slice(bytes);
+    
+    result
+}
+
+fn decode_string(data: &[u8]) -> Result<(String, usize), String> {
+    if data.len() < 4 {
+        return Err("数据太短".to_string());
+    }
+    
+    let length = u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as usize;
+    
+    if data.len() < 4 + length {
+        return Err("字符串数据不完整".to_string());
     }
+    
+    let string_bytes = &data[4..4 + length];
+    let string = String::from_utf8(string_bytes.to_vec())
+        .map_err(|_| "无效的UTF-8数据")?;
+    
+    Ok((string, 4 + length))
 }
 
-// 长度前缀协议
-fn length_prefixed_protocol() {
-    println!("长度前缀协议：");
+// 默认最多接受16MB的帧，防止恶意长度前缀导致巨大的内存分配
+const DEFAULT_MAX_FRAME_LEN: usize = 16 * 1024 * 1024;
+
+// 从流中逐帧读取长度前缀数据，适用于套接字等数据分批到达的场景
+// 读取中途出错（如非阻塞套接字的WouldBlock）时已读到的部分会保留，下次调用继续
+struct FrameReader<R: Read> {
+    reader: R,
+    max_len: usize,
+    header: [u8; 4],
+    header_filled: usize,
+    body: Vec<u8>,
+    body_filled: usize,
+    in_body: bool,
+}
+
+impl<R: Read> FrameReader<R> {
+    fn new(reader: R) -> Self {
+        FrameReader {
+            reader,
+            max_len: DEFAULT_MAX_FRAME_LEN,
+            header: [0; 4],
+            header_filled: 0,
+            body: Vec::new(),
+            body_filled: 0,
+            in_body: false,
+        }
+    }
     
-    fn encode_string(s: &str) -> Vec<u8> {
-        let bytes = s.as_bytes();
-        let mut result = Vec::new();
-        
-        // 长度前缀 (4字节)
-        result.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
-        
-        // 字符串数据
-        result.extend_from_slice(bytes);
-        
-        result
+    fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
     }
     
-    fn decode_string(data: &[u8]) -> Result<(String, usize), String> {
-        if data.len() < 4 {
-            return Err("数据太短".to_string());
+    // 读取下一帧，在帧边界处遇到EOF返回None，帧中途遇到EOF返回UnexpectedEof，
+    // 长度前缀超过上限返回InvalidData（不会分配内存）
+    fn next_frame(&mut self) -> io::Result<Option<Vec<u8>>> {
+        while !self.in_body {
+            if self.header_filled == self.header.len() {
+                let length = u32::from_le_bytes(self.header) as usize;
+                if length > self.max_len {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("帧长度 {} 超过上限 {}", length, self.max_len),
+                    ));
+                }
+                self.body = vec![0; length];
+                self.body_filled = 0;
+                self.in_body = true;
+                break;
+            }
+            
+            let read = match self.reader.read(&mut self.header[self.header_filled..]) {
+                Ok(read) => read,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            if read == 0 {
+                if self.header_filled == 0 {
+                    return Ok(None);
+                }
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "长度前缀不完整"));
+            }
+            self.header_filled += read;
         }
         
-        let length = u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as usize;
-        
-        if data.len() < 4 + length {
-            return Err("字符串数据不完整".to_string());
+        while self.body_filled < self.body.len() {
+            let read = match self.reader.read(&mut self.body[self.body_filled..]) {
+                Ok(read) => read,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            if read == 0 {
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "帧数据不完整"));
+            }
+            self.body_filled += read;
         }
         
-        let string_bytes = &data[4..4 + length];
-        let string = String::from_utf8(string_bytes.to_vec())
-            .map_err(|_| "无效的UTF-8数据")?;
-        
-        Ok((string, 4 + length))
+        self.header_filled = 0;
+        self.in_body = false;
+        Ok(Some(std::mem::take(&mut self.body)))
     }
+}
+
+// 长度前缀协议
+fn length_prefixed_protocol() {
+    println!("长度前缀协议：");
     
     let messages = ["Hello", "World", "Rust编程"];
     let mut encoded_data = Vec::new();
@@ -986,6 +2522,98 @@ fn length_prefixed_protocol() {
     }
     
     println!("  解码消息: {:?}", decoded_messages);
+    
+    // 从流中逐帧读取
+    let mut frames = FrameReader::new(io::Cursor::new(&encoded_data)).with_max_len(1024);
+    while let Ok(Some(frame)) = frames.next_frame() {
+        println!("  读取帧: {}", String::from_utf8_lossy(&frame));
+    }
+}
+
+// TLV编码：每条记录为 [类型 1字节][长度 4字节大端][值]
+mod tlv {
+    use std::io::{self, Read, Write};
+    
+    // 默认最多接受16MB的值，防止恶意长度导致巨大的内存分配
+    pub const DEFAULT_MAX_LEN: usize = 16 * 1024 * 1024;
+    
+    pub fn encode<W: Write>(type_: u8, value: &[u8], out: &mut W) -> io::Result<()> {
+        let len = u32::try_from(value.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "TLV值超过u32长度"))?;
+        out.write_all(&[type_])?;
+        out.write_all(&len.to_be_bytes())?;
+        out.write_all(value)
+    }
+    
+    pub struct TlvReader<R: Read> {
+        reader: R,
+        max_len: usize,
+    }
+    
+    impl<R: Read> TlvReader<R> {
+        pub fn new(reader: R) -> Self {
+            TlvReader {
+                reader,
+                max_len: DEFAULT_MAX_LEN,
+            }
+        }
+        
+        pub fn with_max_len(mut self, max_len: usize) -> Self {
+            self.max_len = max_len;
+            self
+        }
+        
+        // 在记录边界处遇到EOF返回None，记录中途截断返回UnexpectedEof，
+        // 声明的长度超过上限返回InvalidData（不会分配内存）
+        pub fn next(&mut self) -> io::Result<Option<(u8, Vec<u8>)>> {
+            let mut header = [0u8; 5];
+            let mut filled = 0;
+            while filled < header.len() {
+                match self.reader.read(&mut header[filled..]) {
+                    Ok(0) if filled == 0 => return Ok(None),
+                    Ok(0) => {
+                        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "TLV头部不完整"));
+                    }
+                    Ok(read) => filled += read,
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(e) => return Err(e),
+                }
+            }
+            
+            let len = u32::from_be_bytes([header[1], header[2], header[3], header[4]]) as usize;
+            if len > self.max_len {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("TLV长度 {} 超过上限 {}", len, self.max_len),
+                ));
+            }
+            
+            let mut value = vec![0; len];
+            self.reader.read_exact(&mut value).map_err(|e| {
+                if e.kind() == io::ErrorKind::UnexpectedEof {
+                    io::Error::new(io::ErrorKind::UnexpectedEof, "TLV值不完整")
+                } else {
+                    e
+                }
+            })?;
+            Ok(Some((header[0], value)))
+        }
+    }
+}
+
+fn tlv_protocol_example() {
+    println!("TLV编码：");
+    
+    let mut encoded = Vec::new();
+    tlv::encode(1, b"alice", &mut encoded).unwrap();
+    tlv::encode(2, &30u32.to_be_bytes(), &mut encoded).unwrap();
+    tlv::encode(3, "北京".as_bytes(), &mut encoded).unwrap();
+    println!("  编码数据: {:02X?}", encoded);
+    
+    let mut reader = tlv::TlvReader::new(io::Cursor::new(&encoded)).with_max_len(1024);
+    while let Ok(Some((type_, value))) = reader.next() {
+        println!("  类型 {}: {:02X?}", type_, value);
+    }
 }
 
 // 最佳实践
@@ -1105,4 +2733,716 @@ mod tests {
         
         assert_eq!(data, deserialized);
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_color_parse_formats() {
+        let orange = Color { r: 0xFF, g: 0x88, b: 0x00 };
+        assert_eq!("#FF8800".parse::<Color>(), Ok(orange));
+        assert_eq!("#f80".parse::<Color>(), Ok(orange));
+        assert_eq!("rgb(255, 136, 0)".parse::<Color>(), Ok(orange));
+        assert_eq!("RGB(255,136,0)".parse::<Color>(), Ok(orange));
+        
+        assert_eq!("red".parse::<Color>(), Ok(Color { r: 255, g: 0, b: 0 }));
+        assert_eq!("Green".parse::<Color>(), Ok(Color { r: 0, g: 128, b: 0 }));
+        assert_eq!("blue".parse::<Color>(), Ok(Color { r: 0, g: 0, b: 255 }));
+        assert_eq!("black".parse::<Color>(), Ok(Color { r: 0, g: 0, b: 0 }));
+        assert_eq!("white".parse::<Color>(), Ok(Color { r: 255, g: 255, b: 255 }));
+        
+        // to_hex输出可以再解析回同一个颜色
+        for input in ["#FF8800", "#f80", "rgb(12, 34, 56)", "white"] {
+            let color: Color = input.parse().unwrap();
+            assert_eq!(color.to_hex().parse::<Color>(), Ok(color));
+        }
+        assert_eq!(orange.to_hex(), "#FF8800");
+        assert_eq!(orange.to_string(), "RGB(255, 136, 0)");
+    }
+    
+    #[test]
+    fn test_color_parse_errors() {
+        assert_eq!("#FFFF".parse::<Color>(), Err(ColorParseError::BadLength(4)));
+        assert_eq!("#".parse::<Color>(), Err(ColorParseError::BadLength(0)));
+        assert_eq!("rgb(1, 2)".parse::<Color>(), Err(ColorParseError::BadLength(2)));
+        assert_eq!("#GG0000".parse::<Color>(), Err(ColorParseError::BadDigit("G".to_string())));
+        assert_eq!("rgb(1, 2, 300)".parse::<Color>(), Err(ColorParseError::BadDigit("300".to_string())));
+        assert_eq!("purple".parse::<Color>(), Err(ColorParseError::UnknownName("purple".to_string())));
+    }
+    
+    #[test]
+    fn test_csv_reader_quoted_fields() {
+        let data = "id,name,note\r\n1,\"Smith, John\",\"say \"\"hi\"\"\"\n\n2,\"first line\nsecond line\",plain\n3,,\n";
+        let records: Vec<Vec<String>> = CsvReader::new(data.as_bytes())
+            .records()
+            .collect::<io::Result<_>>()
+            .unwrap();
+        
+        assert_eq!(records, vec![
+            vec!["id", "name", "note"],
+            vec!["1", "Smith, John", "say \"hi\""],
+            vec!["2", "first line\nsecond line", "plain"],
+            vec!["3", "", ""],
+        ]);
+    }
+    
+    #[test]
+    fn test_csv_reader_last_line_without_newline() {
+        let mut reader = CsvReader::new("a,\"b\"".as_bytes());
+        assert_eq!(reader.read_record().unwrap(), Some(vec!["a".to_string(), "b".to_string()]));
+        assert_eq!(reader.read_record().unwrap(), None);
+    }
+    
+    #[test]
+    fn test_csv_reader_unterminated_quote() {
+        let mut records = CsvReader::new("1,\"never closed\n2,x\n".as_bytes()).records();
+        let err = records.next().unwrap().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+    
+    #[test]
+    fn test_csv_writer_round_trip() {
+        let records: Vec<Vec<&str>> = vec![
+            vec!["plain", "Smith, John", "say \"hi\""],
+            vec!["multi\nline", "crlf\r\nfield", ""],
+            vec!["\"", ",", "trailing space "],
+            vec![""],
+        ];
+        
+        let mut writer = CsvWriter::new(Vec::new());
+        for record in &records {
+            writer.write_record(record).unwrap();
+        }
+        writer
+            .write_record_iter((1..=3).map(|n| format!("{},{}", n, n * 10)))
+            .unwrap();
+        let output = writer.into_inner();
+        
+        let text = String::from_utf8(output.clone()).unwrap();
+        assert!(text.starts_with("plain,\"Smith, John\",\"say \"\"hi\"\"\"\n"));
+        
+        let parsed: Vec<Vec<String>> = CsvReader::new(&output[..])
+            .records()
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(parsed.len(), 5);
+        for (parsed, expected) in parsed.iter().zip(&records) {
+            assert_eq!(parsed, expected);
+        }
+        assert_eq!(parsed[4], vec!["1,10", "2,20", "3,30"]);
+    }
+    
+    #[test]
+    fn test_ini_round_trip() {
+        let mut config = IniConfig::new();
+        config.set("", "app", "demo");
+        config.set("database", "host", "localhost");
+        config.set("database", "port", "5432");
+        config.set("server", "port", "8080");
+        
+        let parsed = IniConfig::from_ini(&config.to_ini()).unwrap();
+        assert_eq!(parsed, config);
+        assert_eq!(parsed.get("", "app"), Some("demo"));
+        assert_eq!(parsed.get("database", "port"), Some("5432"));
+        assert_eq!(parsed.get("server", "host"), None);
+        assert_eq!(parsed.get("missing", "port"), None);
+    }
+    
+    #[test]
+    fn test_ini_parse_comments_and_whitespace() {
+        let text = "  top = level  \n; comment\n# another\n\n[ section ]\n  key =  value with spaces \nempty=\n";
+        let config = IniConfig::from_ini(text).unwrap();
+        assert_eq!(config.get("", "top"), Some("level"));
+        assert_eq!(config.get("section", "key"), Some("value with spaces"));
+        assert_eq!(config.get("section", "empty"), Some(""));
+    }
+    
+    #[test]
+    fn test_ini_parse_errors_report_line() {
+        let err = IniConfig::from_ini("[ok]\na=1\nmissing equals\n").unwrap_err();
+        assert_eq!(err.line, 3);
+        
+        let err = IniConfig::from_ini("\n[unclosed\n").unwrap_err();
+        assert_eq!(err.line, 2);
+        assert!(err.to_string().starts_with("第2行"));
+        
+        assert_eq!(IniConfig::from_ini("=value").unwrap_err().line, 1);
+    }
+    
+    #[test]
+    fn test_crc32_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+    
+    #[test]
+    fn test_message_round_trip() {
+        let message = Message {
+            msg_type: 7,
+            sequence: 0xDEAD_BEEF,
+            payload: "载荷 payload".as_bytes().to_vec(),
+        };
+        
+        let bytes = message.serialize();
+        assert_eq!(bytes[0], Message::VERSION);
+        assert_eq!(bytes.len(), Message::HEADER_LEN + message.payload.len() + Message::CRC_LEN);
+        assert_eq!(Message::deserialize(&bytes), Ok(message));
+        
+        let empty = Message { msg_type: 0, sequence: 0, payload: Vec::new() };
+        assert_eq!(Message::deserialize(&empty.serialize()), Ok(empty));
+    }
+    
+    #[test]
+    fn test_message_rejects_corruption() {
+        let message = Message { msg_type: 1, sequence: 42, payload: b"hello".to_vec() };
+        let bytes = message.serialize();
+        
+        let mut flipped = bytes.clone();
+        flipped[Message::HEADER_LEN + 1] ^= 0x20;
+        assert!(matches!(Message::deserialize(&flipped), Err(MessageError::BadCrc { .. })));
+        
+        let mut bad_version = bytes.clone();
+        bad_version[0] = 9;
+        assert_eq!(Message::deserialize(&bad_version), Err(MessageError::BadVersion(9)));
+        
+        assert_eq!(Message::deserialize(&bytes[..bytes.len() - 1]), Err(MessageError::Truncated));
+        assert_eq!(Message::deserialize(&bytes[..5]), Err(MessageError::Truncated));
+    }
+    
+    // 每次最多返回chunk个字节的读取器，模拟分批到达的网络数据
+    struct ChunkedReader<R: Read> {
+        inner: R,
+        chunk: usize,
+    }
+    
+    impl<R: Read> Read for ChunkedReader<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let len = buf.len().min(self.chunk);
+            self.inner.read(&mut buf[..len])
+        }
+    }
+    
+    #[test]
+    fn test_frame_reader_streams_frames() {
+        let mut stream = Vec::new();
+        for message in ["Hello", "", "Rust编程 frames"] {
+            stream.extend_from_slice(&encode_string(message));
+        }
+        
+        for chunk in [1, 3, 7, 64] {
+            let reader = ChunkedReader { inner: io::Cursor::new(stream.clone()), chunk };
+            let mut frames = FrameReader::new(reader);
+            
+            assert_eq!(frames.next_frame().unwrap(), Some(b"Hello".to_vec()));
+            assert_eq!(frames.next_frame().unwrap(), Some(Vec::new()));
+            assert_eq!(frames.next_frame().unwrap(), Some("Rust编程 frames".as_bytes().to_vec()));
+            assert_eq!(frames.next_frame().unwrap(), None);
+        }
+    }
+    
+    #[test]
+    fn test_frame_reader_truncated() {
+        let mut stream = encode_string("complete");
+        stream.extend_from_slice(&encode_string("cut off"));
+        stream.truncate(stream.len() - 3);
+        
+        let mut frames = FrameReader::new(io::Cursor::new(stream));
+        assert_eq!(frames.next_frame().unwrap(), Some(b"complete".to_vec()));
+        assert_eq!(frames.next_frame().unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+        
+        // 长度前缀本身被截断
+        let mut frames = FrameReader::new(io::Cursor::new(vec![5u8, 0]));
+        assert_eq!(frames.next_frame().unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+    }
+    
+    #[test]
+    fn test_frame_reader_rejects_oversized_length() {
+        // 声明长度为4GB-1，但实际没有数据
+        let mut frames = FrameReader::new(io::Cursor::new(vec![0xFFu8; 4]));
+        assert_eq!(frames.next_frame().unwrap_err().kind(), io::ErrorKind::InvalidData);
+        
+        let stream = encode_string(&"x".repeat(100));
+        let mut frames = FrameReader::new(io::Cursor::new(stream.clone())).with_max_len(99);
+        assert_eq!(frames.next_frame().unwrap_err().kind(), io::ErrorKind::InvalidData);
+        let mut frames = FrameReader::new(io::Cursor::new(stream)).with_max_len(100);
+        assert_eq!(frames.next_frame().unwrap().unwrap().len(), 100);
+    }
+    
+    #[test]
+    fn test_tlv_round_trip_through_cursor() {
+        let records: [(u8, &[u8]); 3] = [(1, b"name"), (7, b""), (255, &[0, 1, 2, 3, 4])];
+        let mut stream = Vec::new();
+        for (type_, value) in records {
+            tlv::encode(type_, value, &mut stream).unwrap();
+        }
+        assert_eq!(&stream[..5], &[1, 0, 0, 0, 4]);
+        
+        let mut reader = tlv::TlvReader::new(io::Cursor::new(&stream));
+        for (type_, value) in records {
+            assert_eq!(reader.next().unwrap(), Some((type_, value.to_vec())));
+        }
+        assert_eq!(reader.next().unwrap(), None);
+        
+        // 逐字节到达的流同样可以解析
+        let chunked = ChunkedReader { inner: io::Cursor::new(&stream), chunk: 1 };
+        let mut reader = tlv::TlvReader::new(chunked);
+        assert_eq!(reader.next().unwrap(), Some((1, b"name".to_vec())));
+        
+        // 值被截断
+        let mut reader = tlv::TlvReader::new(io::Cursor::new(&stream[..7]));
+        assert_eq!(reader.next().unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+    }
+    
+    #[test]
+    fn test_tlv_rejects_oversized_length() {
+        // 声明长度为4GB-1，但实际没有数据
+        let stream = [9u8, 0xFF, 0xFF, 0xFF, 0xFF];
+        let mut reader = tlv::TlvReader::new(io::Cursor::new(&stream[..]));
+        assert_eq!(reader.next().unwrap_err().kind(), io::ErrorKind::InvalidData);
+        
+        let mut stream = Vec::new();
+        tlv::encode(1, &[0; 100], &mut stream).unwrap();
+        let mut reader = tlv::TlvReader::new(io::Cursor::new(&stream)).with_max_len(99);
+        assert_eq!(reader.next().unwrap_err().kind(), io::ErrorKind::InvalidData);
+        let mut reader = tlv::TlvReader::new(io::Cursor::new(&stream)).with_max_len(100);
+        assert_eq!(reader.next().unwrap().unwrap().1.len(), 100);
+    }
+    
+    #[test]
+    fn test_base64_rfc4648_vectors() {
+        let vectors = [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ];
+        for (plain, encoded) in vectors {
+            assert_eq!(base64_encode(plain.as_bytes()), encoded);
+            assert_eq!(base64_decode(encoded).unwrap(), plain.as_bytes());
+            assert_eq!(base64_url_encode(plain.as_bytes()), encoded.trim_end_matches('='));
+            assert_eq!(base64_url_decode(encoded).unwrap(), plain.as_bytes());
+        }
+        
+        // 两种字母表只在62、63号字符上不同
+        let data = [0xFB, 0xFF, 0xBF];
+        assert_eq!(base64_encode(&data), "+/+/");
+        assert_eq!(base64_url_encode(&data), "-_-_");
+        assert_eq!(base64_url_decode("-_-_").unwrap(), data);
+        
+        let binary: Vec<u8> = (0..=255).collect();
+        assert_eq!(base64_decode(&base64_encode(&binary)).unwrap(), binary);
+    }
+    
+    #[test]
+    fn test_base64_rejects_malformed_input() {
+        assert_eq!(
+            base64_decode("Zm9v!A=="),
+            Err(DecodeError::InvalidCharacter { position: 4, character: '!' })
+        );
+        // 标准字母表里没有-，URL安全字母表里没有+
+        assert!(matches!(base64_decode("-_-_"), Err(DecodeError::InvalidCharacter { .. })));
+        assert!(matches!(base64_url_decode("+/+/"), Err(DecodeError::InvalidCharacter { .. })));
+        assert_eq!(base64_decode("Zm9=v==="), Err(DecodeError::InvalidPadding));
+        assert_eq!(
+            base64_decode("Zm=v"),
+            Err(DecodeError::InvalidCharacter { position: 2, character: '=' })
+        );
+        assert_eq!(base64_decode("Zg"), Err(DecodeError::InvalidLength(2)));
+        assert_eq!(base64_url_decode("Zm9vY"), Err(DecodeError::InvalidLength(5)));
+        assert_eq!(base64_url_decode("Zg").unwrap(), b"f");
+    }
+    
+    #[test]
+    fn test_hex_round_trip() {
+        let data: Vec<u8> = (0..=255).collect();
+        let lower = hex_encode(&data, false);
+        let upper = hex_encode(&data, true);
+        assert!(lower.ends_with("fdfeff"));
+        assert!(upper.ends_with("FDFEFF"));
+        assert_eq!(hex_decode(&lower).unwrap(), data);
+        assert_eq!(hex_decode(&upper).unwrap(), data);
+        
+        assert_eq!(hex_encode(&[], true), "");
+        assert_eq!(hex_decode("").unwrap(), Vec::<u8>::new());
+        assert_eq!(hex_decode("  0xDE ad\nBE\tef ").unwrap(), [0xDE, 0xAD, 0xBE, 0xEF]);
+        assert_eq!(hex_decode("0X0a").unwrap(), [0x0A]);
+    }
+    
+    #[test]
+    fn test_hex_decode_errors() {
+        assert_eq!(hex_decode("abc"), Err(DecodeError::InvalidLength(3)));
+        assert_eq!(hex_decode("0x1 23"), Err(DecodeError::InvalidLength(3)));
+        assert_eq!(
+            hex_decode("12zz"),
+            Err(DecodeError::InvalidCharacter { position: 2, character: 'z' })
+        );
+        // 前缀只能出现在开头
+        assert_eq!(
+            hex_decode("12 0x34"),
+            Err(DecodeError::InvalidCharacter { position: 4, character: 'x' })
+        );
+        assert!(matches!(hex_decode("十六"), Err(DecodeError::InvalidCharacter { position: 0, .. })));
+    }
+    
+    #[test]
+    fn test_bit_writer_reader_mixed_widths() {
+        let mut writer = BitWriter::new();
+        writer.write_bits(0b101, 3);
+        writer.write_bits(0b10001, 5);
+        writer.write_bits(0xABC, 12);
+        writer.write_bits(0b111, 3);
+        // 超出count的高位被忽略
+        writer.write_bits(0xFF, 1);
+        assert_eq!(writer.bit_len(), 24);
+        
+        let bytes = writer.finish();
+        assert_eq!(bytes, [0b1011_0001, 0xAB, 0b1100_1111]);
+        
+        let mut reader = BitReader::new(&bytes);
+        assert_eq!(reader.read_bits(3), Some(0b101));
+        assert_eq!(reader.read_bits(5), Some(0b10001));
+        assert_eq!(reader.read_bits(12), Some(0xABC));
+        assert_eq!(reader.read_bits(3), Some(0b111));
+        assert_eq!(reader.read_bits(2), None);
+        assert_eq!(reader.read_bits(1), Some(1));
+        assert_eq!(reader.read_bits(1), None);
+    }
+    
+    #[test]
+    fn test_bit_writer_partial_byte_and_full_width() {
+        let mut writer = BitWriter::new();
+        writer.write_bits(1, 1);
+        writer.write_bits(u64::MAX - 1, 64);
+        assert_eq!(writer.bit_len(), 65);
+        
+        // 65位占9个字节，最后一个字节只用了最高位
+        let bytes = writer.finish();
+        assert_eq!(bytes.len(), 9);
+        assert_eq!(bytes[8], 0);
+        
+        let mut reader = BitReader::new(&bytes);
+        assert_eq!(reader.read_bits(1), Some(1));
+        assert_eq!(reader.read_bits(64), Some(u64::MAX - 1));
+        assert_eq!(reader.read_bits(7), Some(0));
+        assert_eq!(reader.read_bits(0), Some(0));
+    }
+    
+    #[test]
+    fn test_varint_round_trip() {
+        let cases: [(u64, &[u8]); 5] = [
+            (0, &[0x00]),
+            (1, &[0x01]),
+            (127, &[0x7F]),
+            (300, &[0xAC, 0x02]),
+            (u64::MAX, &[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x01]),
+        ];
+        for (value, expected) in cases {
+            let mut out = Vec::new();
+            write_varint(value, &mut out);
+            assert_eq!(out, expected);
+            assert_eq!(read_varint(&mut io::Cursor::new(&out)).unwrap(), value);
+        }
+        
+        // ZigZag：小的负数也只占一个字节
+        let mut out = Vec::new();
+        write_signed_varint(-1, &mut out);
+        assert_eq!(out, [0x01]);
+        for value in [0, -1, 1, -64, 63, -65, i64::MIN, i64::MAX] {
+            let mut out = Vec::new();
+            write_signed_varint(value, &mut out);
+            assert_eq!(read_signed_varint(&mut io::Cursor::new(&out)).unwrap(), value);
+        }
+    }
+    
+    #[test]
+    fn test_varint_rejects_invalid_sequences() {
+        // 11个字节
+        let mut too_long = vec![0x80; 10];
+        too_long.push(0x01);
+        let err = read_varint(&mut io::Cursor::new(&too_long)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        
+        // 第10个字节超出u64范围
+        let mut overflow = vec![0xFF; 9];
+        overflow.push(0x02);
+        let err = read_varint(&mut io::Cursor::new(&overflow)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        
+        // 序列未结束
+        let err = read_varint(&mut io::Cursor::new(&[0x80, 0x80])).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+    
+    #[test]
+    fn test_msgpack_small_values_match_spec() {
+        let value = JsonValue::parse(r#"{"a":1}"#).unwrap();
+        assert_eq!(to_msgpack(&value), [0x81, 0xA1, b'a', 0x01]);
+        
+        let value = JsonValue::parse(r#"[null, true, false, -1, 1.5, "hi"]"#).unwrap();
+        assert_eq!(
+            to_msgpack(&value),
+            [
+                0x96, 0xC0, 0xC3, 0xC2, 0xFF,
+                0xCB, 0x3F, 0xF8, 0, 0, 0, 0, 0, 0,
+                0xA2, b'h', b'i',
+            ]
+        );
+    }
+    
+    #[test]
+    fn test_msgpack_integer_and_length_boundaries() {
+        let encode = |n: f64| to_msgpack(&JsonValue::Number(n));
+        assert_eq!(encode(0.0), [0x00]);
+        assert_eq!(encode(127.0), [0x7F]);
+        assert_eq!(encode(128.0), [0xCC, 0x80]);
+        assert_eq!(encode(256.0), [0xCD, 0x01, 0x00]);
+        assert_eq!(encode(65536.0), [0xCE, 0x00, 0x01, 0x00, 0x00]);
+        assert_eq!(encode(4294967296.0), [0xCF, 0, 0, 0, 1, 0, 0, 0, 0]);
+        assert_eq!(encode(-32.0), [0xE0]);
+        assert_eq!(encode(-33.0), [0xD0, 0xDF]);
+        assert_eq!(encode(-129.0), [0xD1, 0xFF, 0x7F]);
+        assert_eq!(encode(-32769.0), [0xD2, 0xFF, 0xFF, 0x7F, 0xFF]);
+        assert_eq!(encode(-2147483649.0), [0xD3, 0xFF, 0xFF, 0xFF, 0xFF, 0x7F, 0xFF, 0xFF, 0xFF]);
+        // 超出u64范围的整数按浮点数编码
+        assert_eq!(encode(1e20)[0], 0xCB);
+        
+        // 字符串有str8格式，数组和map从16位长度格式开始
+        let long = to_msgpack(&JsonValue::String("x".repeat(32)));
+        assert_eq!(&long[..2], &[0xD9, 32]);
+        let array = to_msgpack(&JsonValue::Array(vec![JsonValue::Null; 16]));
+        assert_eq!(&array[..3], &[0xDC, 0x00, 0x10]);
+        assert_eq!(array.len(), 3 + 16);
+        let entries = (0..16).map(|i| (i.to_string(), JsonValue::Null)).collect();
+        assert_eq!(&to_msgpack(&JsonValue::Object(entries))[..3], &[0xDE, 0x00, 0x10]);
+    }
+    
+    #[test]
+    fn test_points_round_trip_both_endians() {
+        let points = vec![
+            Point3D { x: 1.0, y: 2.5, z: -3.75 },
+            Point3D { x: 0.0, y: -0.0, z: f32::MAX },
+            Point3D { x: 1e-10, y: 12345.678, z: -1.0 },
+        ];
+        
+        for endian in [Endian::Little, Endian::Big] {
+            let mut bytes = Vec::new();
+            write_points(&points, &mut bytes, endian);
+            assert_eq!(bytes.len(), 36);
+            assert_eq!(read_points(&bytes, endian).unwrap(), points);
+        }
+        
+        // 两种字节序的编码互为字节反转
+        let mut little = Vec::new();
+        let mut big = Vec::new();
+        write_points(&points[..1], &mut little, Endian::Little);
+        write_points(&points[..1], &mut big, Endian::Big);
+        assert_eq!(&little[..4], &[0x00, 0x00, 0x80, 0x3F]);
+        assert_eq!(&big[..4], &[0x3F, 0x80, 0x00, 0x00]);
+        assert_eq!(little[..], points[0].to_bytes()[..]);
+    }
+    
+    #[test]
+    fn test_read_points_length_mismatch() {
+        assert_eq!(read_points(&[], Endian::Little).unwrap(), Vec::new());
+        assert_eq!(
+            read_points(&[0u8; 13], Endian::Big),
+            Err(DecodeError::LengthMismatch { len: 13, element_size: 12 })
+        );
+    }
+    
+    #[test]
+    fn test_json_escape() {
+        assert_eq!(json_escape("plain 中文"), "plain 中文");
+        assert_eq!(json_escape("a\"b\\c"), r#"a\"b\\c"#);
+        assert_eq!(json_escape("line\n\ttab\r"), r"line\n\ttab\r");
+        assert_eq!(json_escape("\u{01}\u{08}\u{0C}"), r"\u0001\b\f");
+        
+        assert_eq!(json_unescape(r"中\u0041\ud83e\udd80\/").unwrap(), "中A🦀/");
+        assert!(json_unescape(r"bad \x").is_err());
+        assert!(json_unescape(r"\ud83e").is_err());
+        assert!(json_unescape(r"\u+041").is_err());
+    }
+    
+    #[test]
+    fn test_user_json_round_trip_with_special_chars() {
+        let user = User {
+            id: 7,
+            name: "he said \"hi\"\n".to_string(),
+            email: "back\\slash,\tcomma@example.com".to_string(),
+            active: false,
+        };
+        
+        let json = user.to_json();
+        assert!(json.contains(r#""name": "he said \"hi\"\n","#));
+        assert_eq!(json.lines().count(), 6);
+        assert_eq!(User::from_json_simple(&json).unwrap(), user);
+    }
+    
+    #[test]
+    fn test_json_value_nested() {
+        let input = r#" {"name": "rust", "tags": ["fast", "safe"], "meta": {"stars": 100, "open": true, "owner": null}, "empty": [{}, []]} "#;
+        let value = JsonValue::parse(input).unwrap();
+        
+        assert_eq!(value.get("name"), Some(&JsonValue::String("rust".to_string())));
+        assert_eq!(
+            value.get("tags"),
+            Some(&JsonValue::Array(vec![
+                JsonValue::String("fast".to_string()),
+                JsonValue::String("safe".to_string()),
+            ]))
+        );
+        let meta = value.get("meta").unwrap();
+        assert_eq!(meta.get("stars"), Some(&JsonValue::Number(100.0)));
+        assert_eq!(meta.get("open"), Some(&JsonValue::Bool(true)));
+        assert_eq!(meta.get("owner"), Some(&JsonValue::Null));
+        
+        // 序列化后再次解析得到相同的值，键的顺序保持不变
+        let serialized = value.to_string();
+        assert!(serialized.starts_with(r#"{"name":"rust","tags":["fast","safe"]"#));
+        assert_eq!(JsonValue::parse(&serialized).unwrap(), value);
+    }
+    
+    #[test]
+    fn test_json_value_numbers() {
+        let value = JsonValue::parse("[0, -1, 3.25, 1e3, -2.5E-2, 12345678901]").unwrap();
+        assert_eq!(value, JsonValue::Array(vec![
+            JsonValue::Number(0.0),
+            JsonValue::Number(-1.0),
+            JsonValue::Number(3.25),
+            JsonValue::Number(1000.0),
+            JsonValue::Number(-0.025),
+            JsonValue::Number(12345678901.0),
+        ]));
+        assert_eq!(value.to_string(), "[0,-1,3.25,1000,-0.025,12345678901]");
+        
+        for bad in ["01", "-", "1.", "1e", "+1", ".5"] {
+            assert!(JsonValue::parse(bad).is_err(), "{} 应该解析失败", bad);
+        }
+    }
+    
+    #[test]
+    fn test_json_value_strings() {
+        let value = JsonValue::parse(r#""quote \" slash \\ newline \n unicode 中""#).unwrap();
+        assert_eq!(value, JsonValue::String("quote \" slash \\ newline \n unicode 中".to_string()));
+        assert_eq!(value.to_string(), r#""quote \" slash \\ newline \n unicode 中""#);
+        
+        assert!(JsonValue::parse("\"unterminated").is_err());
+        assert!(JsonValue::parse("\"raw\nnewline\"").is_err());
+        assert!(JsonValue::parse(r#"{"a": 1,}"#).is_err());
+        assert!(JsonValue::parse("[1] extra").is_err());
+    }
+    
+    #[test]
+    fn test_json_value_depth_limit() {
+        let depth = JsonValue::MAX_DEPTH;
+        let ok = format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        assert!(JsonValue::parse(&ok).is_ok());
+        
+        let hostile = "[".repeat(100_000);
+        let err = JsonValue::parse(&hostile).unwrap_err();
+        assert_eq!(err.position, depth);
+        assert!(err.message.contains("嵌套"));
+    }
+    
+    #[test]
+    fn test_http_request_serialize_deterministic() {
+        let mut request = HttpRequest::new("POST", "/api/users");
+        request
+            .header("Host", "example.com")
+            .header("Accept", "*/*")
+            .header("X-Trace", "1")
+            .body(r#"{"name":"张三"}"#);
+        
+        let first = request.serialize();
+        let second = request.serialize();
+        assert_eq!(first, second);
+        
+        // 头部保持插入顺序，Content-Length按UTF-8字节数计算
+        let expected_length = r#"{"name":"张三"}"#.len();
+        assert_eq!(expected_length, 17);
+        assert_eq!(
+            first,
+            format!(
+                "POST /api/users HTTP/1.1\r\nHost: example.com\r\nAccept: */*\r\nX-Trace: 1\r\nContent-Length: {}\r\n\r\n{{\"name\":\"张三\"}}",
+                expected_length
+            )
+        );
+    }
+    
+    #[test]
+    fn test_http_request_keeps_explicit_content_length() {
+        let mut request = HttpRequest::new("GET", "/");
+        request.header("content-length", "0");
+        
+        let serialized = request.serialize();
+        assert_eq!(serialized.matches("ength:").count(), 1);
+        assert!(serialized.ends_with("content-length: 0\r\n\r\n"));
+    }
+    
+    #[test]
+    fn test_table_formatter_aligns_columns() {
+        let mut table = TableFormatter::new()
+            .header(&["name", "qty"])
+            .align(1, Align::Right);
+        table.add_row(&["apple", "3"]);
+        table.add_row(&["kiwi", "120"]);
+        
+        let expected = "name  | qty\n\
+                        ----- | ---\n\
+                        apple |   3\n\
+                        kiwi  | 120\n";
+        assert_eq!(table.render(), expected);
+    }
+    
+    #[test]
+    fn test_table_formatter_wide_chars() {
+        let mut table = TableFormatter::new().separator("|");
+        table.add_row(&["张三", "x"]);
+        table.add_row(&["Bob", "y"]);
+        
+        // "张三"显示宽度为4，"Bob"补一个空格
+        assert_eq!(table.render(), "张三|x\nBob |y\n");
+    }
+    
+    #[test]
+    fn test_table_formatter_empty_and_single_column() {
+        assert_eq!(TableFormatter::new().render(), "");
+        
+        let mut table = TableFormatter::new().header(&["id"]);
+        table.add_row(&["1"]);
+        table.add_row(&["100"]);
+        assert_eq!(table.render(), "id\n---\n1\n100\n");
+    }
+    
+    #[test]
+    fn test_config_round_trip_special_chars() {
+        let config = Config {
+            host: "a=b\nc\\d\r".to_string(),
+            port: 8080,
+            debug: true,
+            timeout: 2.5,
+        };
+        
+        let serialized = config.serialize();
+        assert_eq!(serialized.lines().count(), 4);
+        assert!(serialized.starts_with("host=a\\=b\\nc\\\\d\\r\n"));
+        
+        assert_eq!(Config::deserialize(&serialized).unwrap(), config);
+    }
+    
+    #[test]
+    fn test_config_errors() {
+        assert_eq!(
+            Config::deserialize("host=x\nretries=3"),
+            Err(ConfigError::UnknownKey("retries".to_string()))
+        );
+        assert_eq!(
+            Config::deserialize("port=abc"),
+            Err(ConfigError::InvalidValue {
+                key: "port".to_string(),
+                value: "abc".to_string(),
+            })
+        );
+        assert!(Config::deserialize("host=bad\\q").is_err());
+    }
+}
@@ -277,6 +277,7 @@ fn path_and_import() {
 // 案例4：实际项目结构模拟
 mod web_server {
     pub mod http {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
         pub enum Method {
             GET,
             POST,
@@ -284,6 +285,19 @@ mod web_server {
             DELETE,
         }
         
+        impl Method {
+            pub const ALL: [Method; 4] = [Method::GET, Method::POST, Method::PUT, Method::DELETE];
+            
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    Method::GET => "GET",
+                    Method::POST => "POST",
+                    Method::PUT => "PUT",
+                    Method::DELETE => "DELETE",
+                }
+            }
+        }
+        
         pub struct Request {
             pub method: Method,
             pub path: String,
@@ -302,10 +316,59 @@ mod web_server {
             pub fn add_header(&mut self, key: &str, value: &str) {
                 self.headers.insert(key.to_string(), value.to_string());
             }
+            
+            // 解析路径中 ? 之后的查询参数
+            pub fn query_params(&self) -> std::collections::HashMap<String, String> {
+                match self.path.split_once('?') {
+                    Some((_, query)) => parse_form(query),
+                    None => std::collections::HashMap::new(),
+                }
+            }
+        }
+        
+        // 解析 application/x-www-form-urlencoded 格式：k1=v1&k2=v2
+        // 重复的键以最后一次出现的值为准，没有 = 的键值为空字符串
+        pub fn parse_form(body: &str) -> std::collections::HashMap<String, String> {
+            body.split('&')
+                .filter(|pair| !pair.is_empty())
+                .map(|pair| {
+                    let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                    (percent_decode(key), percent_decode(value))
+                })
+                .collect()
+        }
+        
+        // 百分号解码：%XX还原为字节，+还原为空格；不合法的 % 序列原样保留
+        pub fn percent_decode(s: &str) -> String {
+            let bytes = s.as_bytes();
+            let mut decoded = Vec::with_capacity(bytes.len());
+            let mut i = 0;
+            while i < bytes.len() {
+                let escaped = match bytes.get(i + 1..i + 3) {
+                    Some(&[high, low]) if bytes[i] == b'%' => hex_value(high).zip(hex_value(low)),
+                    _ => None,
+                };
+                match (bytes[i], escaped) {
+                    (_, Some((high, low))) => {
+                        decoded.push(high << 4 | low);
+                        i += 3;
+                        continue;
+                    }
+                    (b'+', _) => decoded.push(b' '),
+                    (byte, _) => decoded.push(byte),
+                }
+                i += 1;
+            }
+            String::from_utf8_lossy(&decoded).into_owned()
+        }
+        
+        fn hex_value(byte: u8) -> Option<u8> {
+            (byte as char).to_digit(16).map(|digit| digit as u8)
         }
         
         pub struct Response {
             pub status_code: u16,
+            pub headers: std::collections::HashMap<String, String>,
             pub body: String,
         }
         
@@ -313,10 +376,15 @@ mod web_server {
             pub fn new(status_code: u16, body: &str) -> Self {
                 Response {
                     status_code,
+                    headers: std::collections::HashMap::new(),
                     body: body.to_string(),
                 }
             }
             
+            pub fn add_header(&mut self, key: &str, value: &str) {
+                self.headers.insert(key.to_string(), value.to_string());
+            }
+            
             pub fn ok(body: &str) -> Self {
                 Self::new(200, body)
             }
@@ -324,6 +392,14 @@ mod web_server {
             pub fn not_found() -> Self {
                 Self::new(404, "Not Found")
             }
+            
+            // 路径存在但方法不匹配，Allow头列出该路径支持的方法
+            pub fn method_not_allowed(allowed: &[Method]) -> Self {
+                let mut response = Self::new(405, "Method Not Allowed");
+                let allow: Vec<&str> = allowed.iter().map(|m| m.as_str()).collect();
+                response.add_header("Allow", &allow.join(", "));
+                response
+            }
         }
     }
     
@@ -334,7 +410,7 @@ mod web_server {
         type Handler = Box<dyn Fn(&Request) -> Response>;
         
         pub struct Router {
-            routes: HashMap<String, Handler>,
+            routes: HashMap<(Method, String), Handler>,
         }
         
         impl Router {
@@ -344,23 +420,200 @@ mod web_server {
                 }
             }
             
+            // 为路径注册一个响应所有方法的处理器
             pub fn add_route<F>(&mut self, path: &str, handler: F)
             where
                 F: Fn(&Request) -> Response + 'static,
             {
-                self.routes.insert(path.to_string(), Box::new(handler));
+                let handler = std::rc::Rc::new(handler);
+                for method in Method::ALL {
+                    let handler = handler.clone();
+                    self.add_route_method(method, path, move |req: &Request| handler(req));
+                }
+            }
+            
+            pub fn add_route_method<F>(&mut self, method: Method, path: &str, handler: F)
+            where
+                F: Fn(&Request) -> Response + 'static,
+            {
+                self.routes.insert((method, path.to_string()), Box::new(handler));
+            }
+            
+            pub fn get<F>(&mut self, path: &str, handler: F)
+            where
+                F: Fn(&Request) -> Response + 'static,
+            {
+                self.add_route_method(Method::GET, path, handler);
+            }
+            
+            pub fn post<F>(&mut self, path: &str, handler: F)
+            where
+                F: Fn(&Request) -> Response + 'static,
+            {
+                self.add_route_method(Method::POST, path, handler);
+            }
+            
+            pub fn put<F>(&mut self, path: &str, handler: F)
+            where
+                F: Fn(&Request) -> Response + 'static,
+            {
+                self.add_route_method(Method::PUT, path, handler);
+            }
+            
+            pub fn delete<F>(&mut self, path: &str, handler: F)
+            where
+                F: Fn(&Request) -> Response + 'static,
+            {
+                self.add_route_method(Method::DELETE, path, handler);
             }
             
             pub fn handle_request(&self, request: &Request) -> Response {
-                if let Some(handler) = self.routes.get(&request.path) {
-                    handler(request)
-                } else {
+                let key = (request.method, request.path.clone());
+                if let Some(handler) = self.routes.get(&key) {
+                    return handler(request);
+                }
+                
+                let allowed: Vec<Method> = Method::ALL
+                    .into_iter()
+                    .filter(|m| self.routes.contains_key(&(*m, request.path.clone())))
+                    .collect();
+                
+                if allowed.is_empty() {
                     Response::not_found()
+                } else {
+                    Response::method_not_allowed(&allowed)
                 }
             }
         }
     }
     
+    // 基于路径段前缀树的路由，支持三种段：
+    // 静态段 users、参数段 :id、通配尾段 *rest（只能出现在最后，匹配剩余的所有段）
+    // 同一位置优先级：静态 > 参数 > 通配，匹配失败时回溯尝试下一种
+    pub mod prefix_router {
+        use std::collections::HashMap;
+        use std::fmt;
+        
+        pub type Params = HashMap<String, String>;
+        
+        // 同一位置的参数段只有一个节点，名字不同的参数会让已有路由捕获到错误的键
+        #[derive(Debug, Clone, PartialEq)]
+        pub struct ParamConflict {
+            pub pattern: String,
+            pub existing: String,
+            pub requested: String,
+        }
+        
+        impl fmt::Display for ParamConflict {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(
+                    f,
+                    "路由 {} 的参数 :{} 与同一位置已有的参数 :{} 冲突",
+                    self.pattern, self.requested, self.existing
+                )
+            }
+        }
+        
+        impl std::error::Error for ParamConflict {}
+        
+        struct Node<T> {
+            value: Option<T>,
+            children: HashMap<String, Node<T>>,
+            param: Option<(String, Box<Node<T>>)>,
+            wildcard: Option<(String, T)>,
+        }
+        
+        impl<T> Node<T> {
+            fn new() -> Self {
+                Node {
+                    value: None,
+                    children: HashMap::new(),
+                    param: None,
+                    wildcard: None,
+                }
+            }
+        }
+        
+        pub struct PrefixRouter<T> {
+            root: Node<T>,
+        }
+        
+        impl<T> PrefixRouter<T> {
+            pub fn new() -> Self {
+                PrefixRouter { root: Node::new() }
+            }
+            
+            // 同一模式重复插入时覆盖旧值；同一位置的参数名必须一致
+            pub fn insert(&mut self, pattern: &str, value: T) -> Result<(), ParamConflict> {
+                let mut node = &mut self.root;
+                for segment in pattern.split('/').filter(|s| !s.is_empty()) {
+                    if let Some(name) = segment.strip_prefix('*') {
+                        node.wildcard = Some((name.to_string(), value));
+                        return Ok(());
+                    }
+                    node = if let Some(name) = segment.strip_prefix(':') {
+                        let (param_name, child) = node
+                            .param
+                            .get_or_insert_with(|| (name.to_string(), Box::new(Node::new())));
+                        if param_name != name {
+                            return Err(ParamConflict {
+                                pattern: pattern.to_string(),
+                                existing: param_name.clone(),
+                                requested: name.to_string(),
+                            });
+                        }
+                        child
+                    } else {
+                        node.children.entry(segment.to_string()).or_insert_with(Node::new)
+                    };
+                }
+                node.value = Some(value);
+                Ok(())
+            }
+            
+            // 查找路径（忽略 ? 之后的查询串），返回匹配的值和捕获的参数
+            pub fn lookup(&self, path: &str) -> Option<(&T, Params)> {
+                let path = path.split('?').next().unwrap_or(path);
+                let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+                let mut params = Params::new();
+                let value = Self::find(&self.root, &segments, &mut params)?;
+                Some((value, params))
+            }
+            
+            fn find<'a>(node: &'a Node<T>, segments: &[&str], params: &mut Params) -> Option<&'a T> {
+                let (first, rest) = match segments.split_first() {
+                    Some(split) => split,
+                    // 路径结束：精确匹配，或者通配段匹配空的剩余部分
+                    None => {
+                        return node.value.as_ref().or_else(|| {
+                            let (name, value) = node.wildcard.as_ref()?;
+                            params.insert(name.clone(), String::new());
+                            Some(value)
+                        });
+                    }
+                };
+                
+                if let Some(child) = node.children.get(*first) {
+                    if let Some(value) = Self::find(child, rest, params) {
+                        return Some(value);
+                    }
+                }
+                
+                if let Some((name, child)) = &node.param {
+                    params.insert(name.clone(), first.to_string());
+                    if let Some(value) = Self::find(child, rest, params) {
+                        return Some(value);
+                    }
+                    params.remove(name);
+                }
+                
+                let (name, value) = node.wildcard.as_ref()?;
+                params.insert(name.clone(), segments.join("/"));
+                Some(value)
+            }
+        }
+    }
+    
     pub mod middleware {
         use super::http::{Request, Response};
         
@@ -397,6 +650,13 @@ mod web_server {
             }
         }
         
+        // 装箱的中间件（例如从插件注册表创建的）也可以直接添加到服务器
+        impl<M: Middleware + ?Sized> Middleware for Box<M> {
+            fn process(&self, request: &mut Request) -> Option<Response> {
+                (**self).process(request)
+            }
+        }
+        
         impl Middleware for Auth {
             fn process(&self, request: &mut Request) -> Option<Response> {
                 if let Some(auth_header) = request.headers.get("Authorization") {
@@ -459,7 +719,13 @@ mod web_server {
 
 // 数据库模块
 mod database {
+    use std::cell::RefCell;
     use std::collections::HashMap;
+    use std::fs;
+    use std::io::{self, Write};
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::{Duration, Instant};
     
     pub trait Database {
         fn get(&self, key: &str) -> Option<String>;
@@ -467,50 +733,302 @@ mod database {
         fn delete(&mut self, key: &str) -> bool;
     }
     
+    // 值和可选的过期时间
+    struct Entry {
+        value: String,
+        expires_at: Option<Instant>,
+    }
+    
+    impl Entry {
+        fn is_expired(&self) -> bool {
+            self.expires_at.is_some_and(|at| Instant::now() >= at)
+        }
+    }
+    
+    // Database::get只拿到&self，用RefCell让查询时也能清理过期条目
     pub struct MemoryDatabase {
-        data: HashMap<String, String>,
+        data: RefCell<HashMap<String, Entry>>,
     }
     
     impl MemoryDatabase {
         pub fn new() -> Self {
             MemoryDatabase {
-                data: HashMap::new(),
+                data: RefCell::new(HashMap::new()),
             }
         }
+        
+        // 未过期的当前值；查到已过期的条目时顺便删除，避免它一直占用内存
+        fn live_value(&self, key: &str) -> Option<String> {
+            let mut data = self.data.borrow_mut();
+            if data.get(key).is_some_and(Entry::is_expired) {
+                data.remove(key);
+                return None;
+            }
+            data.get(key).map(|entry| entry.value.clone())
+        }
+        
+        // 保存的条目数，包括还没被查询清理的过期条目
+        pub fn stored_len(&self) -> usize {
+            self.data.borrow().len()
+        }
+        
+        // 仅当当前值等于expected时写入new；expected为None表示要求键不存在
+        pub fn compare_and_set(&mut self, key: &str, expected: Option<&str>, new: &str) -> bool {
+            if self.live_value(key).as_deref() != expected {
+                return false;
+            }
+            self.set(key, new);
+            true
+        }
+        
+        // 写入一个在ttl之后过期的值，过期后get返回None
+        pub fn set_with_ttl(&mut self, key: &str, value: &str, ttl: Duration) {
+            self.data.get_mut().insert(key.to_string(), Entry {
+                value: value.to_string(),
+                expires_at: Some(Instant::now() + ttl),
+            });
+        }
     }
     
     impl Database for MemoryDatabase {
         fn get(&self, key: &str) -> Option<String> {
-            self.data.get(key).cloned()
+            self.live_value(key)
+        }
+        
+        fn set(&mut self, key: &str, value: &str) {
+            self.data.get_mut().insert(key.to_string(), Entry {
+                value: value.to_string(),
+                expires_at: None,
+            });
+        }
+        
+        fn delete(&mut self, key: &str) -> bool {
+            match self.data.get_mut().remove(key) {
+                Some(entry) => !entry.is_expired(),
+                None => false,
+            }
+        }
+    }
+    
+    // 基于文件的数据库：每行一个 key=value，键值中的 \\、= 和换行会被转义
+    // 写操作先写临时文件再rename覆盖，读者不会看到写了一半的文件
+    pub struct FileDatabase {
+        path: PathBuf,
+    }
+    
+    impl FileDatabase {
+        pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+            let path = path.as_ref().to_path_buf();
+            if !path.exists() {
+                fs::write(&path, "")?;
+            }
+            Ok(FileDatabase { path })
+        }
+        
+        fn load(&self) -> io::Result<HashMap<String, String>> {
+            let content = match fs::read_to_string(&self.path) {
+                Ok(content) => content,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(HashMap::new()),
+                Err(e) => return Err(e),
+            };
+            
+            let mut data = HashMap::new();
+            for (line_no, line) in content.lines().enumerate() {
+                if line.is_empty() {
+                    continue;
+                }
+                let (key, value) = split_unescaped(line).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("第{}行缺少分隔符 '='", line_no + 1),
+                    )
+                })?;
+                data.insert(unescape_field(key), unescape_field(value));
+            }
+            Ok(data)
+        }
+        
+        fn store(&self, data: &HashMap<String, String>) -> io::Result<()> {
+            // 按键排序，保证文件内容稳定
+            let mut entries: Vec<_> = data.iter().collect();
+            entries.sort();
+            
+            let mut content = String::new();
+            for (key, value) in entries {
+                content.push_str(&escape_field(key));
+                content.push('=');
+                content.push_str(&escape_field(value));
+                content.push('\n');
+            }
+            
+            let temp_path = self.temp_path();
+            let result = fs::File::create_new(&temp_path)
+                .and_then(|mut file| {
+                    file.write_all(content.as_bytes())?;
+                    file.sync_all()
+                })
+                .and_then(|_| fs::rename(&temp_path, &self.path));
+            
+            if result.is_err() {
+                let _ = fs::remove_file(&temp_path);
+            }
+            result
+        }
+        
+        // 临时文件放在同一目录下才能原子rename；文件名带上进程号和计数器，
+        // 避免同一目录下的多个数据库或多个进程互相覆盖临时文件
+        fn temp_path(&self) -> PathBuf {
+            static COUNTER: AtomicUsize = AtomicUsize::new(0);
+            
+            let file_name = self.path.file_name().unwrap_or_default().to_string_lossy();
+            let counter = COUNTER.fetch_add(1, Ordering::Relaxed);
+            self.path.with_file_name(format!("{}.{}.{}.tmp", file_name, std::process::id(), counter))
+        }
+        
+        pub fn try_set(&mut self, key: &str, value: &str) -> io::Result<()> {
+            let mut data = self.load()?;
+            data.insert(key.to_string(), value.to_string());
+            self.store(&data)
+        }
+        
+        pub fn try_delete(&mut self, key: &str) -> io::Result<bool> {
+            let mut data = self.load()?;
+            if data.remove(key).is_none() {
+                return Ok(false);
+            }
+            self.store(&data)?;
+            Ok(true)
+        }
+    }
+    
+    // Database trait无法返回错误，I/O失败时打印错误信息
+    impl Database for FileDatabase {
+        fn get(&self, key: &str) -> Option<String> {
+            match self.load() {
+                Ok(mut data) => data.remove(key),
+                Err(e) => {
+                    eprintln!("读取数据库文件失败: {}", e);
+                    None
+                }
+            }
         }
         
         fn set(&mut self, key: &str, value: &str) {
-            self.data.insert(key.to_string(), value.to_string());
+            if let Err(e) = self.try_set(key, value) {
+                eprintln!("写入数据库文件失败: {}", e);
+            }
         }
         
         fn delete(&mut self, key: &str) -> bool {
-            self.data.remove(key).is_some()
+            self.try_delete(key).unwrap_or_else(|e| {
+                eprintln!("写入数据库文件失败: {}", e);
+                false
+            })
         }
     }
     
+    fn escape_field(s: &str) -> String {
+        let mut result = String::with_capacity(s.len());
+        for c in s.chars() {
+            match c {
+                '\\' => result.push_str("\\\\"),
+                '=' => result.push_str("\\="),
+                '\n' => result.push_str("\\n"),
+                '\r' => result.push_str("\\r"),
+                _ => result.push(c),
+            }
+        }
+        result
+    }
+    
+    fn unescape_field(s: &str) -> String {
+        let mut result = String::with_capacity(s.len());
+        let mut chars = s.chars();
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                result.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('n') => result.push('\n'),
+                Some('r') => result.push('\r'),
+                Some(other) => result.push(other),
+                None => result.push('\\'),
+            }
+        }
+        result
+    }
+    
+    // 在第一个未转义的 '=' 处分割
+    fn split_unescaped(line: &str) -> Option<(&str, &str)> {
+        let mut escaped = false;
+        for (i, c) in line.char_indices() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '=' => return Some((&line[..i], &line[i + 1..])),
+                _ => {}
+            }
+        }
+        None
+    }
+    
     // 数据库连接池
     pub mod pool {
         use super::Database;
-        use std::sync::{Arc, Mutex};
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+        use std::time::{Duration, Instant};
         
         pub struct ConnectionPool<T: Database> {
             connections: Vec<Arc<Mutex<T>>>,
             current: usize,
+            state: Arc<PoolState>,
+        }
+        
+        // 连接占用状态，由池和所有借出的守卫共享
+        struct PoolState {
+            in_use: Vec<AtomicBool>,
+            released: Mutex<()>,
+            signal: Condvar,
+        }
+        
+        // 借出的连接，drop时归还给连接池并唤醒等待者
+        pub struct PooledConnection<T> {
+            index: usize,
+            connection: Arc<Mutex<T>>,
+            state: Arc<PoolState>,
+        }
+        
+        impl<T> PooledConnection<T> {
+            pub fn lock(&self) -> MutexGuard<'_, T> {
+                self.connection.lock().unwrap()
+            }
+        }
+        
+        impl<T> Drop for PooledConnection<T> {
+            fn drop(&mut self) {
+                self.state.in_use[self.index].store(false, Ordering::Release);
+                let _released = self.state.released.lock().unwrap();
+                self.state.signal.notify_one();
+            }
         }
         
         impl<T: Database> ConnectionPool<T> {
             pub fn new(connections: Vec<T>) -> Self {
+                let state = PoolState {
+                    in_use: connections.iter().map(|_| AtomicBool::new(false)).collect(),
+                    released: Mutex::new(()),
+                    signal: Condvar::new(),
+                };
+                
                 ConnectionPool {
                     connections: connections
                         .into_iter()
                         .map(|conn| Arc::new(Mutex::new(conn)))
                         .collect(),
                     current: 0,
+                    state: Arc::new(state),
                 }
             }
             
@@ -523,6 +1041,81 @@ mod database {
                 self.current = (self.current + 1) % self.connections.len();
                 Some(conn)
             }
+            
+            // 只借出空闲的连接；全部被占用时最多等待timeout，超时返回None
+            pub fn get_connection_timeout(&mut self, timeout: Duration) -> Option<PooledConnection<T>> {
+                let deadline = Instant::now() + timeout;
+                let state = self.state.clone();
+                let mut released = state.released.lock().unwrap();
+                
+                loop {
+                    if let Some(conn) = self.try_acquire() {
+                        return Some(conn);
+                    }
+                    
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return None;
+                    }
+                    released = state.signal.wait_timeout(released, deadline - now).unwrap().0;
+                }
+            }
+            
+            // 从轮询位置开始依次尝试占用一个空闲连接
+            fn try_acquire(&mut self) -> Option<PooledConnection<T>> {
+                let count = self.connections.len();
+                for offset in 0..count {
+                    let index = (self.current + offset) % count;
+                    let claimed = self.state.in_use[index]
+                        .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+                        .is_ok();
+                    if claimed {
+                        self.current = (index + 1) % count;
+                        return Some(PooledConnection {
+                            index,
+                            connection: self.connections[index].clone(),
+                            state: self.state.clone(),
+                        });
+                    }
+                }
+                None
+            }
+        }
+    }
+}
+
+// 插件注册表：按名字保存工厂函数，需要时创建trait对象
+mod registry {
+    use std::collections::HashMap;
+    
+    pub struct Registry<T: ?Sized> {
+        factories: HashMap<String, Box<dyn Fn() -> Box<T>>>,
+    }
+    
+    impl<T: ?Sized> Registry<T> {
+        pub fn new() -> Self {
+            Registry {
+                factories: HashMap::new(),
+            }
+        }
+        
+        // 同名插件后注册的覆盖先注册的
+        pub fn register<F>(&mut self, name: &str, factory: F)
+        where
+            F: Fn() -> Box<T> + 'static,
+        {
+            self.factories.insert(name.to_string(), Box::new(factory));
+        }
+        
+        // 每次调用都创建新的实例
+        pub fn create(&self, name: &str) -> Option<Box<T>> {
+            self.factories.get(name).map(|factory| factory())
+        }
+        
+        pub fn names(&self) -> Vec<&str> {
+            let mut names: Vec<&str> = self.factories.keys().map(String::as_str).collect();
+            names.sort();
+            names
         }
     }
 }
@@ -533,9 +1126,18 @@ fn project_structure_example() {
     // 创建Web服务器
     let mut server = web_server::server::Server::new();
     
+    // 通过注册表按名字创建中间件
+    let mut plugins: registry::Registry<dyn web_server::middleware::Middleware> = registry::Registry::new();
+    plugins.register("logger", || Box::new(web_server::middleware::Logger));
+    plugins.register("auth", || Box::new(web_server::middleware::Auth::new("secret-token")));
+    println!("已注册的中间件: {:?}", plugins.names());
+    
     // 添加中间件
-    server.add_middleware(web_server::middleware::Logger);
-    server.add_middleware(web_server::middleware::Auth::new("secret-token"));
+    for name in ["logger", "auth"] {
+        if let Some(middleware) = plugins.create(name) {
+            server.add_middleware(middleware);
+        }
+    }
     
     // 添加路由
     server.add_route("/", |_req| {
@@ -565,6 +1167,54 @@ fn project_structure_example() {
     let response = server.handle_request(unauthorized_request);
     println!("未授权响应: {}, 内容: {}", response.status_code, response.body);
     
+    // 解析查询参数和表单
+    let search = web_server::http::Request::new(
+        web_server::http::Method::GET,
+        "/search?q=rust+%E6%A8%A1%E5%9D%97&page=2",
+    );
+    println!("查询参数: {:?}", search.query_params().get("q"));
+    println!("表单: {:?}", web_server::http::parse_form("user=alice&remember=").get("user"));
+    
+    // 前缀树路由：静态文件和带参数的路径
+    type PrefixHandler = Box<dyn Fn(&web_server::prefix_router::Params) -> web_server::http::Response>;
+    let mut prefix_router: web_server::prefix_router::PrefixRouter<PrefixHandler> =
+        web_server::prefix_router::PrefixRouter::new();
+    prefix_router.insert("/static/*path", Box::new(|params| {
+        web_server::http::Response::ok(&format!("静态文件: {}", params["path"]))
+    })).unwrap();
+    prefix_router.insert("/users/:id", Box::new(|params| {
+        web_server::http::Response::ok(&format!("用户: {}", params["id"]))
+    })).unwrap();
+    // 同一位置换一个参数名会被拒绝，已有路由不受影响
+    if let Err(e) = prefix_router.insert("/users/:name/posts", Box::new(|params| {
+        web_server::http::Response::ok(&format!("文章: {}", params["name"]))
+    })) {
+        println!("注册路由失败: {}", e);
+    }
+    for path in ["/static/css/site.css", "/users/42", "/unknown"] {
+        match prefix_router.lookup(path) {
+            Some((handler, params)) => println!("{} -> {}", path, handler(&params).body),
+            None => println!("{} -> 404", path),
+        }
+    }
+    
+    // 按HTTP方法分发路由
+    let mut router = web_server::router::Router::new();
+    router.get("/api/items", |_req| web_server::http::Response::ok("列出所有条目"));
+    router.post("/api/items", |_req| web_server::http::Response::new(201, "创建条目"));
+    router.put("/api/items/1", |_req| web_server::http::Response::ok("更新条目1"));
+    router.delete("/api/items/1", |_req| web_server::http::Response::new(204, ""));
+    
+    let put_request = web_server::http::Request::new(web_server::http::Method::PUT, "/api/items/1");
+    println!("PUT /api/items/1: {}", router.handle_request(&put_request).body);
+    
+    let delete_request = web_server::http::Request::new(
+        web_server::http::Method::DELETE,
+        "/api/items",
+    );
+    let response = router.handle_request(&delete_request);
+    println!("方法不匹配: {}, Allow: {:?}", response.status_code, response.headers.get("Allow"));
+    
     // 数据库使用示例
     let mut db = database::MemoryDatabase::new();
     db.set("user:1", "Alice");
@@ -574,6 +1224,27 @@ fn project_structure_example() {
         println!("数据库查询结果: {}", user);
     }
     
+    // 比较并设置：只有值仍为预期时才更新
+    let updated = db.compare_and_set("user:1", Some("Alice"), "Alice Smith");
+    let stale = db.compare_and_set("user:1", Some("Alice"), "Alice Jones");
+    println!("CAS更新: {}, 过期预期的CAS: {}", updated, stale);
+    
+    // 带过期时间的键
+    db.set_with_ttl("session", "token-123", std::time::Duration::from_secs(60));
+    println!("会话令牌: {:?}", db.get("session"));
+    println!("保存的条目数: {}", db.stored_len());
+    
+    // 文件数据库实现了同样的Database trait
+    let db_path = std::env::temp_dir().join("module_system_demo.db");
+    match database::FileDatabase::open(&db_path) {
+        Ok(mut file_db) => {
+            file_db.set("user:1", "Alice");
+            println!("文件数据库查询结果: {:?}", file_db.get("user:1"));
+            let _ = std::fs::remove_file(&db_path);
+        }
+        Err(e) => println!("打开文件数据库失败: {}", e),
+    }
+    
     // 连接池示例
     let databases = vec![
         database::MemoryDatabase::new(),
@@ -587,6 +1258,12 @@ fn project_structure_example() {
         db.set("test", "value");
         println!("连接池数据库操作完成");
     }
+    
+    // 只借出空闲连接，守卫离开作用域时自动归还
+    if let Some(conn) = pool.get_connection_timeout(std::time::Duration::from_millis(100)) {
+        conn.lock().set("pooled", "value");
+        println!("借出的空闲连接操作完成");
+    }
 }
 
 // 模块重导出示例
@@ -634,6 +1311,45 @@ mod tests {
         assert_eq!(response.body, "Test response");
     }
     
+    #[test]
+    fn test_router_dispatches_by_method() {
+        use web_server::http::{Method, Request, Response};
+        
+        let mut router = web_server::router::Router::new();
+        router.get("/x", |_req| Response::ok("get x"));
+        router.post("/x", |_req| Response::ok("post x"));
+        router.put("/x", |_req| Response::ok("put x"));
+        router.delete("/x", |_req| Response::ok("delete x"));
+        
+        let response = router.handle_request(&Request::new(Method::GET, "/x"));
+        assert_eq!(response.body, "get x");
+        
+        let response = router.handle_request(&Request::new(Method::POST, "/x"));
+        assert_eq!(response.body, "post x");
+        
+        let response = router.handle_request(&Request::new(Method::PUT, "/x"));
+        assert_eq!(response.body, "put x");
+        
+        let response = router.handle_request(&Request::new(Method::DELETE, "/x"));
+        assert_eq!(response.body, "delete x");
+    }
+    
+    #[test]
+    fn test_router_method_not_allowed() {
+        use web_server::http::{Method, Request, Response};
+        
+        let mut router = web_server::router::Router::new();
+        router.get("/x", |_req| Response::ok("get x"));
+        router.post("/x", |_req| Response::ok("post x"));
+        
+        let response = router.handle_request(&Request::new(Method::DELETE, "/x"));
+        assert_eq!(response.status_code, 405);
+        assert_eq!(response.headers.get("Allow").map(String::as_str), Some("GET, POST"));
+        
+        let response = router.handle_request(&Request::new(Method::DELETE, "/missing"));
+        assert_eq!(response.status_code, 404);
+    }
+    
     #[test]
     fn test_database() {
         let mut db = database::MemoryDatabase::new();
@@ -645,6 +1361,141 @@ mod tests {
         assert_eq!(db.get("key1"), None);
     }
     
+    #[test]
+    fn test_pool_blocks_until_connection_released() {
+        use std::time::Duration;
+        
+        let mut pool = database::pool::ConnectionPool::new(vec![
+            database::MemoryDatabase::new(),
+            database::MemoryDatabase::new(),
+        ]);
+        
+        let first = pool.get_connection_timeout(Duration::from_millis(100)).unwrap();
+        let _second = pool.get_connection_timeout(Duration::from_millis(100)).unwrap();
+        
+        let releaser = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100));
+            drop(first);
+        });
+        
+        let start = std::time::Instant::now();
+        let third = pool.get_connection_timeout(Duration::from_secs(5));
+        assert!(third.is_some());
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        releaser.join().unwrap();
+    }
+    
+    #[test]
+    fn test_pool_timeout_returns_none() {
+        use std::time::Duration;
+        
+        let mut pool = database::pool::ConnectionPool::new(vec![database::MemoryDatabase::new()]);
+        let _held = pool.get_connection_timeout(Duration::from_millis(100)).unwrap();
+        
+        assert!(pool.get_connection_timeout(Duration::from_millis(50)).is_none());
+    }
+    
+    #[test]
+    fn test_compare_and_set() {
+        let mut db = database::MemoryDatabase::new();
+        
+        assert!(db.compare_and_set("lock", None, "owner-a"));
+        assert_eq!(db.get("lock"), Some("owner-a".to_string()));
+        
+        assert!(db.compare_and_set("lock", Some("owner-a"), "owner-b"));
+        assert_eq!(db.get("lock"), Some("owner-b".to_string()));
+    }
+    
+    #[test]
+    fn test_compare_and_set_stale_expectation() {
+        let mut db = database::MemoryDatabase::new();
+        db.set("lock", "owner-b");
+        
+        assert!(!db.compare_and_set("lock", Some("owner-a"), "owner-c"));
+        assert!(!db.compare_and_set("lock", None, "owner-c"));
+        assert_eq!(db.get("lock"), Some("owner-b".to_string()));
+    }
+    
+    #[test]
+    fn test_ttl_expiry() {
+        use std::time::Duration;
+        
+        let mut db = database::MemoryDatabase::new();
+        db.set_with_ttl("session", "abc", Duration::from_millis(50));
+        assert_eq!(db.get("session"), Some("abc".to_string()));
+        
+        std::thread::sleep(Duration::from_millis(80));
+        // 过期条目在查询到时被删除
+        assert_eq!(db.stored_len(), 1);
+        assert_eq!(db.get("session"), None);
+        assert_eq!(db.stored_len(), 0);
+        // 过期的键可以被视为不存在重新占用
+        assert!(db.compare_and_set("session", None, "def"));
+    }
+    
+    #[test]
+    fn test_file_database_round_trip() {
+        let path = std::env::temp_dir().join(format!("file_db_test_{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        
+        {
+            let mut db = database::FileDatabase::open(&path).unwrap();
+            db.set("user:1", "Alice");
+            db.set("a=b", "line1\nline2");
+            db.set("path", "C:\\temp\\x=1");
+            db.set("tmp", "to be deleted");
+            assert!(db.delete("tmp"));
+        }
+        
+        let db = database::FileDatabase::open(&path).unwrap();
+        assert_eq!(db.get("user:1"), Some("Alice".to_string()));
+        assert_eq!(db.get("a=b"), Some("line1\nline2".to_string()));
+        assert_eq!(db.get("path"), Some("C:\\temp\\x=1".to_string()));
+        assert_eq!(db.get("tmp"), None);
+        
+        // FileDatabase同样可以放进连接池
+        let mut pool = database::pool::ConnectionPool::new(vec![db]);
+        let conn = pool.get_connection().unwrap();
+        assert_eq!(conn.lock().unwrap().get("user:1"), Some("Alice".to_string()));
+        
+        std::fs::remove_file(&path).unwrap();
+    }
+    
+    #[test]
+    fn test_file_database_same_stem() {
+        // shared.db和shared.log曾经共用shared.tmp作为临时文件
+        let dir = std::env::temp_dir().join(format!("file_db_stem_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        
+        let writers: Vec<_> = ["shared.db", "shared.log"]
+            .into_iter()
+            .map(|name| {
+                let path = dir.join(name);
+                std::thread::spawn(move || {
+                    let mut db = database::FileDatabase::open(&path).unwrap();
+                    for i in 0..50 {
+                        db.try_set("owner", name).unwrap();
+                        db.try_set("round", &i.to_string()).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        
+        for name in ["shared.db", "shared.log"] {
+            let db = database::FileDatabase::open(dir.join(name)).unwrap();
+            assert_eq!(db.get("owner"), Some(name.to_string()));
+            assert_eq!(db.get("round"), Some("49".to_string()));
+        }
+        // 没有遗留的临时文件
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 2);
+        
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+    
     #[test]
     fn test_prelude() {
         use crate::prelude::*;
@@ -657,6 +1508,114 @@ mod tests {
         assert_eq!(db.get("test"), Some("value".to_string()));
     }
     
+    #[test]
+    fn test_registry_creates_plugins_by_name() {
+        use web_server::middleware::{Auth, Logger, Middleware};
+        
+        let mut plugins: registry::Registry<dyn Middleware> = registry::Registry::new();
+        plugins.register("logger", || Box::new(Logger));
+        plugins.register("auth", || Box::new(Auth::new("token")));
+        assert_eq!(plugins.names(), vec!["auth", "logger"]);
+        assert!(plugins.create("missing").is_none());
+        
+        let auth = plugins.create("auth").unwrap();
+        let mut request = web_server::http::Request::new(web_server::http::Method::GET, "/");
+        assert_eq!(auth.process(&mut request).unwrap().status_code, 401);
+        request.add_header("Authorization", "token");
+        assert!(auth.process(&mut request).is_none());
+        
+        // 创建的中间件可以直接交给服务器
+        let mut server = web_server::server::Server::new();
+        server.add_middleware(auth);
+        server.add_route("/", |_req| web_server::http::Response::ok("home"));
+        let request = web_server::http::Request::new(web_server::http::Method::GET, "/");
+        assert_eq!(server.handle_request(request).status_code, 401);
+    }
+    
+    #[test]
+    fn test_query_params_and_form_parsing() {
+        use web_server::http::{parse_form, percent_decode, Method, Request};
+        
+        let request = Request::new(Method::GET, "/search?q=hello+world&lang=%E4%B8%AD%E6%96%87&empty=&flag");
+        let params = request.query_params();
+        assert_eq!(params["q"], "hello world");
+        assert_eq!(params["lang"], "中文");
+        assert_eq!(params["empty"], "");
+        assert_eq!(params["flag"], "");
+        assert!(Request::new(Method::GET, "/plain").query_params().is_empty());
+        
+        // 重复的键以最后一次为准
+        let form = parse_form("tag=a&tag=b&name=Jo%20Doe&&a%26b=c%3Dd");
+        assert_eq!(form.len(), 3);
+        assert_eq!(form["tag"], "b");
+        assert_eq!(form["name"], "Jo Doe");
+        assert_eq!(form["a&b"], "c=d");
+        
+        // 不合法或不完整的转义原样保留
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz%4"), "%zz%4");
+    }
+    
+    #[test]
+    fn test_prefix_router_precedence_and_captures() {
+        use web_server::prefix_router::PrefixRouter;
+        
+        let mut router = PrefixRouter::new();
+        router.insert("/", "home").unwrap();
+        router.insert("/static/*path", "static").unwrap();
+        router.insert("/users/:id", "user").unwrap();
+        router.insert("/users/me", "me").unwrap();
+        router.insert("/users/:id/posts/:post", "post").unwrap();
+        
+        let resolve = |path: &str| {
+            router.lookup(path).map(|(value, params)| {
+                let mut params: Vec<(String, String)> = params.into_iter().collect();
+                params.sort();
+                (*value, params)
+            })
+        };
+        let param = |k: &str, v: &str| (k.to_string(), v.to_string());
+        
+        assert_eq!(resolve("/"), Some(("home", vec![])));
+        assert_eq!(resolve("/static/css/site.css"), Some(("static", vec![param("path", "css/site.css")])));
+        assert_eq!(resolve("/users/42"), Some(("user", vec![param("id", "42")])));
+        assert_eq!(resolve("/users/42?tab=posts"), Some(("user", vec![param("id", "42")])));
+        // 静态段优先于参数段
+        assert_eq!(resolve("/users/me"), Some(("me", vec![])));
+        assert_eq!(
+            resolve("/users/7/posts/9"),
+            Some(("post", vec![param("id", "7"), param("post", "9")]))
+        );
+        assert_eq!(resolve("/users/7/comments"), None);
+        assert_eq!(resolve("/nothing"), None);
+    }
+    
+    #[test]
+    fn test_prefix_router_rejects_conflicting_param_names() {
+        use web_server::prefix_router::{ParamConflict, PrefixRouter};
+        
+        let mut router = PrefixRouter::new();
+        router.insert("/users/:id", "user").unwrap();
+        assert_eq!(
+            router.insert("/users/:name/posts", "posts"),
+            Err(ParamConflict {
+                pattern: "/users/:name/posts".to_string(),
+                existing: "id".to_string(),
+                requested: "name".to_string(),
+            })
+        );
+        
+        // 已有路由仍然用原来的参数名捕获
+        let (value, params) = router.lookup("/users/42").unwrap();
+        assert_eq!(*value, "user");
+        assert_eq!(params.get("id").map(String::as_str), Some("42"));
+        assert_eq!(router.lookup("/users/42/posts"), None);
+        
+        // 参数名一致时可以继续扩展
+        router.insert("/users/:id/posts", "posts").unwrap();
+        assert_eq!(*router.lookup("/users/42/posts").unwrap().0, "posts");
+    }
+    
     #[test]
     fn test_examples() {
         module_basics();
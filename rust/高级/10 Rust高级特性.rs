@@ -1,8 +1,12 @@
 // 10 Rust高级特性 - 高级类型、函数式编程和元编程技巧
 // 本章介绍Rust的高级特性：高级类型、闭包、迭代器、函数式编程等
 
-use std::ops::{Add, Deref, Index};
+use std::ops::{Add, Deref, DerefMut, Index};
 use std::fmt::{self, Display};
+use std::collections::{HashMap, VecDeque};
+use std::cell::RefCell;
+use std::sync::{Arc, Condvar, Mutex};
+use std::hash::Hash;
 
 fn main() {
     // 高级类型示例
@@ -36,6 +40,24 @@ impl Deref for Wrapper {
     }
 }
 
+impl DerefMut for Wrapper {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl Wrapper {
+    fn push(&mut self, s: impl Into<String>) {
+        self.0.push(s.into());
+    }
+}
+
+impl FromIterator<String> for Wrapper {
+    fn from_iter<I: IntoIterator<Item = String>>(iter: I) -> Self {
+        Wrapper(iter.into_iter().collect())
+    }
+}
+
 impl Display for Wrapper {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "Wrapper({})", self.0.join(", "))
@@ -83,6 +105,12 @@ fn advanced_types() {
     println!("包装器: {}", w);
     println!("长度: {}", w.len());  // 通过Deref可以调用Vec的方法
     
+    // 通过collect构造，通过push和DerefMut修改
+    let mut w: Wrapper = ["rust", "go"].iter().map(|s| s.to_string()).collect();
+    w.push("zig");
+    w.sort();
+    println!("排序后的包装器: {}", w);
+    
     // 函数指针类型
     fn add_one(x: i32) -> i32 {
         x + 1
@@ -149,6 +177,47 @@ fn fn_composition_example() {
     
     let composed = compose(multiply_by_two, add_ten);
     println!("组合函数 (x+10)*2 with x=5: {}", composed(5));
+    
+    // 链式组合任意多个函数，每一步的输入类型必须与上一步的输出类型一致
+    let pipeline = Pipe::new(add_ten)
+        .then(multiply_by_two)
+        .then(|x: i32| format!("结果是{}", x))
+        .build();
+    println!("管道组合 with x=5: {}", pipeline(5));
+}
+
+// 函数管道构建器：Pipe<A, B, F> 表示一个从A到B的函数
+struct Pipe<A, B, F>
+where
+    F: Fn(A) -> B,
+{
+    f: F,
+    _types: std::marker::PhantomData<fn(A) -> B>,
+}
+
+impl<A, B, F> Pipe<A, B, F>
+where
+    F: Fn(A) -> B,
+{
+    fn new(f: F) -> Self {
+        Pipe {
+            f,
+            _types: std::marker::PhantomData,
+        }
+    }
+    
+    // 追加下一步，g的参数类型必须是当前的输出类型B
+    fn then<C, G>(self, g: G) -> Pipe<A, C, impl Fn(A) -> C>
+    where
+        G: Fn(B) -> C,
+    {
+        let f = self.f;
+        Pipe::new(move |x| g(f(x)))
+    }
+    
+    fn build(self) -> impl Fn(A) -> B {
+        self.f
+    }
 }
 
 // 案例2：闭包和函数式编程
@@ -195,49 +264,237 @@ fn closures_and_functional() {
     functional_data_processing();
 }
 
+// 缓存闭包：按参数分别缓存计算结果
+struct Cacher<T, K, V>
+where
+    T: Fn(K) -> V,
+    K: Eq + Hash + Copy,
+    V: Copy,
+{
+    calculation: T,
+    values: HashMap<K, V>,
+}
+
+impl<T, K, V> Cacher<T, K, V>
+where
+    T: Fn(K) -> V,
+    K: Eq + Hash + Copy,
+    V: Copy,
+{
+    fn new(calculation: T) -> Cacher<T, K, V> {
+        Cacher {
+            calculation,
+            values: HashMap::new(),
+        }
+    }
+    
+    fn value(&mut self, arg: K) -> V {
+        if let Some(&v) = self.values.get(&arg) {
+            return v;
+        }
+        let v = (self.calculation)(arg);
+        self.values.insert(arg, v);
+        v
+    }
+}
+
 // 缓存闭包示例
 fn caching_closure_example() {
     println!("\n--- 缓存闭包示例 ---");
     
-    struct Cacher<T>
-    where
-        T: Fn(u32) -> u32,
-    {
-        calculation: T,
-        value: Option<u32>,
+    let mut expensive_closure = Cacher::new(|num| {
+        println!("计算中...");
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        num
+    });
+    
+    println!("第一次调用: {}", expensive_closure.value(10));
+    println!("第二次调用: {}", expensive_closure.value(10));
+    println!("不同参数: {}", expensive_closure.value(20));
+    
+    // 通过共享引用调用的缓存，最多保留2个结果
+    let lengths = Memoize::with_capacity(
+        |word: &String| {
+            println!("计算 {} 的长度", word);
+            word.chars().count()
+        },
+        2,
+    );
+    for word in ["你好", "rust", "你好", "cargo", "你好"] {
+        println!("{} -> {}", word, lengths.get(&word.to_string()));
+    }
+    println!("缓存中的结果数: {}", lengths.len());
+    
+    // 多线程共享的缓存：同一个键只计算一次
+    let slow_square = Arc::new(ConcurrentMemoize::new(|x: &u64| {
+        println!("线程 {:?} 计算 {} 的平方", std::thread::current().id(), x);
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        x * x
+    }));
+    let handles: Vec<_> = (0..3)
+        .map(|_| {
+            let memo = Arc::clone(&slow_square);
+            std::thread::spawn(move || memo.get(&12))
+        })
+        .collect();
+    for handle in handles {
+        println!("并发缓存结果: {}", handle.join().unwrap());
+    }
+}
+
+// 单个键的计算状态
+enum Slot<V> {
+    Pending,
+    Ready(V),
+    // 计算者panic，等待者需要重新尝试
+    Failed,
+}
+
+struct OnceState<V> {
+    slot: Mutex<Slot<V>>,
+    ready: Condvar,
+}
+
+// 线程安全的记忆化包装器：多个线程同时请求同一个缺失的键时，
+// 只有第一个线程执行计算，其余线程等待并共享结果
+struct ConcurrentMemoize<K, V> {
+    func: Box<dyn Fn(&K) -> V + Send + Sync>,
+    cache: Mutex<HashMap<K, Arc<OnceState<V>>>>,
+}
+
+impl<K, V> ConcurrentMemoize<K, V>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    fn new(func: impl Fn(&K) -> V + Send + Sync + 'static) -> Self {
+        ConcurrentMemoize {
+            func: Box::new(func),
+            cache: Mutex::new(HashMap::new()),
+        }
     }
     
-    impl<T> Cacher<T>
-    where
-        T: Fn(u32) -> u32,
-    {
-        fn new(calculation: T) -> Cacher<T> {
-            Cacher {
-                calculation,
-                value: None,
+    fn get(&self, key: &K) -> V {
+        loop {
+            // 只在查找和插入时持有整张表的锁
+            let (state, is_leader) = {
+                let mut cache = self.cache.lock().unwrap();
+                match cache.get(key) {
+                    Some(state) => (Arc::clone(state), false),
+                    None => {
+                        let state = Arc::new(OnceState {
+                            slot: Mutex::new(Slot::Pending),
+                            ready: Condvar::new(),
+                        });
+                        cache.insert(key.clone(), Arc::clone(&state));
+                        (state, true)
+                    }
+                }
+            };
+            
+            if is_leader {
+                return self.compute(key, &state);
+            }
+            
+            let mut slot = state.slot.lock().unwrap();
+            while let Slot::Pending = *slot {
+                slot = state.ready.wait(slot).unwrap();
+            }
+            if let Slot::Ready(value) = &*slot {
+                return value.clone();
             }
+            // 计算失败，条目已被移除，重新竞争成为计算者
+        }
+    }
+    
+    fn compute(&self, key: &K, state: &OnceState<V>) -> V {
+        // 即使func发生panic，也要唤醒等待者并移除条目
+        struct FailGuard<'a, K: Eq + Hash, V> {
+            memo: &'a ConcurrentMemoize<K, V>,
+            key: &'a K,
+            state: &'a OnceState<V>,
+            done: bool,
         }
         
-        fn value(&mut self, arg: u32) -> u32 {
-            match self.value {
-                Some(v) => v,
-                None => {
-                    let v = (self.calculation)(arg);
-                    self.value = Some(v);
-                    v
+        impl<K: Eq + Hash, V> Drop for FailGuard<'_, K, V> {
+            fn drop(&mut self) {
+                if !self.done {
+                    self.memo.cache.lock().unwrap_or_else(|e| e.into_inner()).remove(self.key);
+                    *self.state.slot.lock().unwrap_or_else(|e| e.into_inner()) = Slot::Failed;
+                    self.state.ready.notify_all();
                 }
             }
         }
+        
+        let mut guard = FailGuard { memo: self, key, state, done: false };
+        let value = (self.func)(key);
+        guard.done = true;
+        
+        *state.slot.lock().unwrap() = Slot::Ready(value.clone());
+        state.ready.notify_all();
+        value
+    }
+}
+
+// 通用记忆化包装器：缓存放在RefCell中，因此只需要&self即可调用
+struct Memoize<K, V> {
+    func: Box<dyn Fn(&K) -> V>,
+    cache: RefCell<HashMap<K, V>>,
+    // 按插入顺序记录键，用于淘汰最早的结果
+    order: RefCell<VecDeque<K>>,
+    capacity: Option<usize>,
+}
+
+impl<K, V> Memoize<K, V>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    fn new(func: impl Fn(&K) -> V + 'static) -> Self {
+        Memoize {
+            func: Box::new(func),
+            cache: RefCell::new(HashMap::new()),
+            order: RefCell::new(VecDeque::new()),
+            capacity: None,
+        }
     }
     
-    let mut expensive_closure = Cacher::new(|num| {
-        println!("计算中...");
-        std::thread::sleep(std::time::Duration::from_millis(100));
-        num
-    });
+    // 最多缓存capacity个结果，超出时淘汰最早插入的
+    fn with_capacity(func: impl Fn(&K) -> V + 'static, capacity: usize) -> Self {
+        Memoize {
+            capacity: Some(capacity.max(1)),
+            ..Memoize::new(func)
+        }
+    }
     
-    println!("第一次调用: {}", expensive_closure.value(10));
-    println!("第二次调用: {}", expensive_closure.value(10));
+    fn get(&self, key: &K) -> V {
+        if let Some(value) = self.cache.borrow().get(key) {
+            return value.clone();
+        }
+        
+        // 计算期间不持有借用，func内部可以递归调用get
+        let value = (self.func)(key);
+        
+        let mut cache = self.cache.borrow_mut();
+        if !cache.contains_key(key) {
+            let mut order = self.order.borrow_mut();
+            if let Some(capacity) = self.capacity {
+                while cache.len() >= capacity {
+                    match order.pop_front() {
+                        Some(oldest) => cache.remove(&oldest),
+                        None => break,
+                    };
+                }
+            }
+            order.push_back(key.clone());
+        }
+        cache.insert(key.clone(), value.clone());
+        value
+    }
+    
+    fn len(&self) -> usize {
+        self.cache.borrow().len()
+    }
 }
 
 fn functional_data_processing() {
@@ -272,6 +529,108 @@ fn functional_data_processing() {
 }
 
 // 案例3：迭代器和适配器
+
+// 自定义迭代器：从start开始按step递增，直到不小于max
+struct Counter {
+    current: usize,
+    max: usize,
+    step: usize,
+}
+
+impl Counter {
+    fn new(max: usize) -> Counter {
+        Counter::with_config(0, max, 1)
+    }
+    
+    fn with_config(start: usize, max: usize, step: usize) -> Counter {
+        Counter { current: start, max, step }
+    }
+}
+
+impl Iterator for Counter {
+    type Item = usize;
+    
+    fn next(&mut self) -> Option<Self::Item> {
+        // 步长为0会无限产出同一个值，直接结束迭代
+        if self.step == 0 || self.current >= self.max {
+            return None;
+        }
+        let current = self.current;
+        // 溢出时说明已越过max，后续不再产出
+        self.current = current.checked_add(self.step).unwrap_or(self.max);
+        Some(current)
+    }
+}
+
+// 按边界条件把迭代器切分成多段
+// 每遇到一个边界元素就结束当前段；输入结束时，非空的剩余部分作为最后一段产出
+// 连续的边界会产生空段，空输入不产出任何段
+fn split_when<I, F>(iter: I, is_boundary: F) -> SplitWhen<I::IntoIter, F>
+where
+    I: IntoIterator,
+    F: FnMut(&I::Item) -> bool,
+{
+    SplitWhen {
+        iter: iter.into_iter(),
+        is_boundary,
+        keep_boundary: false,
+        state: SplitState::Collecting,
+    }
+}
+
+enum SplitState {
+    Collecting,
+    Finished,
+}
+
+struct SplitWhen<I, F> {
+    iter: I,
+    is_boundary: F,
+    keep_boundary: bool,
+    state: SplitState,
+}
+
+impl<I, F> SplitWhen<I, F> {
+    // 边界元素归入前一段的末尾（默认丢弃）
+    fn keep_boundary(mut self) -> Self {
+        self.keep_boundary = true;
+        self
+    }
+}
+
+impl<I, F> Iterator for SplitWhen<I, F>
+where
+    I: Iterator,
+    F: FnMut(&I::Item) -> bool,
+{
+    type Item = Vec<I::Item>;
+    
+    fn next(&mut self) -> Option<Self::Item> {
+        if let SplitState::Finished = self.state {
+            return None;
+        }
+        
+        let mut segment = Vec::new();
+        loop {
+            match self.iter.next() {
+                Some(item) => {
+                    if (self.is_boundary)(&item) {
+                        if self.keep_boundary {
+                            segment.push(item);
+                        }
+                        return Some(segment);
+                    }
+                    segment.push(item);
+                }
+                None => {
+                    self.state = SplitState::Finished;
+                    return if segment.is_empty() { None } else { Some(segment) };
+                }
+            }
+        }
+    }
+}
+
 fn iterators_and_adapters() {
     println!("\n=== 迭代器和适配器示例 ===");
     
@@ -292,31 +651,6 @@ fn iterators_and_adapters() {
     println!("向量总和: {}", sum);
     
     // 自定义迭代器
-    struct Counter {
-        current: usize,
-        max: usize,
-    }
-    
-    impl Counter {
-        fn new(max: usize) -> Counter {
-            Counter { current: 0, max }
-        }
-    }
-    
-    impl Iterator for Counter {
-        type Item = usize;
-        
-        fn next(&mut self) -> Option<Self::Item> {
-            if self.current < self.max {
-                let current = self.current;
-                self.current += 1;
-                Some(current)
-            } else {
-                None
-            }
-        }
-    }
-    
     let mut counter = Counter::new(5);
     for num in counter {
         println!("计数器: {}", num);
@@ -332,6 +666,18 @@ fn iterators_and_adapters() {
     
     println!("复杂迭代器操作结果: {:?}", result);
     
+    // 自定义起点和步长
+    let evens: Vec<_> = Counter::with_config(0, 10, 2).collect();
+    println!("步长为2的计数器: {:?}", evens);
+    
+    // 按边界分段
+    let segments: Vec<Vec<i32>> = split_when(vec![1, 2, 0, 3, 0, 4, 5], |&x| x == 0).collect();
+    println!("按0分段: {:?}", segments);
+    let with_boundary: Vec<Vec<i32>> = split_when(vec![1, 2, 0, 3], |&x| x == 0)
+        .keep_boundary()
+        .collect();
+    println!("保留边界元素: {:?}", with_boundary);
+    
     // 性能对比
     iterator_performance_comparison();
 }
@@ -559,52 +905,112 @@ fn associated_types_vs_generics() {
 fn type_level_programming() {
     println!("\n=== 类型级编程示例 ===");
     
-    // 幻影类型
-    use std::marker::PhantomData;
+    // 幻影类型：单位只存在于类型中，不占用运行时空间
+    let distance_m = Measurement::<Meter>::new(1500.0);
+    let distance_km = distance_m.into_kilometers();
     
-    struct Measurement<Unit> {
-        value: f64,
-        _unit: PhantomData<Unit>,
-    }
+    println!("距离: {} 米 = {} 千米", 1500.0, distance_km.value());
+    println!("换算回米: {}", distance_km.into_meters().value());
     
-    struct Meter;
-    struct Kilometer;
+    // 通用换算和同单位运算
+    let run = Measurement::<Mile>::new(3.0) + Measurement::<Mile>::new(0.1);
+    println!("跑步距离: {} 英里 = {:.1} 米", run.value(), run.convert::<Meter>().value());
+    let height = Measurement::<Foot>::new(10.0) - Measurement::<Foot>::new(4.0);
+    println!("高度差: {} 英尺 = {:.4} 千米", height.value(), height.convert::<Kilometer>().value());
     
-    impl<Unit> Measurement<Unit> {
-        fn new(value: f64) -> Self {
-            Measurement {
-                value,
-                _unit: PhantomData,
-            }
-        }
-        
-        fn value(&self) -> f64 {
-            self.value
-        }
-    }
+    // 类型状态模式
+    type_state_pattern();
     
-    impl Measurement<Meter> {
-        fn to_kilometers(self) -> Measurement<Kilometer> {
-            Measurement::new(self.value / 1000.0)
-        }
+    // 编译时计算
+    compile_time_computation();
+}
+
+// 长度单位：以米作为基准单位
+trait Unit {
+    // 一个该单位等于多少米
+    const METERS_PER_UNIT: f64;
+    
+    fn to_base(value: f64) -> f64 {
+        value * Self::METERS_PER_UNIT
     }
     
-    impl Measurement<Kilometer> {
-        fn to_meters(self) -> Measurement<Meter> {
-            Measurement::new(self.value * 1000.0)
+    fn from_base(meters: f64) -> f64 {
+        meters / Self::METERS_PER_UNIT
+    }
+}
+
+struct Meter;
+struct Kilometer;
+struct Mile;
+struct Foot;
+
+impl Unit for Meter {
+    const METERS_PER_UNIT: f64 = 1.0;
+}
+
+impl Unit for Kilometer {
+    const METERS_PER_UNIT: f64 = 1000.0;
+}
+
+impl Unit for Mile {
+    const METERS_PER_UNIT: f64 = 1609.344;
+}
+
+impl Unit for Foot {
+    const METERS_PER_UNIT: f64 = 0.3048;
+}
+
+// 带单位的数值，不同单位的数值属于不同类型
+struct Measurement<U: Unit> {
+    value: f64,
+    _unit: std::marker::PhantomData<U>,
+}
+
+impl<U: Unit> Measurement<U> {
+    fn new(value: f64) -> Self {
+        Measurement {
+            value,
+            _unit: std::marker::PhantomData,
         }
     }
     
-    let distance_m = Measurement::<Meter>::new(1500.0);
-    let distance_km = distance_m.to_kilometers();
+    fn value(&self) -> f64 {
+        self.value
+    }
     
-    println!("距离: {} 米 = {} 千米", 1500.0, distance_km.value());
+    // 通过基准单位换算到任意单位
+    fn convert<V: Unit>(self) -> Measurement<V> {
+        Measurement::new(V::from_base(U::to_base(self.value)))
+    }
+}
+
+impl Measurement<Meter> {
+    fn into_kilometers(self) -> Measurement<Kilometer> {
+        self.convert()
+    }
+}
+
+impl Measurement<Kilometer> {
+    fn into_meters(self) -> Measurement<Meter> {
+        self.convert()
+    }
+}
+
+// 只有相同单位才能相加减，米 + 英尺 无法通过编译
+impl<U: Unit> Add for Measurement<U> {
+    type Output = Measurement<U>;
     
-    // 类型状态模式
-    type_state_pattern();
+    fn add(self, other: Self) -> Self::Output {
+        Measurement::new(self.value + other.value)
+    }
+}
+
+impl<U: Unit> std::ops::Sub for Measurement<U> {
+    type Output = Measurement<U>;
     
-    // 编译时计算
-    compile_time_computation();
+    fn sub(self, other: Self) -> Self::Output {
+        Measurement::new(self.value - other.value)
+    }
 }
 
 fn type_state_pattern() {
@@ -653,31 +1059,60 @@ fn type_state_pattern() {
     // door.walk_through();        // 编译错误：门已关闭
 }
 
-fn compile_time_computation() {
-    println!("\n--- 编译时计算 ---");
+// 常量泛型数组，长度N是类型的一部分
+struct Array<T, const N: usize> {
+    data: [T; N],
+}
+
+impl<T, const N: usize> Array<T, N> {
+    fn new(data: [T; N]) -> Self {
+        Array { data }
+    }
     
-    // 常量泛型
-    struct Array<T, const N: usize> {
-        data: [T; N],
+    fn len(&self) -> usize {
+        N
     }
     
-    impl<T, const N: usize> Array<T, N> {
-        fn new(data: [T; N]) -> Self {
-            Array { data }
-        }
-        
-        fn len(&self) -> usize {
-            N
-        }
-        
-        fn get(&self, index: usize) -> Option<&T> {
-            self.data.get(index)
+    fn get(&self, index: usize) -> Option<&T> {
+        self.data.get(index)
+    }
+    
+    fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.data.iter()
+    }
+    
+    // 逐元素转换，结果的长度在编译期仍然是N
+    fn map<U, F: FnMut(T) -> U>(self, f: F) -> Array<U, N> {
+        Array {
+            data: self.data.map(f),
         }
     }
+}
+
+impl<T, const N: usize> IntoIterator for Array<T, N> {
+    type Item = T;
+    type IntoIter = std::array::IntoIter<T, N>;
     
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.into_iter()
+    }
+}
+
+fn compile_time_computation() {
+    println!("\n--- 编译时计算 ---");
+    
+    // 常量泛型
     let arr = Array::new([1, 2, 3, 4, 5]);
     println!("数组长度: {}", arr.len());
     println!("第一个元素: {:?}", arr.get(0));
+    println!("元素之和: {}", arr.iter().sum::<i32>());
+    
+    // map保持编译期长度不变
+    let labels: Array<String, 5> = arr.map(|x| format!("#{}", x));
+    for label in labels {
+        print!("{} ", label);
+    }
+    println!();
     
     // 类型级数字
     trait TypeNum {
@@ -777,6 +1212,21 @@ mod tests {
         assert_eq!(w.len(), 1);
     }
     
+    #[test]
+    fn test_wrapper_collect_and_push() {
+        let mut w: Wrapper = vec!["a".to_string(), "b".to_string()].into_iter().collect();
+        assert_eq!(w.to_string(), "Wrapper(a, b)");
+        
+        w.push("c");
+        w.push(String::from("d"));
+        assert_eq!(w.len(), 4);
+        assert_eq!(w.to_string(), "Wrapper(a, b, c, d)");
+        
+        // DerefMut 允许直接调用Vec的可变方法
+        w.retain(|s| s != "b");
+        assert_eq!(w.to_string(), "Wrapper(a, c, d)");
+    }
+    
     #[test]
     fn test_point_operations() {
         let p1 = Point { x: 1.0, y: 2.0 };
@@ -794,13 +1244,77 @@ mod tests {
         assert_eq!(sum, 10);  // 0+1+2+3+4 = 10
     }
     
+    #[test]
+    fn test_counter_with_step() {
+        let values: Vec<usize> = Counter::with_config(1, 10, 2).collect();
+        assert_eq!(values, vec![1, 3, 5, 7, 9]);
+    }
+    
+    #[test]
+    fn test_counter_zero_step() {
+        assert_eq!(Counter::with_config(0, 10, 0).next(), None);
+    }
+    
+    #[test]
+    fn test_counter_empty_range() {
+        assert_eq!(Counter::with_config(5, 5, 1).count(), 0);
+        assert_eq!(Counter::with_config(8, 3, 1).count(), 0);
+    }
+    
+    #[test]
+    fn test_split_when_by_predicate() {
+        let segments: Vec<Vec<i32>> = split_when(vec![1, 2, 0, 3, 4, 0, 5], |&x| x == 0).collect();
+        assert_eq!(segments, vec![vec![1, 2], vec![3, 4], vec![5]]);
+        
+        let kept: Vec<Vec<i32>> = split_when(vec![1, 0, 2, 0], |&x| x == 0)
+            .keep_boundary()
+            .collect();
+        assert_eq!(kept, vec![vec![1, 0], vec![2, 0]]);
+    }
+    
+    #[test]
+    fn test_split_when_consecutive_boundaries() {
+        let segments: Vec<Vec<char>> = split_when("a,,b".chars(), |&c| c == ',').collect();
+        assert_eq!(segments, vec![vec!['a'], vec![], vec!['b']]);
+    }
+    
+    #[test]
+    fn test_split_when_trailing_segment_and_empty_input() {
+        let segments: Vec<Vec<i32>> = split_when(vec![0, 1, 2], |&x| x == 0).collect();
+        assert_eq!(segments, vec![vec![], vec![1, 2]]);
+        
+        let empty: Vec<Vec<i32>> = split_when(Vec::new(), |&x: &i32| x == 0).collect();
+        assert!(empty.is_empty());
+    }
+    
     #[test]
     fn test_measurement_conversion() {
         let distance = Measurement::<Meter>::new(1000.0);
-        let km_distance = distance.to_kilometers();
+        let km_distance = distance.into_kilometers();
         assert_eq!(km_distance.value(), 1.0);
     }
     
+    #[test]
+    fn test_measurement_generic_convert() {
+        let feet = Measurement::<Mile>::new(1.0).convert::<Foot>();
+        assert!((feet.value() - 5280.0).abs() < 1e-9);
+        
+        let km = Measurement::<Foot>::new(3280.84).convert::<Kilometer>();
+        assert!((km.value() - 1.0).abs() < 1e-6);
+    }
+    
+    #[test]
+    fn test_measurement_same_unit_arithmetic() {
+        let total = Measurement::<Meter>::new(1.5) + Measurement::<Meter>::new(2.5);
+        assert_eq!(total.value(), 4.0);
+        
+        let rest = total - Measurement::<Meter>::new(1.0);
+        assert_eq!(rest.value(), 3.0);
+        
+        // 下面的代码无法编译：Measurement<Meter> 只实现了与 Measurement<Meter> 的加法
+        // let _ = Measurement::<Meter>::new(1.0) + Measurement::<Foot>::new(1.0);
+    }
+    
     #[test]
     fn test_array_const_generic() {
         let arr = Array::new([1, 2, 3]);
@@ -808,6 +1322,160 @@ mod tests {
         assert_eq!(arr.get(0), Some(&1));
     }
     
+    #[test]
+    fn test_pipe_three_stages() {
+        fn add_ten(x: i32) -> i32 {
+            x + 10
+        }
+        
+        fn times_two(x: i32) -> i64 {
+            x as i64 * 2
+        }
+        
+        fn to_string(x: i64) -> String {
+            x.to_string()
+        }
+        
+        let pipeline = Pipe::new(add_ten).then(times_two).then(to_string).build();
+        assert_eq!(pipeline(5), "30");
+        assert_eq!(pipeline(-10), "0");
+    }
+    
+    #[test]
+    fn test_array_map_and_iter() {
+        let squares = Array::new([1, 2, 3]).map(|x| x * x);
+        assert_eq!(squares.len(), 3);
+        assert_eq!(squares.iter().copied().collect::<Vec<_>>(), vec![1, 4, 9]);
+        assert_eq!(squares.iter().sum::<i32>(), 14);
+        
+        // 按值迭代，元素所有权被移出
+        let words = Array::new([1, 2]).map(|n| n.to_string());
+        let owned: Vec<String> = words.into_iter().collect();
+        assert_eq!(owned, vec!["1".to_string(), "2".to_string()]);
+    }
+    
+    #[test]
+    fn test_cacher_per_argument() {
+        use std::cell::Cell;
+        
+        let calls = Cell::new(0);
+        let mut cacher = Cacher::new(|x: u32| {
+            calls.set(calls.get() + 1);
+            x * 2
+        });
+        
+        assert_eq!(cacher.value(1), 2);
+        assert_eq!(cacher.value(2), 4);
+        assert_eq!(cacher.value(1), 2);
+        assert_eq!(calls.get(), 2);
+    }
+    
+    #[test]
+    fn test_memoize_caches_results() {
+        use std::rc::Rc;
+        use std::cell::Cell;
+        
+        let calls = Rc::new(Cell::new(0));
+        let counter = Rc::clone(&calls);
+        let square = Memoize::new(move |x: &u64| {
+            counter.set(counter.get() + 1);
+            x * x
+        });
+        
+        assert_eq!(square.get(&3), 9);
+        assert_eq!(square.get(&3), 9);
+        assert_eq!(square.get(&4), 16);
+        assert_eq!(square.get(&3), 9);
+        assert_eq!(calls.get(), 2);
+        assert_eq!(square.len(), 2);
+    }
+    
+    #[test]
+    fn test_memoize_evicts_oldest() {
+        use std::rc::Rc;
+        use std::cell::Cell;
+        
+        let calls = Rc::new(Cell::new(0));
+        let counter = Rc::clone(&calls);
+        let double = Memoize::with_capacity(
+            move |x: &i32| {
+                counter.set(counter.get() + 1);
+                x * 2
+            },
+            2,
+        );
+        
+        double.get(&1);
+        double.get(&2);
+        double.get(&3); // 淘汰1
+        assert_eq!(double.len(), 2);
+        assert_eq!(calls.get(), 3);
+        
+        double.get(&3);
+        double.get(&2);
+        assert_eq!(calls.get(), 3);
+        
+        double.get(&1); // 需要重新计算
+        assert_eq!(calls.get(), 4);
+    }
+    
+    #[test]
+    fn test_concurrent_memoize_single_flight() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Barrier;
+        
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        let memo = Arc::new(ConcurrentMemoize::new(move |key: &String| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            key.len()
+        }));
+        
+        // 让8个线程尽量同时请求同一个键
+        let barrier = Arc::new(Barrier::new(8));
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let memo = Arc::clone(&memo);
+                let barrier = Arc::clone(&barrier);
+                std::thread::spawn(move || {
+                    barrier.wait();
+                    memo.get(&"相同的键".to_string())
+                })
+            })
+            .collect();
+        
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), 12);
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        
+        // 其他键单独计算
+        assert_eq!(memo.get(&"ab".to_string()), 2);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+    
+    #[test]
+    fn test_concurrent_memoize_recovers_from_panic() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        let memo = Arc::new(ConcurrentMemoize::new(move |x: &i32| {
+            if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                panic!("第一次计算失败");
+            }
+            x + 1
+        }));
+        
+        let first = Arc::clone(&memo);
+        assert!(std::thread::spawn(move || first.get(&1)).join().is_err());
+        
+        // 失败的条目被移除，下一次调用重新计算
+        assert_eq!(memo.get(&1), 2);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+    
     #[test]
     fn test_examples() {
         advanced_types();